@@ -5,16 +5,8 @@
 (jp 'rom0)
 (db 0x00)
 
-; logo
-(db 0xCE 0xED 0x66 0x66 0xCC 0x0D 0x00 0x0B 0x03 0x73 0x00 0x83 0x00 0x0C 0x00 0x0D)
-(db 0x00 0x08 0x11 0x1F 0x88 0x89 0x00 0x0E 0xDC 0xCC 0x6E 0xE6 0xDD 0xDD 0xD9 0x99)
-(db 0xBB 0xBB 0x67 0x63 0x6E 0x0E 0xEC 0xCC 0xDD 0xDC 0x99 0x9F 0xBB 0xB9 0x33 0x3E)
-
-(db 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00)
-(db 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00 0x00)
-
-; checksums
-(db 0xE7 0x01 0x98)
+; logo, title and checksums
+(gb-header)
 
 
 (section .rom0)
@@ -0,0 +1,133 @@
+#[cfg(test)]
+#[path = "./gb_test.rs"]
+mod gb_test;
+
+/// The cartridge header follows the entry point at 0x0100 and ends
+/// before 0x0150. The boot ROM only starts a cartridge with this logo
+/// and a valid header checksum.
+pub const LOGO_OFFSET: usize = 0x104;
+pub const TITLE_OFFSET: usize = 0x134;
+pub const CGB_FLAG_OFFSET: usize = 0x143;
+pub const NEW_LICENSEE_OFFSET: usize = 0x144;
+pub const SGB_FLAG_OFFSET: usize = 0x146;
+pub const CARTRIDGE_TYPE_OFFSET: usize = 0x147;
+pub const ROM_SIZE_OFFSET: usize = 0x148;
+pub const RAM_SIZE_OFFSET: usize = 0x149;
+pub const OLD_LICENSEE_OFFSET: usize = 0x14B;
+pub const HEADER_CHECKSUM_OFFSET: usize = 0x14D;
+pub const GLOBAL_CHECKSUM_OFFSET: usize = 0x14E;
+pub const HEADER_END: usize = 0x150;
+
+/// The longest title, the last title byte is the CGB flag on a CGB.
+pub const MAX_TITLE_LENGTH: usize = 16;
+
+/// The old licensee code that refers to the new licensee code.
+const USE_NEW_LICENSEE: u8 = 0x33;
+/// The SGB flag of a game with SGB functions.
+const SGB_SUPPORTED: u8 = 0x03;
+
+pub const LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+/// The publisher of the game, newer games use two ASCII characters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Licensee {
+    Old(u8),
+    New([u8; 2]),
+}
+
+/// The cartridge header from the logo to the checksums. The checksums
+/// are computed from the whole ROM, see fix_checksums.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub title: String,
+    /// 0x80 for a game that also runs on a DMG, 0xC0 for CGB only
+    pub cgb: u8,
+    pub sgb: bool,
+    pub cartridge_type: u8,
+    pub rom_size: u8,
+    pub ram_size: u8,
+    pub licensee: Licensee,
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Header {
+            title: String::new(),
+            cgb: 0,
+            sgb: false,
+            cartridge_type: 0,
+            rom_size: 0,
+            ram_size: 0,
+            licensee: Licensee::Old(0),
+        }
+    }
+}
+
+impl Header {
+    /// The header bytes from LOGO_OFFSET to HEADER_END, the checksums are 0.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let max_title_length = if self.cgb == 0 {
+            MAX_TITLE_LENGTH
+        } else {
+            MAX_TITLE_LENGTH - 1
+        };
+        if self.title.len() > max_title_length || !self.title.is_ascii() {
+            return Err(format!(
+                "title must be at most {} ASCII characters: {:?}",
+                max_title_length, self.title
+            ));
+        }
+
+        let mut bytes = vec![0; HEADER_END - LOGO_OFFSET];
+        let mut write = |offset: usize, data: &[u8]| {
+            let at = offset - LOGO_OFFSET;
+            bytes[at..at + data.len()].copy_from_slice(data);
+        };
+        write(LOGO_OFFSET, &LOGO);
+        write(TITLE_OFFSET, self.title.as_bytes());
+        if self.cgb != 0 {
+            write(CGB_FLAG_OFFSET, &[self.cgb]);
+        }
+        match &self.licensee {
+            Licensee::Old(code) => write(OLD_LICENSEE_OFFSET, &[*code]),
+            Licensee::New(code) => {
+                write(NEW_LICENSEE_OFFSET, code);
+                write(OLD_LICENSEE_OFFSET, &[USE_NEW_LICENSEE]);
+            }
+        }
+        if self.sgb {
+            write(SGB_FLAG_OFFSET, &[SGB_SUPPORTED]);
+        }
+        write(CARTRIDGE_TYPE_OFFSET, &[self.cartridge_type]);
+        write(ROM_SIZE_OFFSET, &[self.rom_size]);
+        write(RAM_SIZE_OFFSET, &[self.ram_size]);
+        Ok(bytes)
+    }
+}
+
+/// Writes the header checksum and the global checksum into the ROM. The
+/// global checksum is not checked by the boot ROM, it is written big endian.
+pub fn fix_checksums(rom: &mut [u8]) -> Result<(), String> {
+    if rom.len() < HEADER_END {
+        return Err(format!(
+            "the rom of 0x{:X} bytes ends before the header end 0x{:04X}",
+            rom.len(),
+            HEADER_END
+        ));
+    }
+    rom[HEADER_CHECKSUM_OFFSET] = rom[TITLE_OFFSET..HEADER_CHECKSUM_OFFSET]
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_sub(*b).wrapping_sub(1));
+
+    let global = rom
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != GLOBAL_CHECKSUM_OFFSET && *i != GLOBAL_CHECKSUM_OFFSET + 1)
+        .fold(0u16, |sum, (_, b)| sum.wrapping_add(*b as u16));
+    rom[GLOBAL_CHECKSUM_OFFSET..HEADER_END].copy_from_slice(&global.to_be_bytes());
+    Ok(())
+}
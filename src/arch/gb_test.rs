@@ -0,0 +1,84 @@
+use crate::arch::gb::{
+    CGB_FLAG_OFFSET, HEADER_END, Header, LOGO, LOGO_OFFSET, Licensee, TITLE_OFFSET, fix_checksums,
+};
+
+#[test]
+fn test_header_to_bytes() -> Result<(), String> {
+    let bytes = Header::default().to_bytes()?;
+    assert_eq!(bytes.len(), HEADER_END - LOGO_OFFSET);
+    assert_eq!(bytes[..LOGO.len()], LOGO);
+    assert!(bytes[LOGO.len()..].iter().all(|b| *b == 0));
+
+    let header = Header {
+        title: "PSY".to_string(),
+        cgb: 0x80,
+        sgb: true,
+        cartridge_type: 0x1B,
+        rom_size: 0x02,
+        ram_size: 0x03,
+        licensee: Licensee::New(*b"01"),
+    };
+    let bytes = header.to_bytes()?;
+    let at = TITLE_OFFSET - LOGO_OFFSET;
+    assert_eq!(
+        bytes[at..],
+        [
+            b'P', b'S', b'Y', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,    // title
+            0x80, // CGB flag
+            b'0', b'1', // new licensee
+            0x03, // SGB flag
+            0x1B, 0x02, 0x03, // cartridge type, ROM and RAM size
+            0x00, // destination
+            0x33, // old licensee, the new one is used
+            0x00, // version
+            0x00, 0x00, 0x00, // checksums
+        ]
+    );
+
+    let header = Header {
+        title: "A DMG GAME TITLE".to_string(),
+        licensee: Licensee::Old(0x01),
+        ..Header::default()
+    };
+    let bytes = header.to_bytes()?;
+    assert_eq!(bytes[CGB_FLAG_OFFSET - LOGO_OFFSET], b'E');
+    assert_eq!(bytes[0x14B - LOGO_OFFSET], 0x01);
+
+    let header = Header {
+        title: "A CGB GAME TITLE".to_string(),
+        cgb: 0xC0,
+        ..Header::default()
+    };
+    assert_eq!(
+        header.to_bytes().unwrap_err(),
+        "title must be at most 15 ASCII characters: \"A CGB GAME TITLE\""
+    );
+    Ok(())
+}
+
+#[test]
+fn test_fix_checksums() -> Result<(), String> {
+    let mut rom = vec![0; 0x200];
+    rom[LOGO_OFFSET..HEADER_END].copy_from_slice(&Header::default().to_bytes()?);
+    rom[0x150] = 0xFF;
+    fix_checksums(&mut rom)?;
+    let logo_sum: u16 = LOGO.iter().map(|b| *b as u16).sum();
+    assert_eq!(rom[0x14D], 0xE7);
+    assert_eq!(
+        u16::from_be_bytes([rom[0x14E], rom[0x14F]]),
+        logo_sum + 0xE7 + 0xFF
+    );
+
+    // the old checksums are not part of the global checksum
+    fix_checksums(&mut rom)?;
+    assert_eq!(
+        u16::from_be_bytes([rom[0x14E], rom[0x14F]]),
+        logo_sum + 0xE7 + 0xFF
+    );
+
+    assert_eq!(
+        fix_checksums(&mut [0; 0x14F]).unwrap_err(),
+        "the rom of 0x14F bytes ends before the header end 0x0150"
+    );
+    Ok(())
+}
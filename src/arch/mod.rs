@@ -1 +1,2 @@
+pub mod gb;
 pub mod sm83;
@@ -15,9 +15,16 @@ pub const REG_C: &str = "c";
 
 /// carry flag
 pub const FLAG_C: &str = "c";
+/// not carry flag
+pub const FLAG_NC: &str = "nc";
+/// zero flag
+pub const FLAG_Z: &str = "z";
 /// not zero flag
 pub const FLAG_NZ: &str = "nz";
 
+/// All condition flags of the conditional jp, jr, call and ret.
+pub const FLAGS: [&str; 4] = [FLAG_NZ, FLAG_Z, FLAG_NC, FLAG_C];
+
 #[derive(Debug)]
 pub struct Sm83Instr {
     pub mnemonic: &'static str,
@@ -25,6 +32,9 @@ pub struct Sm83Instr {
     pub arg_bytes: usize,
 }
 
+/// An instruction following the 0xCB prefix. Prefix instructions
+/// have no arguments, the instruction is always two bytes long.
+#[derive(Debug)]
 pub struct Sm83PrefixInstr {
     pub mnemonic: &'static str,
     pub op_code: u8,
@@ -32,6 +42,7 @@ pub struct Sm83PrefixInstr {
 
 impl Sm83Instr {
     /// The length of the instruction in bytes.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.arg_bytes + 1 // +1 for the op code
     }
@@ -39,42 +50,96 @@ impl Sm83Instr {
     /// The (psy) text representation of the instruction.
     /// if the binary block is supplied it should start
     /// with the op_code of the instruction and contains the
-    /// arguments of the instruction.
+    /// arguments of the instruction. The argument placeholder
+    /// in the mnemonic (n8, n16, a8, a16, e8) is replaced with
+    /// the argument value. Without a binary block the placeholder
+    /// is kept.
     pub fn text(&self, binary: Option<&[u8]>) -> String {
+        self.format(binary, None)
+    }
+
+    /// The (psy) text representation of the instruction at the address.
+    /// Like text, but the e8 argument of jr is written as the target
+    /// address, the way the assembler reads it.
+    pub fn text_at(&self, binary: &[u8], address: u64) -> String {
+        self.format(Some(binary), Some(address))
+    }
+
+    fn format(&self, binary: Option<&[u8]>, address: Option<u64>) -> String {
+        let jump_from = match address {
+            Some(address) if self.mnemonic.starts_with("jr ") => {
+                Some(address as i64 + self.len() as i64)
+            }
+            _ => None,
+        };
         let mut str = String::new();
         str.push('(');
-        str.push_str(self.mnemonic);
-
-        let ip = 1;
-        if self.arg_bytes == 0 {
-            str.push(')');
-        } else if self.arg_bytes == 1
-            && let Some(data) = binary
-        {
-            if ip < data.len() {
-                str.push_str(&format!(" 0x{:x})", data[ip]));
-            } else {
-                str.push_str("ERR)"); //placeholder for now
-            }
-        } else if self.arg_bytes == 1 && binary.is_none() {
-            str.push_str(" n8)")
-        } else if self.arg_bytes == 2
-            && let Some(data) = binary
-        {
-            if ip + 1 < data.len() {
-                let a16 = u16::from_le_bytes([data[ip], data[ip + 1]]);
-                str.push_str(&format!(" 0x{:x})", a16));
-            } else {
-                str.push_str("ERR)"); //placeholder for now
+        match binary {
+            Some(data) => {
+                let args = data.get(1..self.len()).unwrap_or(&[]);
+                let parts: Vec<String> = self
+                    .mnemonic
+                    .split(' ')
+                    .map(|part| {
+                        let (open, rest) = match part.strip_prefix('(') {
+                            Some(rest) => ("(", rest),
+                            None => ("", part),
+                        };
+                        let (token, close) = match rest.strip_suffix(')') {
+                            Some(token) => (token, ")"),
+                            None => (rest, ""),
+                        };
+                        match arg_text(token, args, jump_from) {
+                            Some(value) => format!("{}{}{}", open, value, close),
+                            None => part.to_string(),
+                        }
+                    })
+                    .collect();
+                str.push_str(&parts.join(" "));
             }
-        } else if self.arg_bytes == 2 && binary.is_none() {
-            str.push_str(" n16)");
+            None => str.push_str(self.mnemonic),
         }
-
+        str.push(')');
         str
     }
 }
 
+impl Sm83PrefixInstr {
+    /// The length of the instruction in bytes, including the prefix.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        2
+    }
+
+    /// The (psy) text representation of the instruction.
+    pub fn text(&self) -> String {
+        format!("({})", self.mnemonic)
+    }
+}
+
+/// The text of the argument for an argument placeholder. None if
+/// the token is not a placeholder, "ERR" if the arguments are incomplete.
+/// An e8 argument is added to jump_from if given.
+fn arg_text(token: &str, args: &[u8], jump_from: Option<i64>) -> Option<String> {
+    let text = match token {
+        "n8" => args.first().map(|v| format!("0x{:x}", v)),
+        "a8" => args.first().map(|v| format!("0x{:x}", 0xFF00 | *v as u16)),
+        "e8" => args.first().map(|v| match jump_from {
+            Some(from) if from + (*v as i8 as i64) >= 0 => {
+                format!("0x{:x}", from + *v as i8 as i64)
+            }
+            Some(from) => format!("{}", from + *v as i8 as i64),
+            None => format!("{}", *v as i8),
+        }),
+        "n16" | "a16" => match args {
+            [lo, hi, ..] => Some(format!("0x{:x}", u16::from_le_bytes([*lo, *hi]))),
+            _ => None,
+        },
+        _ => return None,
+    };
+    Some(text.unwrap_or("ERR".to_string()))
+}
+
 /// A invalid instruction. Used to represent an instruction in
 /// disassemble that cannot be decoded.
 pub static INSTR_INVALID: Sm83Instr = Sm83Instr {
@@ -83,660 +148,2847 @@ pub static INSTR_INVALID: Sm83Instr = Sm83Instr {
     arg_bytes: 0,
 };
 
+// CPU CONTROL
 pub static INSTR_NOP: Sm83Instr = Sm83Instr {
-    mnemonic: "NOP",
+    mnemonic: "nop",
     op_code: 0x00,
     arg_bytes: 0,
 };
+pub static INSTR_STOP: Sm83Instr = Sm83Instr {
+    mnemonic: "stop",
+    op_code: 0x10,
+    arg_bytes: 1,
+};
+pub static INSTR_HALT: Sm83Instr = Sm83Instr {
+    mnemonic: "halt",
+    op_code: 0x76,
+    arg_bytes: 0,
+};
+pub static INSTR_DI: Sm83Instr = Sm83Instr {
+    mnemonic: "di",
+    op_code: 0xF3,
+    arg_bytes: 0,
+};
+pub static INSTR_EI: Sm83Instr = Sm83Instr {
+    mnemonic: "ei",
+    op_code: 0xFB,
+    arg_bytes: 0,
+};
 
 // RST
-pub static INSTR_RST_38: Sm83Instr = Sm83Instr {
-    mnemonic: "RST 0x38",
-    op_code: 0xFF,
+pub static INSTR_RST_00: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x00",
+    op_code: 0xC7,
+    arg_bytes: 0,
+};
+pub static INSTR_RST_08: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x08",
+    op_code: 0xCF,
+    arg_bytes: 0,
+};
+pub static INSTR_RST_10: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x10",
+    op_code: 0xD7,
+    arg_bytes: 0,
+};
+pub static INSTR_RST_18: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x18",
+    op_code: 0xDF,
+    arg_bytes: 0,
+};
+pub static INSTR_RST_20: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x20",
+    op_code: 0xE7,
     arg_bytes: 0,
 };
-
 pub static INSTR_RST_28: Sm83Instr = Sm83Instr {
-    mnemonic: "RST 0x28",
+    mnemonic: "rst 0x28",
     op_code: 0xEF,
     arg_bytes: 0,
 };
-
-pub static INSTR_DI: Sm83Instr = Sm83Instr {
-    mnemonic: "DI",
-    op_code: 0xF3,
+pub static INSTR_RST_30: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x30",
+    op_code: 0xF7,
     arg_bytes: 0,
 };
-
-pub static INSTR_EI: Sm83Instr = Sm83Instr {
-    mnemonic: "EI",
-    op_code: 0xFB,
+pub static INSTR_RST_38: Sm83Instr = Sm83Instr {
+    mnemonic: "rst 0x38",
+    op_code: 0xFF,
     arg_bytes: 0,
 };
 
 // JP
+pub static INSTR_JP_IF_NZ: Sm83Instr = Sm83Instr {
+    mnemonic: "jp #nz a16",
+    op_code: 0xC2,
+    arg_bytes: 2,
+};
 pub static INSTR_JP: Sm83Instr = Sm83Instr {
-    mnemonic: "JP",
+    mnemonic: "jp a16",
     op_code: 0xC3,
     arg_bytes: 2,
 };
-pub static INSTR_JP_IF_C: Sm83Instr = Sm83Instr {
-    mnemonic: "JP #C",
-    op_code: 0xDA,
+pub static INSTR_JP_IF_Z: Sm83Instr = Sm83Instr {
+    mnemonic: "jp #z a16",
+    op_code: 0xCA,
     arg_bytes: 2,
 };
-pub static INSTR_JP_IF_NZ: Sm83Instr = Sm83Instr {
-    mnemonic: "JP #NZ",
-    op_code: 0xC2,
+pub static INSTR_JP_IF_NC: Sm83Instr = Sm83Instr {
+    mnemonic: "jp #nc a16",
+    op_code: 0xD2,
+    arg_bytes: 2,
+};
+pub static INSTR_JP_IF_C: Sm83Instr = Sm83Instr {
+    mnemonic: "jp #c a16",
+    op_code: 0xDA,
     arg_bytes: 2,
 };
 pub static INSTR_JP_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "JP %hl",
+    mnemonic: "jp %hl",
     op_code: 0xE9,
     arg_bytes: 0,
 };
 
 // JR
 pub static INSTR_JR: Sm83Instr = Sm83Instr {
-    mnemonic: "JR",
+    mnemonic: "jr e8",
     op_code: 0x18,
     arg_bytes: 1,
 };
 pub static INSTR_JR_IF_NZ: Sm83Instr = Sm83Instr {
-    mnemonic: "JR #NZ",
+    mnemonic: "jr #nz e8",
     op_code: 0x20,
     arg_bytes: 1,
 };
+pub static INSTR_JR_IF_Z: Sm83Instr = Sm83Instr {
+    mnemonic: "jr #z e8",
+    op_code: 0x28,
+    arg_bytes: 1,
+};
+pub static INSTR_JR_IF_NC: Sm83Instr = Sm83Instr {
+    mnemonic: "jr #nc e8",
+    op_code: 0x30,
+    arg_bytes: 1,
+};
 pub static INSTR_JR_IF_C: Sm83Instr = Sm83Instr {
-    mnemonic: "JR #C",
+    mnemonic: "jr #c e8",
     op_code: 0x38,
     arg_bytes: 1,
 };
 
-// ADD
-pub static INSTR_ADD_A_A: Sm83Instr = Sm83Instr {
-    mnemonic: "ADD %a %a",
-    op_code: 0x87,
-    arg_bytes: 0,
+// CALL
+pub static INSTR_CALL_IF_NZ: Sm83Instr = Sm83Instr {
+    mnemonic: "call #nz a16",
+    op_code: 0xC4,
+    arg_bytes: 2,
 };
-pub static INSTR_ADD_HL_DE: Sm83Instr = Sm83Instr {
-    mnemonic: "ADD %hl %de",
-    op_code: 0x19,
-    arg_bytes: 0,
+pub static INSTR_CALL_IF_Z: Sm83Instr = Sm83Instr {
+    mnemonic: "call #z a16",
+    op_code: 0xCC,
+    arg_bytes: 2,
 };
-pub static INSTR_ADD_SP_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "ADD %sp n8",
-    op_code: 0xE8,
-    arg_bytes: 1,
+pub static INSTR_CALL: Sm83Instr = Sm83Instr {
+    mnemonic: "call a16",
+    op_code: 0xCD,
+    arg_bytes: 2,
+};
+pub static INSTR_CALL_IF_NC: Sm83Instr = Sm83Instr {
+    mnemonic: "call #nc a16",
+    op_code: 0xD4,
+    arg_bytes: 2,
+};
+pub static INSTR_CALL_IF_C: Sm83Instr = Sm83Instr {
+    mnemonic: "call #c a16",
+    op_code: 0xDC,
+    arg_bytes: 2,
 };
 
-// INC
-pub static INSTR_INC_A: Sm83Instr = Sm83Instr {
-    mnemonic: "INC %a",
-    op_code: 0x3C,
+// RET
+pub static INSTR_RET_IF_NZ: Sm83Instr = Sm83Instr {
+    mnemonic: "ret #nz",
+    op_code: 0xC0,
     arg_bytes: 0,
 };
-pub static INSTR_INC_C: Sm83Instr = Sm83Instr {
-    mnemonic: "INC %c",
-    op_code: 0x0C,
+pub static INSTR_RET_IF_Z: Sm83Instr = Sm83Instr {
+    mnemonic: "ret #z",
+    op_code: 0xC8,
     arg_bytes: 0,
 };
-pub static INSTR_INC_L: Sm83Instr = Sm83Instr {
-    mnemonic: "INC %l",
-    op_code: 0x2C,
+pub static INSTR_RET: Sm83Instr = Sm83Instr {
+    mnemonic: "ret",
+    op_code: 0xC9,
     arg_bytes: 0,
 };
-pub static INSTR_INC_BC: Sm83Instr = Sm83Instr {
-    mnemonic: "INC %bc",
-    op_code: 0x03,
+pub static INSTR_RET_IF_NC: Sm83Instr = Sm83Instr {
+    mnemonic: "ret #nc",
+    op_code: 0xD0,
     arg_bytes: 0,
 };
-pub static INSTR_INC_DE: Sm83Instr = Sm83Instr {
-    mnemonic: "INC %de",
-    op_code: 0x13,
+pub static INSTR_RET_IF_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ret #c",
+    op_code: 0xD8,
     arg_bytes: 0,
 };
-pub static INSTR_INC_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "INC %hl",
-    op_code: 0x23,
+pub static INSTR_RETI: Sm83Instr = Sm83Instr {
+    mnemonic: "reti",
+    op_code: 0xD9,
     arg_bytes: 0,
 };
 
-// DEC
-pub static INSTR_DEC_A: Sm83Instr = Sm83Instr {
-    mnemonic: "DEC %a",
-    op_code: 0x3D,
+// ADD
+pub static INSTR_ADD_HL_BC: Sm83Instr = Sm83Instr {
+    mnemonic: "add %hl %bc",
+    op_code: 0x09,
     arg_bytes: 0,
 };
-pub static INSTR_DEC_B: Sm83Instr = Sm83Instr {
-    mnemonic: "DEC %b",
-    op_code: 0x05,
+pub static INSTR_ADD_HL_DE: Sm83Instr = Sm83Instr {
+    mnemonic: "add %hl %de",
+    op_code: 0x19,
     arg_bytes: 0,
 };
-pub static INSTR_DEC_C: Sm83Instr = Sm83Instr {
-    mnemonic: "DEC %c",
-    op_code: 0x0D,
+pub static INSTR_ADD_HL_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "add %hl %hl",
+    op_code: 0x29,
     arg_bytes: 0,
 };
-pub static INSTR_DEC_BC: Sm83Instr = Sm83Instr {
-    mnemonic: "DEC %bc",
-    op_code: 0x0B,
+pub static INSTR_ADD_HL_SP: Sm83Instr = Sm83Instr {
+    mnemonic: "add %hl %sp",
+    op_code: 0x39,
     arg_bytes: 0,
 };
-pub static INSTR_DEC_DE: Sm83Instr = Sm83Instr {
-    mnemonic: "DEC %de",
-    op_code: 0x1B,
+pub static INSTR_ADD_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %b",
+    op_code: 0x80,
     arg_bytes: 0,
 };
-pub static INSTR_DEC_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "DEC %hl",
-    op_code: 0x2B,
+pub static INSTR_ADD_A_C: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %c",
+    op_code: 0x81,
     arg_bytes: 0,
 };
-// LD
-pub static INSTR_LD_TO_HL_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %hl",
-    op_code: 0x21,
-    arg_bytes: 2,
-};
-pub static INSTR_LD_TO_DE_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %de",
-    op_code: 0x11,
-    arg_bytes: 2,
+pub static INSTR_ADD_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %d",
+    op_code: 0x82,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_BC_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %bc",
-    op_code: 0x01,
-    arg_bytes: 2,
+pub static INSTR_ADD_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %e",
+    op_code: 0x83,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_SP_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %sp",
-    op_code: 0x31,
-    arg_bytes: 2,
+pub static INSTR_ADD_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %h",
+    op_code: 0x84,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a",
-    op_code: 0x3E,
-    arg_bytes: 1,
+pub static INSTR_ADD_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %l",
+    op_code: 0x85,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_DEREF_LABEL: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a ('lbl)",
-    op_code: 0xFA,
-    arg_bytes: 2,
+pub static INSTR_ADD_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a (%hl)",
+    op_code: 0x86,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_B_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %b n8",
-    op_code: 0x06,
-    arg_bytes: 1,
+pub static INSTR_ADD_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a %a",
+    op_code: 0x87,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_C_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %c n8",
-    op_code: 0x0E,
+pub static INSTR_ADD_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "add %a n8",
+    op_code: 0xC6,
     arg_bytes: 1,
 };
-pub static INSTR_LD_TO_D_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %d n8",
-    op_code: 0x16,
+pub static INSTR_ADD_SP_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "add %sp e8",
+    op_code: 0xE8,
     arg_bytes: 1,
 };
-pub static INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD (%hl)",
-    op_code: 0x36,
-    arg_bytes: 1,
+
+// ADC
+pub static INSTR_ADC_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %b",
+    op_code: 0x88,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_DEREF_DE_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD (%de) %a",
-    op_code: 0x12,
-    arg_bytes: 1,
+pub static INSTR_ADC_A_C: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %c",
+    op_code: 0x89,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_DEREF_HL_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD (%hl) %a",
-    op_code: 0x77,
+pub static INSTR_ADC_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %d",
+    op_code: 0x8A,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_DEREF_HL_DEC_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD (%hl -) %a",
-    op_code: 0x32,
+pub static INSTR_ADC_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %e",
+    op_code: 0x8B,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_DEREF_HL_INC_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD (%hl +) %a",
-    op_code: 0x22,
+pub static INSTR_ADC_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %h",
+    op_code: 0x8C,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_DEREF_LABEL_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD ('lbl) %a",
-    op_code: 0xEA,
-    arg_bytes: 2,
+pub static INSTR_ADC_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %l",
+    op_code: 0x8D,
+    arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a (%hl)",
-    op_code: 0x7E,
+pub static INSTR_ADC_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a (%hl)",
+    op_code: 0x8E,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_D_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %d (%hl)",
-    op_code: 0x56,
+pub static INSTR_ADC_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a %a",
+    op_code: 0x8F,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_E_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %e (%hl)",
-    op_code: 0x5E,
+pub static INSTR_ADC_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "adc %a n8",
+    op_code: 0xCE,
+    arg_bytes: 1,
+};
+
+// SUB
+pub static INSTR_SUB_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %b",
+    op_code: 0x90,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_DEREF_HL_INC: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a (%hl +)",
-    op_code: 0x2A,
+pub static INSTR_SUB_A_C: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %c",
+    op_code: 0x91,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_DEREF_DE: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a (%de)",
-    op_code: 0x1A,
+pub static INSTR_SUB_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %d",
+    op_code: 0x92,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_B: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a %b",
-    op_code: 0x78,
+pub static INSTR_SUB_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %e",
+    op_code: 0x93,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_A_FROM_C: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %a %c",
-    op_code: 0x79,
+pub static INSTR_SUB_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %h",
+    op_code: 0x94,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_B_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %b %a",
-    op_code: 0x47,
+pub static INSTR_SUB_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %l",
+    op_code: 0x95,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_C_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %c %a",
-    op_code: 0x4F,
+pub static INSTR_SUB_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a (%hl)",
+    op_code: 0x96,
     arg_bytes: 0,
 };
-pub static INSTR_LD_TO_E_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LD %e %a",
-    op_code: 0x5F,
+pub static INSTR_SUB_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a %a",
+    op_code: 0x97,
     arg_bytes: 0,
 };
-// LDH
-pub static INSTR_LDH_TO_IMMEDIATE_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LDH (0xFF00+n8) %a",
-    op_code: 0xE0,
+pub static INSTR_SUB_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "sub %a n8",
+    op_code: 0xD6,
     arg_bytes: 1,
 };
-pub static INSTR_LDH_TO_A_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "LDH %a (0xFF00+n8)",
-    op_code: 0xF0,
-    arg_bytes: 1,
+
+// SBC
+pub static INSTR_SBC_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %b",
+    op_code: 0x98,
+    arg_bytes: 0,
 };
-pub static INSTR_LDH_TO_DEREF_C_FROM_A: Sm83Instr = Sm83Instr {
-    mnemonic: "LDH (%c) %a",
-    op_code: 0xE2,
+pub static INSTR_SBC_A_C: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %c",
+    op_code: 0x99,
     arg_bytes: 0,
 };
-// CP
-pub static INSTR_CP_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "CP",
-    op_code: 0xFE,
-    arg_bytes: 1,
+pub static INSTR_SBC_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %d",
+    op_code: 0x9A,
+    arg_bytes: 0,
 };
-// OR
-pub static INSTR_OR_A_B: Sm83Instr = Sm83Instr {
-    mnemonic: "OR %a %b",
-    op_code: 0xB0,
+pub static INSTR_SBC_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %e",
+    op_code: 0x9B,
     arg_bytes: 0,
 };
-pub static INSTR_OR_A_C: Sm83Instr = Sm83Instr {
-    mnemonic: "OR %a %c",
-    op_code: 0xB1,
+pub static INSTR_SBC_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %h",
+    op_code: 0x9C,
+    arg_bytes: 0,
+};
+pub static INSTR_SBC_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %l",
+    op_code: 0x9D,
+    arg_bytes: 0,
+};
+pub static INSTR_SBC_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a (%hl)",
+    op_code: 0x9E,
+    arg_bytes: 0,
+};
+pub static INSTR_SBC_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a %a",
+    op_code: 0x9F,
     arg_bytes: 0,
 };
+pub static INSTR_SBC_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "sbc %a n8",
+    op_code: 0xDE,
+    arg_bytes: 1,
+};
 
 // AND
-pub static INSTR_AND_A_IMMEDIATE: Sm83Instr = Sm83Instr {
-    mnemonic: "AND %a n8",
-    op_code: 0xE6,
+pub static INSTR_AND_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a %b",
+    op_code: 0xA0,
     arg_bytes: 0,
 };
 pub static INSTR_AND_A_C: Sm83Instr = Sm83Instr {
-    mnemonic: "AND %a %c",
+    mnemonic: "and %a %c",
     op_code: 0xA1,
     arg_bytes: 0,
 };
+pub static INSTR_AND_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a %d",
+    op_code: 0xA2,
+    arg_bytes: 0,
+};
+pub static INSTR_AND_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a %e",
+    op_code: 0xA3,
+    arg_bytes: 0,
+};
+pub static INSTR_AND_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a %h",
+    op_code: 0xA4,
+    arg_bytes: 0,
+};
+pub static INSTR_AND_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a %l",
+    op_code: 0xA5,
+    arg_bytes: 0,
+};
+pub static INSTR_AND_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a (%hl)",
+    op_code: 0xA6,
+    arg_bytes: 0,
+};
+pub static INSTR_AND_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a %a",
+    op_code: 0xA7,
+    arg_bytes: 0,
+};
+pub static INSTR_AND_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "and %a n8",
+    op_code: 0xE6,
+    arg_bytes: 1,
+};
 
 // XOR
-pub static INSTR_XOR_A_A: Sm83Instr = Sm83Instr {
-    mnemonic: "XOR %a %a",
-    op_code: 0xAF,
+pub static INSTR_XOR_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a %b",
+    op_code: 0xA8,
     arg_bytes: 0,
 };
 pub static INSTR_XOR_A_C: Sm83Instr = Sm83Instr {
-    mnemonic: "XOR %a %c",
+    mnemonic: "xor %a %c",
     op_code: 0xA9,
     arg_bytes: 0,
 };
-// CPL
-pub static INSTR_CPL: Sm83Instr = Sm83Instr {
-    mnemonic: "CPL",
-    op_code: 0x2F,
+pub static INSTR_XOR_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a %d",
+    op_code: 0xAA,
     arg_bytes: 0,
 };
-// ROTATE
-pub static INSTR_RRCA: Sm83Instr = Sm83Instr {
-    mnemonic: "RRCA",
-    op_code: 0x0F,
+pub static INSTR_XOR_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a %e",
+    op_code: 0xAB,
     arg_bytes: 0,
 };
-// CALL
-pub static INSTR_CALL: Sm83Instr = Sm83Instr {
-    mnemonic: "CALL 'fn",
-    op_code: 0xCD,
+pub static INSTR_XOR_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a %h",
+    op_code: 0xAC,
+    arg_bytes: 0,
+};
+pub static INSTR_XOR_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a %l",
+    op_code: 0xAD,
+    arg_bytes: 0,
+};
+pub static INSTR_XOR_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a (%hl)",
+    op_code: 0xAE,
+    arg_bytes: 0,
+};
+pub static INSTR_XOR_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a %a",
+    op_code: 0xAF,
+    arg_bytes: 0,
+};
+pub static INSTR_XOR_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "xor %a n8",
+    op_code: 0xEE,
+    arg_bytes: 1,
+};
+
+// OR
+pub static INSTR_OR_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %b",
+    op_code: 0xB0,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_C: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %c",
+    op_code: 0xB1,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %d",
+    op_code: 0xB2,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %e",
+    op_code: 0xB3,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %h",
+    op_code: 0xB4,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %l",
+    op_code: 0xB5,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a (%hl)",
+    op_code: 0xB6,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a %a",
+    op_code: 0xB7,
+    arg_bytes: 0,
+};
+pub static INSTR_OR_A_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "or %a n8",
+    op_code: 0xF6,
+    arg_bytes: 1,
+};
+
+// CP
+pub static INSTR_CP_A_B: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %b",
+    op_code: 0xB8,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_C: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %c",
+    op_code: 0xB9,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_D: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %d",
+    op_code: 0xBA,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_E: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %e",
+    op_code: 0xBB,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_H: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %h",
+    op_code: 0xBC,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_L: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %l",
+    op_code: 0xBD,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a (%hl)",
+    op_code: 0xBE,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_A_A: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a %a",
+    op_code: 0xBF,
+    arg_bytes: 0,
+};
+pub static INSTR_CP_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "cp %a n8",
+    op_code: 0xFE,
+    arg_bytes: 1,
+};
+
+// INC
+pub static INSTR_INC_BC: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %bc",
+    op_code: 0x03,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_B: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %b",
+    op_code: 0x04,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_C: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %c",
+    op_code: 0x0C,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_DE: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %de",
+    op_code: 0x13,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_D: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %d",
+    op_code: 0x14,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_E: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %e",
+    op_code: 0x1C,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %hl",
+    op_code: 0x23,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_H: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %h",
+    op_code: 0x24,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_L: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %l",
+    op_code: 0x2C,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_SP: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %sp",
+    op_code: 0x33,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "inc (%hl)",
+    op_code: 0x34,
+    arg_bytes: 0,
+};
+pub static INSTR_INC_A: Sm83Instr = Sm83Instr {
+    mnemonic: "inc %a",
+    op_code: 0x3C,
+    arg_bytes: 0,
+};
+
+// DEC
+pub static INSTR_DEC_B: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %b",
+    op_code: 0x05,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_BC: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %bc",
+    op_code: 0x0B,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_C: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %c",
+    op_code: 0x0D,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_D: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %d",
+    op_code: 0x15,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_DE: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %de",
+    op_code: 0x1B,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_E: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %e",
+    op_code: 0x1D,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_H: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %h",
+    op_code: 0x25,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %hl",
+    op_code: 0x2B,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_L: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %l",
+    op_code: 0x2D,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "dec (%hl)",
+    op_code: 0x35,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_SP: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %sp",
+    op_code: 0x3B,
+    arg_bytes: 0,
+};
+pub static INSTR_DEC_A: Sm83Instr = Sm83Instr {
+    mnemonic: "dec %a",
+    op_code: 0x3D,
+    arg_bytes: 0,
+};
+
+// LD
+pub static INSTR_LD_TO_DEREF_BC_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%bc) %a",
+    op_code: 0x02,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b n8",
+    op_code: 0x06,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_A_FROM_DEREF_BC: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a (%bc)",
+    op_code: 0x0A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c n8",
+    op_code: 0x0E,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_DEREF_DE_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%de) %a",
+    op_code: 0x12,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d n8",
+    op_code: 0x16,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_A_FROM_DEREF_DE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a (%de)",
+    op_code: 0x1A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e n8",
+    op_code: 0x1E,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_DEREF_HL_INC_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl +) %a",
+    op_code: 0x22,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h n8",
+    op_code: 0x26,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_A_FROM_DEREF_HL_INC: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a (%hl +)",
+    op_code: 0x2A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l n8",
+    op_code: 0x2E,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_DEREF_HL_DEC_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl -) %a",
+    op_code: 0x32,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) n8",
+    op_code: 0x36,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_A_FROM_DEREF_HL_DEC: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a (%hl -)",
+    op_code: 0x3A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a n8",
+    op_code: 0x3E,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_B_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %b",
+    op_code: 0x40,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %c",
+    op_code: 0x41,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %d",
+    op_code: 0x42,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %e",
+    op_code: 0x43,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %h",
+    op_code: 0x44,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %l",
+    op_code: 0x45,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b (%hl)",
+    op_code: 0x46,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_B_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %b %a",
+    op_code: 0x47,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %b",
+    op_code: 0x48,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %c",
+    op_code: 0x49,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %d",
+    op_code: 0x4A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %e",
+    op_code: 0x4B,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %h",
+    op_code: 0x4C,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %l",
+    op_code: 0x4D,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c (%hl)",
+    op_code: 0x4E,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_C_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %c %a",
+    op_code: 0x4F,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %b",
+    op_code: 0x50,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %c",
+    op_code: 0x51,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %d",
+    op_code: 0x52,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %e",
+    op_code: 0x53,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %h",
+    op_code: 0x54,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %l",
+    op_code: 0x55,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d (%hl)",
+    op_code: 0x56,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_D_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %d %a",
+    op_code: 0x57,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %b",
+    op_code: 0x58,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %c",
+    op_code: 0x59,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %d",
+    op_code: 0x5A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %e",
+    op_code: 0x5B,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %h",
+    op_code: 0x5C,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %l",
+    op_code: 0x5D,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e (%hl)",
+    op_code: 0x5E,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_E_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %e %a",
+    op_code: 0x5F,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %b",
+    op_code: 0x60,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %c",
+    op_code: 0x61,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %d",
+    op_code: 0x62,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %e",
+    op_code: 0x63,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %h",
+    op_code: 0x64,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %l",
+    op_code: 0x65,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h (%hl)",
+    op_code: 0x66,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_H_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %h %a",
+    op_code: 0x67,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %b",
+    op_code: 0x68,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %c",
+    op_code: 0x69,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %d",
+    op_code: 0x6A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %e",
+    op_code: 0x6B,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %h",
+    op_code: 0x6C,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %l",
+    op_code: 0x6D,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l (%hl)",
+    op_code: 0x6E,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_L_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %l %a",
+    op_code: 0x6F,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %b",
+    op_code: 0x70,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %c",
+    op_code: 0x71,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %d",
+    op_code: 0x72,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %e",
+    op_code: 0x73,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %h",
+    op_code: 0x74,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %l",
+    op_code: 0x75,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_HL_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (%hl) %a",
+    op_code: 0x77,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_B: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %b",
+    op_code: 0x78,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %c",
+    op_code: 0x79,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_D: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %d",
+    op_code: 0x7A,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_E: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %e",
+    op_code: 0x7B,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_H: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %h",
+    op_code: 0x7C,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_L: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %l",
+    op_code: 0x7D,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_DEREF_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a (%hl)",
+    op_code: 0x7E,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_A_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a %a",
+    op_code: 0x7F,
+    arg_bytes: 0,
+};
+pub static INSTR_LD_TO_DEREF_LABEL_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (a16) %a",
+    op_code: 0xEA,
+    arg_bytes: 2,
+};
+pub static INSTR_LD_TO_A_FROM_DEREF_LABEL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %a (a16)",
+    op_code: 0xFA,
+    arg_bytes: 2,
+};
+
+// LD 16-bit
+pub static INSTR_LD_TO_BC_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %bc n16",
+    op_code: 0x01,
+    arg_bytes: 2,
+};
+pub static INSTR_LD_TO_DEREF_LABEL_FROM_SP: Sm83Instr = Sm83Instr {
+    mnemonic: "ld (a16) %sp",
+    op_code: 0x08,
     arg_bytes: 2,
 };
-pub static INSTR_CALL_IF_C: Sm83Instr = Sm83Instr {
-    mnemonic: "CALL #c 'fn",
-    op_code: 0xDC,
-    arg_bytes: 2,
+pub static INSTR_LD_TO_DE_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %de n16",
+    op_code: 0x11,
+    arg_bytes: 2,
+};
+pub static INSTR_LD_TO_HL_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %hl n16",
+    op_code: 0x21,
+    arg_bytes: 2,
+};
+pub static INSTR_LD_TO_SP_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %sp n16",
+    op_code: 0x31,
+    arg_bytes: 2,
+};
+pub static INSTR_LD_TO_HL_FROM_SP_OFFSET: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %hl %sp e8",
+    op_code: 0xF8,
+    arg_bytes: 1,
+};
+pub static INSTR_LD_TO_SP_FROM_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "ld %sp %hl",
+    op_code: 0xF9,
+    arg_bytes: 0,
+};
+
+// LDH
+pub static INSTR_LDH_TO_IMMEDIATE_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ldh (a8) %a",
+    op_code: 0xE0,
+    arg_bytes: 1,
+};
+pub static INSTR_LDH_TO_DEREF_C_FROM_A: Sm83Instr = Sm83Instr {
+    mnemonic: "ldh (%c) %a",
+    op_code: 0xE2,
+    arg_bytes: 0,
+};
+pub static INSTR_LDH_TO_A_FROM_IMMEDIATE: Sm83Instr = Sm83Instr {
+    mnemonic: "ldh %a (a8)",
+    op_code: 0xF0,
+    arg_bytes: 1,
+};
+pub static INSTR_LDH_TO_A_FROM_DEREF_C: Sm83Instr = Sm83Instr {
+    mnemonic: "ldh %a (%c)",
+    op_code: 0xF2,
+    arg_bytes: 0,
+};
+
+// DAA / CPL / SCF / CCF
+pub static INSTR_DAA: Sm83Instr = Sm83Instr {
+    mnemonic: "daa",
+    op_code: 0x27,
+    arg_bytes: 0,
+};
+pub static INSTR_CPL: Sm83Instr = Sm83Instr {
+    mnemonic: "cpl",
+    op_code: 0x2F,
+    arg_bytes: 0,
+};
+pub static INSTR_SCF: Sm83Instr = Sm83Instr {
+    mnemonic: "scf",
+    op_code: 0x37,
+    arg_bytes: 0,
+};
+pub static INSTR_CCF: Sm83Instr = Sm83Instr {
+    mnemonic: "ccf",
+    op_code: 0x3F,
+    arg_bytes: 0,
+};
+
+// ROTATE
+pub static INSTR_RLCA: Sm83Instr = Sm83Instr {
+    mnemonic: "rlca",
+    op_code: 0x07,
+    arg_bytes: 0,
+};
+pub static INSTR_RRCA: Sm83Instr = Sm83Instr {
+    mnemonic: "rrca",
+    op_code: 0x0F,
+    arg_bytes: 0,
+};
+pub static INSTR_RLA: Sm83Instr = Sm83Instr {
+    mnemonic: "rla",
+    op_code: 0x17,
+    arg_bytes: 0,
+};
+pub static INSTR_RRA: Sm83Instr = Sm83Instr {
+    mnemonic: "rra",
+    op_code: 0x1F,
+    arg_bytes: 0,
+};
+
+// PUSH
+pub static INSTR_PUSH_BC: Sm83Instr = Sm83Instr {
+    mnemonic: "push %bc",
+    op_code: 0xC5,
+    arg_bytes: 0,
+};
+pub static INSTR_PUSH_DE: Sm83Instr = Sm83Instr {
+    mnemonic: "push %de",
+    op_code: 0xD5,
+    arg_bytes: 0,
+};
+pub static INSTR_PUSH_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "push %hl",
+    op_code: 0xE5,
+    arg_bytes: 0,
+};
+pub static INSTR_PUSH_AF: Sm83Instr = Sm83Instr {
+    mnemonic: "push %af",
+    op_code: 0xF5,
+    arg_bytes: 0,
+};
+
+// POP
+pub static INSTR_POP_BC: Sm83Instr = Sm83Instr {
+    mnemonic: "pop %bc",
+    op_code: 0xC1,
+    arg_bytes: 0,
+};
+pub static INSTR_POP_DE: Sm83Instr = Sm83Instr {
+    mnemonic: "pop %de",
+    op_code: 0xD1,
+    arg_bytes: 0,
+};
+pub static INSTR_POP_HL: Sm83Instr = Sm83Instr {
+    mnemonic: "pop %hl",
+    op_code: 0xE1,
+    arg_bytes: 0,
+};
+pub static INSTR_POP_AF: Sm83Instr = Sm83Instr {
+    mnemonic: "pop %af",
+    op_code: 0xF1,
+    arg_bytes: 0,
+};
+
+// PREFIX
+pub static INSTR_PREFIX: Sm83Instr = Sm83Instr {
+    mnemonic: "prefix n8",
+    op_code: 0xCB,
+    arg_bytes: 1,
+};
+
+pub static INSTRUCTIONS: [&Sm83Instr; SM83_NUM_INSTRUCTIONS] = [
+    /*0x00*/ &INSTR_NOP,
+    /*0x01*/ &INSTR_LD_TO_BC_FROM_IMMEDIATE,
+    /*0x02*/ &INSTR_LD_TO_DEREF_BC_FROM_A,
+    /*0x03*/ &INSTR_INC_BC,
+    /*0x04*/ &INSTR_INC_B,
+    /*0x05*/ &INSTR_DEC_B,
+    /*0x06*/ &INSTR_LD_TO_B_FROM_IMMEDIATE,
+    /*0x07*/ &INSTR_RLCA,
+    /*0x08*/ &INSTR_LD_TO_DEREF_LABEL_FROM_SP,
+    /*0x09*/ &INSTR_ADD_HL_BC,
+    /*0x0A*/ &INSTR_LD_TO_A_FROM_DEREF_BC,
+    /*0x0B*/ &INSTR_DEC_BC,
+    /*0x0C*/ &INSTR_INC_C,
+    /*0x0D*/ &INSTR_DEC_C,
+    /*0x0E*/ &INSTR_LD_TO_C_FROM_IMMEDIATE,
+    /*0x0F*/ &INSTR_RRCA,
+    /*0x10*/ &INSTR_STOP,
+    /*0x11*/ &INSTR_LD_TO_DE_FROM_IMMEDIATE,
+    /*0x12*/ &INSTR_LD_TO_DEREF_DE_FROM_A,
+    /*0x13*/ &INSTR_INC_DE,
+    /*0x14*/ &INSTR_INC_D,
+    /*0x15*/ &INSTR_DEC_D,
+    /*0x16*/ &INSTR_LD_TO_D_FROM_IMMEDIATE,
+    /*0x17*/ &INSTR_RLA,
+    /*0x18*/ &INSTR_JR,
+    /*0x19*/ &INSTR_ADD_HL_DE,
+    /*0x1A*/ &INSTR_LD_TO_A_FROM_DEREF_DE,
+    /*0x1B*/ &INSTR_DEC_DE,
+    /*0x1C*/ &INSTR_INC_E,
+    /*0x1D*/ &INSTR_DEC_E,
+    /*0x1E*/ &INSTR_LD_TO_E_FROM_IMMEDIATE,
+    /*0x1F*/ &INSTR_RRA,
+    /*0x20*/ &INSTR_JR_IF_NZ,
+    /*0x21*/ &INSTR_LD_TO_HL_FROM_IMMEDIATE,
+    /*0x22*/ &INSTR_LD_TO_DEREF_HL_INC_FROM_A,
+    /*0x23*/ &INSTR_INC_HL,
+    /*0x24*/ &INSTR_INC_H,
+    /*0x25*/ &INSTR_DEC_H,
+    /*0x26*/ &INSTR_LD_TO_H_FROM_IMMEDIATE,
+    /*0x27*/ &INSTR_DAA,
+    /*0x28*/ &INSTR_JR_IF_Z,
+    /*0x29*/ &INSTR_ADD_HL_HL,
+    /*0x2A*/ &INSTR_LD_TO_A_FROM_DEREF_HL_INC,
+    /*0x2B*/ &INSTR_DEC_HL,
+    /*0x2C*/ &INSTR_INC_L,
+    /*0x2D*/ &INSTR_DEC_L,
+    /*0x2E*/ &INSTR_LD_TO_L_FROM_IMMEDIATE,
+    /*0x2F*/ &INSTR_CPL,
+    /*0x30*/ &INSTR_JR_IF_NC,
+    /*0x31*/ &INSTR_LD_TO_SP_FROM_IMMEDIATE,
+    /*0x32*/ &INSTR_LD_TO_DEREF_HL_DEC_FROM_A,
+    /*0x33*/ &INSTR_INC_SP,
+    /*0x34*/ &INSTR_INC_DEREF_HL,
+    /*0x35*/ &INSTR_DEC_DEREF_HL,
+    /*0x36*/ &INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE,
+    /*0x37*/ &INSTR_SCF,
+    /*0x38*/ &INSTR_JR_IF_C,
+    /*0x39*/ &INSTR_ADD_HL_SP,
+    /*0x3A*/ &INSTR_LD_TO_A_FROM_DEREF_HL_DEC,
+    /*0x3B*/ &INSTR_DEC_SP,
+    /*0x3C*/ &INSTR_INC_A,
+    /*0x3D*/ &INSTR_DEC_A,
+    /*0x3E*/ &INSTR_LD_TO_A_FROM_IMMEDIATE,
+    /*0x3F*/ &INSTR_CCF,
+    /*0x40*/ &INSTR_LD_TO_B_FROM_B,
+    /*0x41*/ &INSTR_LD_TO_B_FROM_C,
+    /*0x42*/ &INSTR_LD_TO_B_FROM_D,
+    /*0x43*/ &INSTR_LD_TO_B_FROM_E,
+    /*0x44*/ &INSTR_LD_TO_B_FROM_H,
+    /*0x45*/ &INSTR_LD_TO_B_FROM_L,
+    /*0x46*/ &INSTR_LD_TO_B_FROM_DEREF_HL,
+    /*0x47*/ &INSTR_LD_TO_B_FROM_A,
+    /*0x48*/ &INSTR_LD_TO_C_FROM_B,
+    /*0x49*/ &INSTR_LD_TO_C_FROM_C,
+    /*0x4A*/ &INSTR_LD_TO_C_FROM_D,
+    /*0x4B*/ &INSTR_LD_TO_C_FROM_E,
+    /*0x4C*/ &INSTR_LD_TO_C_FROM_H,
+    /*0x4D*/ &INSTR_LD_TO_C_FROM_L,
+    /*0x4E*/ &INSTR_LD_TO_C_FROM_DEREF_HL,
+    /*0x4F*/ &INSTR_LD_TO_C_FROM_A,
+    /*0x50*/ &INSTR_LD_TO_D_FROM_B,
+    /*0x51*/ &INSTR_LD_TO_D_FROM_C,
+    /*0x52*/ &INSTR_LD_TO_D_FROM_D,
+    /*0x53*/ &INSTR_LD_TO_D_FROM_E,
+    /*0x54*/ &INSTR_LD_TO_D_FROM_H,
+    /*0x55*/ &INSTR_LD_TO_D_FROM_L,
+    /*0x56*/ &INSTR_LD_TO_D_FROM_DEREF_HL,
+    /*0x57*/ &INSTR_LD_TO_D_FROM_A,
+    /*0x58*/ &INSTR_LD_TO_E_FROM_B,
+    /*0x59*/ &INSTR_LD_TO_E_FROM_C,
+    /*0x5A*/ &INSTR_LD_TO_E_FROM_D,
+    /*0x5B*/ &INSTR_LD_TO_E_FROM_E,
+    /*0x5C*/ &INSTR_LD_TO_E_FROM_H,
+    /*0x5D*/ &INSTR_LD_TO_E_FROM_L,
+    /*0x5E*/ &INSTR_LD_TO_E_FROM_DEREF_HL,
+    /*0x5F*/ &INSTR_LD_TO_E_FROM_A,
+    /*0x60*/ &INSTR_LD_TO_H_FROM_B,
+    /*0x61*/ &INSTR_LD_TO_H_FROM_C,
+    /*0x62*/ &INSTR_LD_TO_H_FROM_D,
+    /*0x63*/ &INSTR_LD_TO_H_FROM_E,
+    /*0x64*/ &INSTR_LD_TO_H_FROM_H,
+    /*0x65*/ &INSTR_LD_TO_H_FROM_L,
+    /*0x66*/ &INSTR_LD_TO_H_FROM_DEREF_HL,
+    /*0x67*/ &INSTR_LD_TO_H_FROM_A,
+    /*0x68*/ &INSTR_LD_TO_L_FROM_B,
+    /*0x69*/ &INSTR_LD_TO_L_FROM_C,
+    /*0x6A*/ &INSTR_LD_TO_L_FROM_D,
+    /*0x6B*/ &INSTR_LD_TO_L_FROM_E,
+    /*0x6C*/ &INSTR_LD_TO_L_FROM_H,
+    /*0x6D*/ &INSTR_LD_TO_L_FROM_L,
+    /*0x6E*/ &INSTR_LD_TO_L_FROM_DEREF_HL,
+    /*0x6F*/ &INSTR_LD_TO_L_FROM_A,
+    /*0x70*/ &INSTR_LD_TO_DEREF_HL_FROM_B,
+    /*0x71*/ &INSTR_LD_TO_DEREF_HL_FROM_C,
+    /*0x72*/ &INSTR_LD_TO_DEREF_HL_FROM_D,
+    /*0x73*/ &INSTR_LD_TO_DEREF_HL_FROM_E,
+    /*0x74*/ &INSTR_LD_TO_DEREF_HL_FROM_H,
+    /*0x75*/ &INSTR_LD_TO_DEREF_HL_FROM_L,
+    /*0x76*/ &INSTR_HALT,
+    /*0x77*/ &INSTR_LD_TO_DEREF_HL_FROM_A,
+    /*0x78*/ &INSTR_LD_TO_A_FROM_B,
+    /*0x79*/ &INSTR_LD_TO_A_FROM_C,
+    /*0x7A*/ &INSTR_LD_TO_A_FROM_D,
+    /*0x7B*/ &INSTR_LD_TO_A_FROM_E,
+    /*0x7C*/ &INSTR_LD_TO_A_FROM_H,
+    /*0x7D*/ &INSTR_LD_TO_A_FROM_L,
+    /*0x7E*/ &INSTR_LD_TO_A_FROM_DEREF_HL,
+    /*0x7F*/ &INSTR_LD_TO_A_FROM_A,
+    /*0x80*/ &INSTR_ADD_A_B,
+    /*0x81*/ &INSTR_ADD_A_C,
+    /*0x82*/ &INSTR_ADD_A_D,
+    /*0x83*/ &INSTR_ADD_A_E,
+    /*0x84*/ &INSTR_ADD_A_H,
+    /*0x85*/ &INSTR_ADD_A_L,
+    /*0x86*/ &INSTR_ADD_A_DEREF_HL,
+    /*0x87*/ &INSTR_ADD_A_A,
+    /*0x88*/ &INSTR_ADC_A_B,
+    /*0x89*/ &INSTR_ADC_A_C,
+    /*0x8A*/ &INSTR_ADC_A_D,
+    /*0x8B*/ &INSTR_ADC_A_E,
+    /*0x8C*/ &INSTR_ADC_A_H,
+    /*0x8D*/ &INSTR_ADC_A_L,
+    /*0x8E*/ &INSTR_ADC_A_DEREF_HL,
+    /*0x8F*/ &INSTR_ADC_A_A,
+    /*0x90*/ &INSTR_SUB_A_B,
+    /*0x91*/ &INSTR_SUB_A_C,
+    /*0x92*/ &INSTR_SUB_A_D,
+    /*0x93*/ &INSTR_SUB_A_E,
+    /*0x94*/ &INSTR_SUB_A_H,
+    /*0x95*/ &INSTR_SUB_A_L,
+    /*0x96*/ &INSTR_SUB_A_DEREF_HL,
+    /*0x97*/ &INSTR_SUB_A_A,
+    /*0x98*/ &INSTR_SBC_A_B,
+    /*0x99*/ &INSTR_SBC_A_C,
+    /*0x9A*/ &INSTR_SBC_A_D,
+    /*0x9B*/ &INSTR_SBC_A_E,
+    /*0x9C*/ &INSTR_SBC_A_H,
+    /*0x9D*/ &INSTR_SBC_A_L,
+    /*0x9E*/ &INSTR_SBC_A_DEREF_HL,
+    /*0x9F*/ &INSTR_SBC_A_A,
+    /*0xA0*/ &INSTR_AND_A_B,
+    /*0xA1*/ &INSTR_AND_A_C,
+    /*0xA2*/ &INSTR_AND_A_D,
+    /*0xA3*/ &INSTR_AND_A_E,
+    /*0xA4*/ &INSTR_AND_A_H,
+    /*0xA5*/ &INSTR_AND_A_L,
+    /*0xA6*/ &INSTR_AND_A_DEREF_HL,
+    /*0xA7*/ &INSTR_AND_A_A,
+    /*0xA8*/ &INSTR_XOR_A_B,
+    /*0xA9*/ &INSTR_XOR_A_C,
+    /*0xAA*/ &INSTR_XOR_A_D,
+    /*0xAB*/ &INSTR_XOR_A_E,
+    /*0xAC*/ &INSTR_XOR_A_H,
+    /*0xAD*/ &INSTR_XOR_A_L,
+    /*0xAE*/ &INSTR_XOR_A_DEREF_HL,
+    /*0xAF*/ &INSTR_XOR_A_A,
+    /*0xB0*/ &INSTR_OR_A_B,
+    /*0xB1*/ &INSTR_OR_A_C,
+    /*0xB2*/ &INSTR_OR_A_D,
+    /*0xB3*/ &INSTR_OR_A_E,
+    /*0xB4*/ &INSTR_OR_A_H,
+    /*0xB5*/ &INSTR_OR_A_L,
+    /*0xB6*/ &INSTR_OR_A_DEREF_HL,
+    /*0xB7*/ &INSTR_OR_A_A,
+    /*0xB8*/ &INSTR_CP_A_B,
+    /*0xB9*/ &INSTR_CP_A_C,
+    /*0xBA*/ &INSTR_CP_A_D,
+    /*0xBB*/ &INSTR_CP_A_E,
+    /*0xBC*/ &INSTR_CP_A_H,
+    /*0xBD*/ &INSTR_CP_A_L,
+    /*0xBE*/ &INSTR_CP_A_DEREF_HL,
+    /*0xBF*/ &INSTR_CP_A_A,
+    /*0xC0*/ &INSTR_RET_IF_NZ,
+    /*0xC1*/ &INSTR_POP_BC,
+    /*0xC2*/ &INSTR_JP_IF_NZ,
+    /*0xC3*/ &INSTR_JP,
+    /*0xC4*/ &INSTR_CALL_IF_NZ,
+    /*0xC5*/ &INSTR_PUSH_BC,
+    /*0xC6*/ &INSTR_ADD_A_IMMEDIATE,
+    /*0xC7*/ &INSTR_RST_00,
+    /*0xC8*/ &INSTR_RET_IF_Z,
+    /*0xC9*/ &INSTR_RET,
+    /*0xCA*/ &INSTR_JP_IF_Z,
+    /*0xCB*/ &INSTR_PREFIX,
+    /*0xCC*/ &INSTR_CALL_IF_Z,
+    /*0xCD*/ &INSTR_CALL,
+    /*0xCE*/ &INSTR_ADC_A_IMMEDIATE,
+    /*0xCF*/ &INSTR_RST_08,
+    /*0xD0*/ &INSTR_RET_IF_NC,
+    /*0xD1*/ &INSTR_POP_DE,
+    /*0xD2*/ &INSTR_JP_IF_NC,
+    /*0xD3*/ &INSTR_INVALID,
+    /*0xD4*/ &INSTR_CALL_IF_NC,
+    /*0xD5*/ &INSTR_PUSH_DE,
+    /*0xD6*/ &INSTR_SUB_A_IMMEDIATE,
+    /*0xD7*/ &INSTR_RST_10,
+    /*0xD8*/ &INSTR_RET_IF_C,
+    /*0xD9*/ &INSTR_RETI,
+    /*0xDA*/ &INSTR_JP_IF_C,
+    /*0xDB*/ &INSTR_INVALID,
+    /*0xDC*/ &INSTR_CALL_IF_C,
+    /*0xDD*/ &INSTR_INVALID,
+    /*0xDE*/ &INSTR_SBC_A_IMMEDIATE,
+    /*0xDF*/ &INSTR_RST_18,
+    /*0xE0*/ &INSTR_LDH_TO_IMMEDIATE_FROM_A,
+    /*0xE1*/ &INSTR_POP_HL,
+    /*0xE2*/ &INSTR_LDH_TO_DEREF_C_FROM_A,
+    /*0xE3*/ &INSTR_INVALID,
+    /*0xE4*/ &INSTR_INVALID,
+    /*0xE5*/ &INSTR_PUSH_HL,
+    /*0xE6*/ &INSTR_AND_A_IMMEDIATE,
+    /*0xE7*/ &INSTR_RST_20,
+    /*0xE8*/ &INSTR_ADD_SP_IMMEDIATE,
+    /*0xE9*/ &INSTR_JP_HL,
+    /*0xEA*/ &INSTR_LD_TO_DEREF_LABEL_FROM_A,
+    /*0xEB*/ &INSTR_INVALID,
+    /*0xEC*/ &INSTR_INVALID,
+    /*0xED*/ &INSTR_INVALID,
+    /*0xEE*/ &INSTR_XOR_A_IMMEDIATE,
+    /*0xEF*/ &INSTR_RST_28,
+    /*0xF0*/ &INSTR_LDH_TO_A_FROM_IMMEDIATE,
+    /*0xF1*/ &INSTR_POP_AF,
+    /*0xF2*/ &INSTR_LDH_TO_A_FROM_DEREF_C,
+    /*0xF3*/ &INSTR_DI,
+    /*0xF4*/ &INSTR_INVALID,
+    /*0xF5*/ &INSTR_PUSH_AF,
+    /*0xF6*/ &INSTR_OR_A_IMMEDIATE,
+    /*0xF7*/ &INSTR_RST_30,
+    /*0xF8*/ &INSTR_LD_TO_HL_FROM_SP_OFFSET,
+    /*0xF9*/ &INSTR_LD_TO_SP_FROM_HL,
+    /*0xFA*/ &INSTR_LD_TO_A_FROM_DEREF_LABEL,
+    /*0xFB*/ &INSTR_EI,
+    /*0xFC*/ &INSTR_INVALID,
+    /*0xFD*/ &INSTR_INVALID,
+    /*0xFE*/ &INSTR_CP_IMMEDIATE,
+    /*0xFF*/ &INSTR_RST_38,
+];
+
+// PREFIX RLC
+pub static INSTR_PREFIX_RLC_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %b",
+    op_code: 0x00,
+};
+pub static INSTR_PREFIX_RLC_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %c",
+    op_code: 0x01,
+};
+pub static INSTR_PREFIX_RLC_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %d",
+    op_code: 0x02,
+};
+pub static INSTR_PREFIX_RLC_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %e",
+    op_code: 0x03,
+};
+pub static INSTR_PREFIX_RLC_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %h",
+    op_code: 0x04,
+};
+pub static INSTR_PREFIX_RLC_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %l",
+    op_code: 0x05,
+};
+pub static INSTR_PREFIX_RLC_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc (%hl)",
+    op_code: 0x06,
+};
+pub static INSTR_PREFIX_RLC_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rlc %a",
+    op_code: 0x07,
+};
+
+// PREFIX RRC
+pub static INSTR_PREFIX_RRC_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %b",
+    op_code: 0x08,
+};
+pub static INSTR_PREFIX_RRC_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %c",
+    op_code: 0x09,
+};
+pub static INSTR_PREFIX_RRC_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %d",
+    op_code: 0x0A,
+};
+pub static INSTR_PREFIX_RRC_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %e",
+    op_code: 0x0B,
+};
+pub static INSTR_PREFIX_RRC_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %h",
+    op_code: 0x0C,
+};
+pub static INSTR_PREFIX_RRC_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %l",
+    op_code: 0x0D,
+};
+pub static INSTR_PREFIX_RRC_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc (%hl)",
+    op_code: 0x0E,
+};
+pub static INSTR_PREFIX_RRC_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rrc %a",
+    op_code: 0x0F,
+};
+
+// PREFIX RL
+pub static INSTR_PREFIX_RL_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %b",
+    op_code: 0x10,
+};
+pub static INSTR_PREFIX_RL_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %c",
+    op_code: 0x11,
+};
+pub static INSTR_PREFIX_RL_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %d",
+    op_code: 0x12,
+};
+pub static INSTR_PREFIX_RL_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %e",
+    op_code: 0x13,
+};
+pub static INSTR_PREFIX_RL_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %h",
+    op_code: 0x14,
+};
+pub static INSTR_PREFIX_RL_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %l",
+    op_code: 0x15,
+};
+pub static INSTR_PREFIX_RL_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl (%hl)",
+    op_code: 0x16,
+};
+pub static INSTR_PREFIX_RL_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rl %a",
+    op_code: 0x17,
+};
+
+// PREFIX RR
+pub static INSTR_PREFIX_RR_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %b",
+    op_code: 0x18,
+};
+pub static INSTR_PREFIX_RR_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %c",
+    op_code: 0x19,
+};
+pub static INSTR_PREFIX_RR_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %d",
+    op_code: 0x1A,
+};
+pub static INSTR_PREFIX_RR_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %e",
+    op_code: 0x1B,
+};
+pub static INSTR_PREFIX_RR_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %h",
+    op_code: 0x1C,
+};
+pub static INSTR_PREFIX_RR_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %l",
+    op_code: 0x1D,
+};
+pub static INSTR_PREFIX_RR_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr (%hl)",
+    op_code: 0x1E,
+};
+pub static INSTR_PREFIX_RR_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "rr %a",
+    op_code: 0x1F,
+};
+
+// PREFIX SLA
+pub static INSTR_PREFIX_SLA_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %b",
+    op_code: 0x20,
+};
+pub static INSTR_PREFIX_SLA_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %c",
+    op_code: 0x21,
+};
+pub static INSTR_PREFIX_SLA_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %d",
+    op_code: 0x22,
+};
+pub static INSTR_PREFIX_SLA_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %e",
+    op_code: 0x23,
+};
+pub static INSTR_PREFIX_SLA_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %h",
+    op_code: 0x24,
+};
+pub static INSTR_PREFIX_SLA_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %l",
+    op_code: 0x25,
+};
+pub static INSTR_PREFIX_SLA_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla (%hl)",
+    op_code: 0x26,
+};
+pub static INSTR_PREFIX_SLA_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sla %a",
+    op_code: 0x27,
+};
+
+// PREFIX SRA
+pub static INSTR_PREFIX_SRA_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %b",
+    op_code: 0x28,
+};
+pub static INSTR_PREFIX_SRA_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %c",
+    op_code: 0x29,
+};
+pub static INSTR_PREFIX_SRA_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %d",
+    op_code: 0x2A,
+};
+pub static INSTR_PREFIX_SRA_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %e",
+    op_code: 0x2B,
+};
+pub static INSTR_PREFIX_SRA_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %h",
+    op_code: 0x2C,
+};
+pub static INSTR_PREFIX_SRA_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %l",
+    op_code: 0x2D,
+};
+pub static INSTR_PREFIX_SRA_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra (%hl)",
+    op_code: 0x2E,
+};
+pub static INSTR_PREFIX_SRA_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "sra %a",
+    op_code: 0x2F,
+};
+
+// PREFIX SWAP
+pub static INSTR_PREFIX_SWAP_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %b",
+    op_code: 0x30,
+};
+pub static INSTR_PREFIX_SWAP_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %c",
+    op_code: 0x31,
+};
+pub static INSTR_PREFIX_SWAP_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %d",
+    op_code: 0x32,
+};
+pub static INSTR_PREFIX_SWAP_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %e",
+    op_code: 0x33,
+};
+pub static INSTR_PREFIX_SWAP_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %h",
+    op_code: 0x34,
+};
+pub static INSTR_PREFIX_SWAP_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %l",
+    op_code: 0x35,
+};
+pub static INSTR_PREFIX_SWAP_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap (%hl)",
+    op_code: 0x36,
+};
+pub static INSTR_PREFIX_SWAP_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "swap %a",
+    op_code: 0x37,
+};
+
+// PREFIX SRL
+pub static INSTR_PREFIX_SRL_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %b",
+    op_code: 0x38,
+};
+pub static INSTR_PREFIX_SRL_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %c",
+    op_code: 0x39,
+};
+pub static INSTR_PREFIX_SRL_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %d",
+    op_code: 0x3A,
+};
+pub static INSTR_PREFIX_SRL_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %e",
+    op_code: 0x3B,
+};
+pub static INSTR_PREFIX_SRL_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %h",
+    op_code: 0x3C,
+};
+pub static INSTR_PREFIX_SRL_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %l",
+    op_code: 0x3D,
+};
+pub static INSTR_PREFIX_SRL_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl (%hl)",
+    op_code: 0x3E,
+};
+pub static INSTR_PREFIX_SRL_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "srl %a",
+    op_code: 0x3F,
+};
+
+// PREFIX BIT
+pub static INSTR_PREFIX_BIT_0_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %b",
+    op_code: 0x40,
+};
+pub static INSTR_PREFIX_BIT_0_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %c",
+    op_code: 0x41,
+};
+pub static INSTR_PREFIX_BIT_0_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %d",
+    op_code: 0x42,
+};
+pub static INSTR_PREFIX_BIT_0_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %e",
+    op_code: 0x43,
+};
+pub static INSTR_PREFIX_BIT_0_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %h",
+    op_code: 0x44,
+};
+pub static INSTR_PREFIX_BIT_0_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %l",
+    op_code: 0x45,
+};
+pub static INSTR_PREFIX_BIT_0_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 (%hl)",
+    op_code: 0x46,
+};
+pub static INSTR_PREFIX_BIT_0_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 0 %a",
+    op_code: 0x47,
+};
+pub static INSTR_PREFIX_BIT_1_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %b",
+    op_code: 0x48,
+};
+pub static INSTR_PREFIX_BIT_1_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %c",
+    op_code: 0x49,
+};
+pub static INSTR_PREFIX_BIT_1_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %d",
+    op_code: 0x4A,
+};
+pub static INSTR_PREFIX_BIT_1_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %e",
+    op_code: 0x4B,
+};
+pub static INSTR_PREFIX_BIT_1_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %h",
+    op_code: 0x4C,
+};
+pub static INSTR_PREFIX_BIT_1_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %l",
+    op_code: 0x4D,
+};
+pub static INSTR_PREFIX_BIT_1_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 (%hl)",
+    op_code: 0x4E,
+};
+pub static INSTR_PREFIX_BIT_1_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 1 %a",
+    op_code: 0x4F,
+};
+pub static INSTR_PREFIX_BIT_2_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %b",
+    op_code: 0x50,
+};
+pub static INSTR_PREFIX_BIT_2_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %c",
+    op_code: 0x51,
+};
+pub static INSTR_PREFIX_BIT_2_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %d",
+    op_code: 0x52,
+};
+pub static INSTR_PREFIX_BIT_2_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %e",
+    op_code: 0x53,
+};
+pub static INSTR_PREFIX_BIT_2_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %h",
+    op_code: 0x54,
+};
+pub static INSTR_PREFIX_BIT_2_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %l",
+    op_code: 0x55,
+};
+pub static INSTR_PREFIX_BIT_2_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 (%hl)",
+    op_code: 0x56,
+};
+pub static INSTR_PREFIX_BIT_2_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 2 %a",
+    op_code: 0x57,
+};
+pub static INSTR_PREFIX_BIT_3_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %b",
+    op_code: 0x58,
+};
+pub static INSTR_PREFIX_BIT_3_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %c",
+    op_code: 0x59,
+};
+pub static INSTR_PREFIX_BIT_3_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %d",
+    op_code: 0x5A,
+};
+pub static INSTR_PREFIX_BIT_3_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %e",
+    op_code: 0x5B,
+};
+pub static INSTR_PREFIX_BIT_3_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %h",
+    op_code: 0x5C,
+};
+pub static INSTR_PREFIX_BIT_3_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %l",
+    op_code: 0x5D,
+};
+pub static INSTR_PREFIX_BIT_3_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 (%hl)",
+    op_code: 0x5E,
+};
+pub static INSTR_PREFIX_BIT_3_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 3 %a",
+    op_code: 0x5F,
+};
+pub static INSTR_PREFIX_BIT_4_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %b",
+    op_code: 0x60,
+};
+pub static INSTR_PREFIX_BIT_4_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %c",
+    op_code: 0x61,
+};
+pub static INSTR_PREFIX_BIT_4_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %d",
+    op_code: 0x62,
+};
+pub static INSTR_PREFIX_BIT_4_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %e",
+    op_code: 0x63,
+};
+pub static INSTR_PREFIX_BIT_4_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %h",
+    op_code: 0x64,
+};
+pub static INSTR_PREFIX_BIT_4_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %l",
+    op_code: 0x65,
+};
+pub static INSTR_PREFIX_BIT_4_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 (%hl)",
+    op_code: 0x66,
+};
+pub static INSTR_PREFIX_BIT_4_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 4 %a",
+    op_code: 0x67,
+};
+pub static INSTR_PREFIX_BIT_5_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %b",
+    op_code: 0x68,
+};
+pub static INSTR_PREFIX_BIT_5_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %c",
+    op_code: 0x69,
+};
+pub static INSTR_PREFIX_BIT_5_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %d",
+    op_code: 0x6A,
+};
+pub static INSTR_PREFIX_BIT_5_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %e",
+    op_code: 0x6B,
+};
+pub static INSTR_PREFIX_BIT_5_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %h",
+    op_code: 0x6C,
+};
+pub static INSTR_PREFIX_BIT_5_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %l",
+    op_code: 0x6D,
+};
+pub static INSTR_PREFIX_BIT_5_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 (%hl)",
+    op_code: 0x6E,
+};
+pub static INSTR_PREFIX_BIT_5_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 5 %a",
+    op_code: 0x6F,
+};
+pub static INSTR_PREFIX_BIT_6_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %b",
+    op_code: 0x70,
+};
+pub static INSTR_PREFIX_BIT_6_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %c",
+    op_code: 0x71,
+};
+pub static INSTR_PREFIX_BIT_6_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %d",
+    op_code: 0x72,
+};
+pub static INSTR_PREFIX_BIT_6_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %e",
+    op_code: 0x73,
+};
+pub static INSTR_PREFIX_BIT_6_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %h",
+    op_code: 0x74,
+};
+pub static INSTR_PREFIX_BIT_6_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %l",
+    op_code: 0x75,
+};
+pub static INSTR_PREFIX_BIT_6_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 (%hl)",
+    op_code: 0x76,
+};
+pub static INSTR_PREFIX_BIT_6_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 6 %a",
+    op_code: 0x77,
+};
+pub static INSTR_PREFIX_BIT_7_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %b",
+    op_code: 0x78,
+};
+pub static INSTR_PREFIX_BIT_7_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %c",
+    op_code: 0x79,
+};
+pub static INSTR_PREFIX_BIT_7_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %d",
+    op_code: 0x7A,
+};
+pub static INSTR_PREFIX_BIT_7_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %e",
+    op_code: 0x7B,
+};
+pub static INSTR_PREFIX_BIT_7_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %h",
+    op_code: 0x7C,
+};
+pub static INSTR_PREFIX_BIT_7_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %l",
+    op_code: 0x7D,
+};
+pub static INSTR_PREFIX_BIT_7_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 (%hl)",
+    op_code: 0x7E,
+};
+pub static INSTR_PREFIX_BIT_7_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "bit 7 %a",
+    op_code: 0x7F,
+};
+
+// PREFIX RES
+pub static INSTR_PREFIX_RES_0_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %b",
+    op_code: 0x80,
+};
+pub static INSTR_PREFIX_RES_0_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %c",
+    op_code: 0x81,
 };
-pub static INSTR_CALL_IF_NZ: Sm83Instr = Sm83Instr {
-    mnemonic: "CALL #nz 'fn",
-    op_code: 0xC4,
-    arg_bytes: 2,
+pub static INSTR_PREFIX_RES_0_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %d",
+    op_code: 0x82,
+};
+pub static INSTR_PREFIX_RES_0_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %e",
+    op_code: 0x83,
+};
+pub static INSTR_PREFIX_RES_0_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %h",
+    op_code: 0x84,
+};
+pub static INSTR_PREFIX_RES_0_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %l",
+    op_code: 0x85,
+};
+pub static INSTR_PREFIX_RES_0_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 (%hl)",
+    op_code: 0x86,
+};
+pub static INSTR_PREFIX_RES_0_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 0 %a",
+    op_code: 0x87,
+};
+pub static INSTR_PREFIX_RES_1_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %b",
+    op_code: 0x88,
+};
+pub static INSTR_PREFIX_RES_1_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %c",
+    op_code: 0x89,
+};
+pub static INSTR_PREFIX_RES_1_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %d",
+    op_code: 0x8A,
+};
+pub static INSTR_PREFIX_RES_1_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %e",
+    op_code: 0x8B,
+};
+pub static INSTR_PREFIX_RES_1_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %h",
+    op_code: 0x8C,
+};
+pub static INSTR_PREFIX_RES_1_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %l",
+    op_code: 0x8D,
+};
+pub static INSTR_PREFIX_RES_1_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 (%hl)",
+    op_code: 0x8E,
+};
+pub static INSTR_PREFIX_RES_1_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 1 %a",
+    op_code: 0x8F,
+};
+pub static INSTR_PREFIX_RES_2_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %b",
+    op_code: 0x90,
+};
+pub static INSTR_PREFIX_RES_2_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %c",
+    op_code: 0x91,
+};
+pub static INSTR_PREFIX_RES_2_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %d",
+    op_code: 0x92,
+};
+pub static INSTR_PREFIX_RES_2_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %e",
+    op_code: 0x93,
+};
+pub static INSTR_PREFIX_RES_2_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %h",
+    op_code: 0x94,
+};
+pub static INSTR_PREFIX_RES_2_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %l",
+    op_code: 0x95,
+};
+pub static INSTR_PREFIX_RES_2_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 (%hl)",
+    op_code: 0x96,
+};
+pub static INSTR_PREFIX_RES_2_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 2 %a",
+    op_code: 0x97,
+};
+pub static INSTR_PREFIX_RES_3_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %b",
+    op_code: 0x98,
+};
+pub static INSTR_PREFIX_RES_3_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %c",
+    op_code: 0x99,
+};
+pub static INSTR_PREFIX_RES_3_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %d",
+    op_code: 0x9A,
+};
+pub static INSTR_PREFIX_RES_3_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %e",
+    op_code: 0x9B,
+};
+pub static INSTR_PREFIX_RES_3_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %h",
+    op_code: 0x9C,
+};
+pub static INSTR_PREFIX_RES_3_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %l",
+    op_code: 0x9D,
+};
+pub static INSTR_PREFIX_RES_3_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 (%hl)",
+    op_code: 0x9E,
+};
+pub static INSTR_PREFIX_RES_3_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 3 %a",
+    op_code: 0x9F,
+};
+pub static INSTR_PREFIX_RES_4_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %b",
+    op_code: 0xA0,
+};
+pub static INSTR_PREFIX_RES_4_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %c",
+    op_code: 0xA1,
+};
+pub static INSTR_PREFIX_RES_4_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %d",
+    op_code: 0xA2,
+};
+pub static INSTR_PREFIX_RES_4_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %e",
+    op_code: 0xA3,
+};
+pub static INSTR_PREFIX_RES_4_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %h",
+    op_code: 0xA4,
+};
+pub static INSTR_PREFIX_RES_4_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %l",
+    op_code: 0xA5,
+};
+pub static INSTR_PREFIX_RES_4_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 (%hl)",
+    op_code: 0xA6,
+};
+pub static INSTR_PREFIX_RES_4_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 4 %a",
+    op_code: 0xA7,
+};
+pub static INSTR_PREFIX_RES_5_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %b",
+    op_code: 0xA8,
+};
+pub static INSTR_PREFIX_RES_5_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %c",
+    op_code: 0xA9,
+};
+pub static INSTR_PREFIX_RES_5_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %d",
+    op_code: 0xAA,
+};
+pub static INSTR_PREFIX_RES_5_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %e",
+    op_code: 0xAB,
+};
+pub static INSTR_PREFIX_RES_5_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %h",
+    op_code: 0xAC,
+};
+pub static INSTR_PREFIX_RES_5_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %l",
+    op_code: 0xAD,
+};
+pub static INSTR_PREFIX_RES_5_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 (%hl)",
+    op_code: 0xAE,
+};
+pub static INSTR_PREFIX_RES_5_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 5 %a",
+    op_code: 0xAF,
+};
+pub static INSTR_PREFIX_RES_6_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %b",
+    op_code: 0xB0,
+};
+pub static INSTR_PREFIX_RES_6_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %c",
+    op_code: 0xB1,
+};
+pub static INSTR_PREFIX_RES_6_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %d",
+    op_code: 0xB2,
+};
+pub static INSTR_PREFIX_RES_6_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %e",
+    op_code: 0xB3,
+};
+pub static INSTR_PREFIX_RES_6_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %h",
+    op_code: 0xB4,
+};
+pub static INSTR_PREFIX_RES_6_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %l",
+    op_code: 0xB5,
+};
+pub static INSTR_PREFIX_RES_6_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 (%hl)",
+    op_code: 0xB6,
+};
+pub static INSTR_PREFIX_RES_6_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 6 %a",
+    op_code: 0xB7,
+};
+pub static INSTR_PREFIX_RES_7_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %b",
+    op_code: 0xB8,
+};
+pub static INSTR_PREFIX_RES_7_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %c",
+    op_code: 0xB9,
+};
+pub static INSTR_PREFIX_RES_7_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %d",
+    op_code: 0xBA,
+};
+pub static INSTR_PREFIX_RES_7_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %e",
+    op_code: 0xBB,
+};
+pub static INSTR_PREFIX_RES_7_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %h",
+    op_code: 0xBC,
+};
+pub static INSTR_PREFIX_RES_7_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %l",
+    op_code: 0xBD,
+};
+pub static INSTR_PREFIX_RES_7_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 (%hl)",
+    op_code: 0xBE,
+};
+pub static INSTR_PREFIX_RES_7_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "res 7 %a",
+    op_code: 0xBF,
 };
 
-// RET
-pub static INSTR_RET: Sm83Instr = Sm83Instr {
-    mnemonic: "RET",
+// PREFIX SET
+pub static INSTR_PREFIX_SET_0_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %b",
+    op_code: 0xC0,
+};
+pub static INSTR_PREFIX_SET_0_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %c",
+    op_code: 0xC1,
+};
+pub static INSTR_PREFIX_SET_0_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %d",
+    op_code: 0xC2,
+};
+pub static INSTR_PREFIX_SET_0_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %e",
+    op_code: 0xC3,
+};
+pub static INSTR_PREFIX_SET_0_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %h",
+    op_code: 0xC4,
+};
+pub static INSTR_PREFIX_SET_0_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %l",
+    op_code: 0xC5,
+};
+pub static INSTR_PREFIX_SET_0_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 (%hl)",
+    op_code: 0xC6,
+};
+pub static INSTR_PREFIX_SET_0_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 0 %a",
+    op_code: 0xC7,
+};
+pub static INSTR_PREFIX_SET_1_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %b",
+    op_code: 0xC8,
+};
+pub static INSTR_PREFIX_SET_1_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %c",
     op_code: 0xC9,
-    arg_bytes: 0,
 };
-
-// PUSH
-pub static INSTR_PUSH_DE: Sm83Instr = Sm83Instr {
-    mnemonic: "PUSH %de",
+pub static INSTR_PREFIX_SET_1_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %d",
+    op_code: 0xCA,
+};
+pub static INSTR_PREFIX_SET_1_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %e",
+    op_code: 0xCB,
+};
+pub static INSTR_PREFIX_SET_1_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %h",
+    op_code: 0xCC,
+};
+pub static INSTR_PREFIX_SET_1_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %l",
+    op_code: 0xCD,
+};
+pub static INSTR_PREFIX_SET_1_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 (%hl)",
+    op_code: 0xCE,
+};
+pub static INSTR_PREFIX_SET_1_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 1 %a",
+    op_code: 0xCF,
+};
+pub static INSTR_PREFIX_SET_2_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %b",
+    op_code: 0xD0,
+};
+pub static INSTR_PREFIX_SET_2_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %c",
+    op_code: 0xD1,
+};
+pub static INSTR_PREFIX_SET_2_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %d",
+    op_code: 0xD2,
+};
+pub static INSTR_PREFIX_SET_2_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %e",
+    op_code: 0xD3,
+};
+pub static INSTR_PREFIX_SET_2_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %h",
+    op_code: 0xD4,
+};
+pub static INSTR_PREFIX_SET_2_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %l",
     op_code: 0xD5,
-    arg_bytes: 0,
 };
-
-// POP
-pub static INSTR_POP_HL: Sm83Instr = Sm83Instr {
-    mnemonic: "POP %hl",
+pub static INSTR_PREFIX_SET_2_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 (%hl)",
+    op_code: 0xD6,
+};
+pub static INSTR_PREFIX_SET_2_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 2 %a",
+    op_code: 0xD7,
+};
+pub static INSTR_PREFIX_SET_3_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %b",
+    op_code: 0xD8,
+};
+pub static INSTR_PREFIX_SET_3_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %c",
+    op_code: 0xD9,
+};
+pub static INSTR_PREFIX_SET_3_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %d",
+    op_code: 0xDA,
+};
+pub static INSTR_PREFIX_SET_3_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %e",
+    op_code: 0xDB,
+};
+pub static INSTR_PREFIX_SET_3_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %h",
+    op_code: 0xDC,
+};
+pub static INSTR_PREFIX_SET_3_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %l",
+    op_code: 0xDD,
+};
+pub static INSTR_PREFIX_SET_3_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 (%hl)",
+    op_code: 0xDE,
+};
+pub static INSTR_PREFIX_SET_3_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 3 %a",
+    op_code: 0xDF,
+};
+pub static INSTR_PREFIX_SET_4_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %b",
+    op_code: 0xE0,
+};
+pub static INSTR_PREFIX_SET_4_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %c",
     op_code: 0xE1,
-    arg_bytes: 0,
 };
-
-// PREFIX / EXTENDED OP
-pub static INSTR_PREFIX: Sm83Instr = Sm83Instr {
-    mnemonic: "PREFIX",
-    op_code: 0xCB,
-    arg_bytes: 1,
+pub static INSTR_PREFIX_SET_4_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %d",
+    op_code: 0xE2,
 };
-
-pub static INSTR_PREFIX_SWAP_A: Sm83PrefixInstr = Sm83PrefixInstr {
-    mnemonic: "SWAP %a",
-    op_code: 0x37,
+pub static INSTR_PREFIX_SET_4_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %e",
+    op_code: 0xE3,
+};
+pub static INSTR_PREFIX_SET_4_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %h",
+    op_code: 0xE4,
+};
+pub static INSTR_PREFIX_SET_4_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %l",
+    op_code: 0xE5,
+};
+pub static INSTR_PREFIX_SET_4_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 (%hl)",
+    op_code: 0xE6,
+};
+pub static INSTR_PREFIX_SET_4_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 4 %a",
+    op_code: 0xE7,
+};
+pub static INSTR_PREFIX_SET_5_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %b",
+    op_code: 0xE8,
+};
+pub static INSTR_PREFIX_SET_5_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %c",
+    op_code: 0xE9,
+};
+pub static INSTR_PREFIX_SET_5_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %d",
+    op_code: 0xEA,
+};
+pub static INSTR_PREFIX_SET_5_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %e",
+    op_code: 0xEB,
+};
+pub static INSTR_PREFIX_SET_5_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %h",
+    op_code: 0xEC,
+};
+pub static INSTR_PREFIX_SET_5_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %l",
+    op_code: 0xED,
+};
+pub static INSTR_PREFIX_SET_5_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 (%hl)",
+    op_code: 0xEE,
+};
+pub static INSTR_PREFIX_SET_5_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 5 %a",
+    op_code: 0xEF,
+};
+pub static INSTR_PREFIX_SET_6_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %b",
+    op_code: 0xF0,
+};
+pub static INSTR_PREFIX_SET_6_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %c",
+    op_code: 0xF1,
+};
+pub static INSTR_PREFIX_SET_6_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %d",
+    op_code: 0xF2,
+};
+pub static INSTR_PREFIX_SET_6_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %e",
+    op_code: 0xF3,
+};
+pub static INSTR_PREFIX_SET_6_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %h",
+    op_code: 0xF4,
+};
+pub static INSTR_PREFIX_SET_6_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %l",
+    op_code: 0xF5,
+};
+pub static INSTR_PREFIX_SET_6_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 (%hl)",
+    op_code: 0xF6,
+};
+pub static INSTR_PREFIX_SET_6_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 6 %a",
+    op_code: 0xF7,
+};
+pub static INSTR_PREFIX_SET_7_B: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %b",
+    op_code: 0xF8,
+};
+pub static INSTR_PREFIX_SET_7_C: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %c",
+    op_code: 0xF9,
+};
+pub static INSTR_PREFIX_SET_7_D: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %d",
+    op_code: 0xFA,
+};
+pub static INSTR_PREFIX_SET_7_E: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %e",
+    op_code: 0xFB,
+};
+pub static INSTR_PREFIX_SET_7_H: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %h",
+    op_code: 0xFC,
+};
+pub static INSTR_PREFIX_SET_7_L: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %l",
+    op_code: 0xFD,
+};
+pub static INSTR_PREFIX_SET_7_DEREF_HL: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 (%hl)",
+    op_code: 0xFE,
+};
+pub static INSTR_PREFIX_SET_7_A: Sm83PrefixInstr = Sm83PrefixInstr {
+    mnemonic: "set 7 %a",
+    op_code: 0xFF,
 };
 
-pub static INSTRUCTIONS: [&Sm83Instr; SM83_NUM_INSTRUCTIONS] = [
-    /*0x00*/ &INSTR_NOP,
-    /*0x01*/ &INSTR_LD_TO_BC_FROM_IMMEDIATE,
-    /*0x02*/ &INSTR_INVALID,
-    /*0x03*/ &INSTR_INC_BC,
-    /*0x04*/ &INSTR_INVALID,
-    /*0x05*/ &INSTR_DEC_B,
-    /*0x06*/ &INSTR_LD_TO_B_FROM_IMMEDIATE,
-    /*0x07*/ &INSTR_INVALID,
-    /*0x08*/ &INSTR_INVALID,
-    /*0x09*/ &INSTR_INVALID,
-    /*0x0A*/ &INSTR_INVALID,
-    /*0x0B*/ &INSTR_DEC_BC,
-    /*0x0C*/ &INSTR_INC_C,
-    /*0x0D*/ &INSTR_DEC_C,
-    /*0x0E*/ &INSTR_LD_TO_C_FROM_IMMEDIATE,
-    /*0x0F*/ &INSTR_RRCA,
-    /*0x10*/ &INSTR_INVALID,
-    /*0x11*/ &INSTR_LD_TO_DE_FROM_IMMEDIATE,
-    /*0x12*/ &INSTR_LD_TO_DEREF_DE_FROM_A,
-    /*0x13*/ &INSTR_INC_DE,
-    /*0x14*/ &INSTR_INVALID,
-    /*0x15*/ &INSTR_INVALID,
-    /*0x16*/ &INSTR_LD_TO_D_FROM_IMMEDIATE,
-    /*0x17*/ &INSTR_INVALID,
-    /*0x18*/ &INSTR_JR,
-    /*0x19*/ &INSTR_ADD_HL_DE,
-    /*0x1A*/ &INSTR_LD_TO_A_FROM_DEREF_DE,
-    /*0x1B*/ &INSTR_DEC_DE,
-    /*0x1C*/ &INSTR_INVALID,
-    /*0x1D*/ &INSTR_INVALID,
-    /*0x1E*/ &INSTR_INVALID,
-    /*0x1F*/ &INSTR_INVALID,
-    /*0x20*/ &INSTR_JR_IF_NZ,
-    /*0x21*/ &INSTR_LD_TO_HL_FROM_IMMEDIATE,
-    /*0x22*/ &INSTR_LD_TO_DEREF_HL_INC_FROM_A,
-    /*0x23*/ &INSTR_INC_HL,
-    /*0x24*/ &INSTR_INVALID,
-    /*0x25*/ &INSTR_INVALID,
-    /*0x26*/ &INSTR_INVALID,
-    /*0x27*/ &INSTR_INVALID,
-    /*0x28*/ &INSTR_INVALID,
-    /*0x29*/ &INSTR_INVALID,
-    /*0x2A*/ &INSTR_LD_TO_A_FROM_DEREF_HL_INC,
-    /*0x2B*/ &INSTR_DEC_HL,
-    /*0x2C*/ &INSTR_INC_L,
-    /*0x2D*/ &INSTR_INVALID,
-    /*0x2E*/ &INSTR_INVALID,
-    /*0x2F*/ &INSTR_CPL,
-    /*0x30*/ &INSTR_INVALID,
-    /*0x31*/ &INSTR_LD_TO_SP_FROM_IMMEDIATE,
-    /*0x32*/ &INSTR_LD_TO_DEREF_HL_DEC_FROM_A,
-    /*0x33*/ &INSTR_INVALID,
-    /*0x34*/ &INSTR_INVALID,
-    /*0x35*/ &INSTR_INVALID,
-    /*0x36*/ &INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE,
-    /*0x37*/ &INSTR_INVALID,
-    /*0x38*/ &INSTR_JR_IF_C,
-    /*0x39*/ &INSTR_INVALID,
-    /*0x3A*/ &INSTR_INVALID,
-    /*0x3B*/ &INSTR_INVALID,
-    /*0x3C*/ &INSTR_INC_A,
-    /*0x3D*/ &INSTR_DEC_A,
-    /*0x3E*/ &INSTR_LD_TO_A_FROM_IMMEDIATE,
-    /*0x3F*/ &INSTR_INVALID,
-    /*0x40*/ &INSTR_INVALID,
-    /*0x41*/ &INSTR_INVALID,
-    /*0x42*/ &INSTR_INVALID,
-    /*0x43*/ &INSTR_INVALID,
-    /*0x44*/ &INSTR_INVALID,
-    /*0x45*/ &INSTR_INVALID,
-    /*0x46*/ &INSTR_INVALID,
-    /*0x47*/ &INSTR_LD_TO_B_FROM_A,
-    /*0x48*/ &INSTR_INVALID,
-    /*0x49*/ &INSTR_INVALID,
-    /*0x4A*/ &INSTR_INVALID,
-    /*0x4B*/ &INSTR_INVALID,
-    /*0x4C*/ &INSTR_INVALID,
-    /*0x4D*/ &INSTR_INVALID,
-    /*0x4E*/ &INSTR_INVALID,
-    /*0x4F*/ &INSTR_LD_TO_C_FROM_A,
-    /*0x50*/ &INSTR_INVALID,
-    /*0x51*/ &INSTR_INVALID,
-    /*0x52*/ &INSTR_INVALID,
-    /*0x53*/ &INSTR_INVALID,
-    /*0x54*/ &INSTR_INVALID,
-    /*0x55*/ &INSTR_INVALID,
-    /*0x56*/ &INSTR_LD_TO_D_FROM_DEREF_HL,
-    /*0x57*/ &INSTR_INVALID,
-    /*0x58*/ &INSTR_INVALID,
-    /*0x59*/ &INSTR_INVALID,
-    /*0x5A*/ &INSTR_INVALID,
-    /*0x5B*/ &INSTR_INVALID,
-    /*0x5C*/ &INSTR_INVALID,
-    /*0x5D*/ &INSTR_INVALID,
-    /*0x5E*/ &INSTR_LD_TO_E_FROM_DEREF_HL,
-    /*0x5F*/ &INSTR_LD_TO_E_FROM_A,
-    /*0x60*/ &INSTR_INVALID,
-    /*0x61*/ &INSTR_INVALID,
-    /*0x62*/ &INSTR_INVALID,
-    /*0x63*/ &INSTR_INVALID,
-    /*0x64*/ &INSTR_INVALID,
-    /*0x65*/ &INSTR_INVALID,
-    /*0x66*/ &INSTR_INVALID,
-    /*0x67*/ &INSTR_INVALID,
-    /*0x68*/ &INSTR_INVALID,
-    /*0x69*/ &INSTR_INVALID,
-    /*0x6A*/ &INSTR_INVALID,
-    /*0x6B*/ &INSTR_INVALID,
-    /*0x6C*/ &INSTR_INVALID,
-    /*0x6D*/ &INSTR_INVALID,
-    /*0x6E*/ &INSTR_INVALID,
-    /*0x6F*/ &INSTR_INVALID,
-    /*0x70*/ &INSTR_INVALID,
-    /*0x71*/ &INSTR_INVALID,
-    /*0x72*/ &INSTR_INVALID,
-    /*0x73*/ &INSTR_INVALID,
-    /*0x74*/ &INSTR_INVALID,
-    /*0x75*/ &INSTR_INVALID,
-    /*0x76*/ &INSTR_INVALID,
-    /*0x77*/ &INSTR_LD_TO_DEREF_HL_FROM_A,
-    /*0x78*/ &INSTR_LD_TO_A_FROM_B,
-    /*0x79*/ &INSTR_LD_TO_A_FROM_C,
-    /*0x7A*/ &INSTR_INVALID,
-    /*0x7B*/ &INSTR_INVALID,
-    /*0x7C*/ &INSTR_INVALID,
-    /*0x7D*/ &INSTR_INVALID,
-    /*0x7E*/ &INSTR_LD_TO_A_FROM_DEREF_HL,
-    /*0x7F*/ &INSTR_INVALID,
-    /*0x80*/ &INSTR_INVALID,
-    /*0x81*/ &INSTR_INVALID,
-    /*0x82*/ &INSTR_INVALID,
-    /*0x83*/ &INSTR_INVALID,
-    /*0x84*/ &INSTR_INVALID,
-    /*0x85*/ &INSTR_INVALID,
-    /*0x86*/ &INSTR_INVALID,
-    /*0x87*/ &INSTR_ADD_A_A,
-    /*0x88*/ &INSTR_INVALID,
-    /*0x89*/ &INSTR_INVALID,
-    /*0x8A*/ &INSTR_INVALID,
-    /*0x8B*/ &INSTR_INVALID,
-    /*0x8C*/ &INSTR_INVALID,
-    /*0x8D*/ &INSTR_INVALID,
-    /*0x8E*/ &INSTR_INVALID,
-    /*0x8F*/ &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    &INSTR_INVALID,
-    /*0xA1*/ &INSTR_AND_A_C,
-    /*0xA2*/ &INSTR_INVALID,
-    /*0xA3*/ &INSTR_INVALID,
-    /*0xA4*/ &INSTR_INVALID,
-    /*0xA5*/ &INSTR_INVALID,
-    /*0xA6*/ &INSTR_INVALID,
-    /*0xA7*/ &INSTR_INVALID,
-    /*0xA8*/ &INSTR_INVALID,
-    /*0xA9*/ &INSTR_XOR_A_C,
-    /*0xAA*/ &INSTR_INVALID,
-    /*0xAB*/ &INSTR_INVALID,
-    /*0xAC*/ &INSTR_INVALID,
-    /*0xAD*/ &INSTR_INVALID,
-    /*0xAE*/ &INSTR_INVALID,
-    /*0xAF*/ &INSTR_XOR_A_A,
-    /*0xB0*/ &INSTR_OR_A_B,
-    /*0xB1*/ &INSTR_OR_A_C,
-    /*0xB2*/ &INSTR_INVALID,
-    /*0xB3*/ &INSTR_INVALID,
-    /*0xB4*/ &INSTR_INVALID,
-    /*0xB5*/ &INSTR_INVALID,
-    /*0xB6*/ &INSTR_INVALID,
-    /*0xB7*/ &INSTR_INVALID,
-    /*0xB8*/ &INSTR_INVALID,
-    /*0xB9*/ &INSTR_INVALID,
-    /*0xBA*/ &INSTR_INVALID,
-    /*0xBB*/ &INSTR_INVALID,
-    /*0xBC*/ &INSTR_INVALID,
-    /*0xBD*/ &INSTR_INVALID,
-    /*0xBE*/ &INSTR_INVALID,
-    /*0xBF*/ &INSTR_INVALID,
-    /*0xC0*/ &INSTR_INVALID,
-    /*0xC1*/ &INSTR_INVALID,
-    /*0xC2*/ &INSTR_JP_IF_NZ,
-    /*0xC3*/ &INSTR_JP,
-    /*0xC4*/ &INSTR_CALL_IF_NZ,
-    /*0xC5*/ &INSTR_INVALID,
-    /*0xC6*/ &INSTR_INVALID,
-    /*0xC7*/ &INSTR_INVALID,
-    /*0xC8*/ &INSTR_INVALID,
-    /*0xC9*/ &INSTR_RET,
-    /*0xCA*/ &INSTR_INVALID,
-    /*0xCB*/ &INSTR_PREFIX,
-    /*0xCC*/ &INSTR_INVALID,
-    /*0xCD*/ &INSTR_CALL,
-    /*0xCE*/ &INSTR_INVALID,
-    /*0xCF*/ &INSTR_INVALID,
-    /*0xD0*/ &INSTR_INVALID,
-    /*0xD1*/ &INSTR_INVALID,
-    /*0xD2*/ &INSTR_INVALID,
-    /*0xD3*/ &INSTR_INVALID,
-    /*0xD4*/ &INSTR_INVALID,
-    /*0xD5*/ &INSTR_PUSH_DE,
-    /*0xD6*/ &INSTR_INVALID,
-    /*0xD7*/ &INSTR_INVALID,
-    /*0xD8*/ &INSTR_INVALID,
-    /*0xD9*/ &INSTR_INVALID,
-    /*0xDA*/ &INSTR_JP_IF_C,
-    /*0xDB*/ &INSTR_INVALID,
-    /*0xDC*/ &INSTR_CALL_IF_C,
-    /*0xDD*/ &INSTR_INVALID,
-    /*0xDE*/ &INSTR_INVALID,
-    /*0xDF*/ &INSTR_INVALID,
-    /*0xE0*/ &INSTR_LDH_TO_IMMEDIATE_FROM_A,
-    /*0xE1*/ &INSTR_POP_HL,
-    /*0xE2*/ &INSTR_LDH_TO_DEREF_C_FROM_A,
-    /*0xE3*/ &INSTR_INVALID,
-    /*0xE4*/ &INSTR_INVALID,
-    /*0xE5*/ &INSTR_INVALID,
-    /*0xE6*/ &INSTR_AND_A_IMMEDIATE,
-    /*0xE7*/ &INSTR_INVALID,
-    /*0xE8*/ &INSTR_ADD_SP_IMMEDIATE,
-    /*0xE9*/ &INSTR_JP_HL,
-    /*0xEA*/ &INSTR_LD_TO_DEREF_LABEL_FROM_A,
-    /*0xEB*/ &INSTR_INVALID,
-    /*0xEC*/ &INSTR_INVALID,
-    /*0xED*/ &INSTR_INVALID,
-    /*0xEE*/ &INSTR_INVALID,
-    /*0xEF*/ &INSTR_RST_28,
-    /*0xF0*/ &INSTR_LDH_TO_A_FROM_IMMEDIATE,
-    /*0xF1*/ &INSTR_INVALID,
-    /*0xF2*/ &INSTR_INVALID,
-    /*0xF3*/ &INSTR_DI,
-    /*0xF4*/ &INSTR_INVALID,
-    /*0xF5*/ &INSTR_INVALID,
-    /*0xF6*/ &INSTR_INVALID,
-    /*0xF7*/ &INSTR_INVALID,
-    /*0xF8*/ &INSTR_INVALID,
-    /*0xF9*/ &INSTR_INVALID,
-    /*0xFA*/ &INSTR_LD_TO_A_FROM_DEREF_LABEL,
-    /*0xFB*/ &INSTR_EI,
-    /*0xFC*/ &INSTR_INVALID,
-    /*0xFD*/ &INSTR_INVALID,
-    /*0xFE*/ &INSTR_CP_IMMEDIATE,
-    /*0xFF*/ &INSTR_RST_38,
+pub static PREFIX_INSTRUCTIONS: [&Sm83PrefixInstr; SM83_NUM_PREFIX_INSTRUCTIONS] = [
+    /*0x00*/ &INSTR_PREFIX_RLC_B,
+    /*0x01*/ &INSTR_PREFIX_RLC_C,
+    /*0x02*/ &INSTR_PREFIX_RLC_D,
+    /*0x03*/ &INSTR_PREFIX_RLC_E,
+    /*0x04*/ &INSTR_PREFIX_RLC_H,
+    /*0x05*/ &INSTR_PREFIX_RLC_L,
+    /*0x06*/ &INSTR_PREFIX_RLC_DEREF_HL,
+    /*0x07*/ &INSTR_PREFIX_RLC_A,
+    /*0x08*/ &INSTR_PREFIX_RRC_B,
+    /*0x09*/ &INSTR_PREFIX_RRC_C,
+    /*0x0A*/ &INSTR_PREFIX_RRC_D,
+    /*0x0B*/ &INSTR_PREFIX_RRC_E,
+    /*0x0C*/ &INSTR_PREFIX_RRC_H,
+    /*0x0D*/ &INSTR_PREFIX_RRC_L,
+    /*0x0E*/ &INSTR_PREFIX_RRC_DEREF_HL,
+    /*0x0F*/ &INSTR_PREFIX_RRC_A,
+    /*0x10*/ &INSTR_PREFIX_RL_B,
+    /*0x11*/ &INSTR_PREFIX_RL_C,
+    /*0x12*/ &INSTR_PREFIX_RL_D,
+    /*0x13*/ &INSTR_PREFIX_RL_E,
+    /*0x14*/ &INSTR_PREFIX_RL_H,
+    /*0x15*/ &INSTR_PREFIX_RL_L,
+    /*0x16*/ &INSTR_PREFIX_RL_DEREF_HL,
+    /*0x17*/ &INSTR_PREFIX_RL_A,
+    /*0x18*/ &INSTR_PREFIX_RR_B,
+    /*0x19*/ &INSTR_PREFIX_RR_C,
+    /*0x1A*/ &INSTR_PREFIX_RR_D,
+    /*0x1B*/ &INSTR_PREFIX_RR_E,
+    /*0x1C*/ &INSTR_PREFIX_RR_H,
+    /*0x1D*/ &INSTR_PREFIX_RR_L,
+    /*0x1E*/ &INSTR_PREFIX_RR_DEREF_HL,
+    /*0x1F*/ &INSTR_PREFIX_RR_A,
+    /*0x20*/ &INSTR_PREFIX_SLA_B,
+    /*0x21*/ &INSTR_PREFIX_SLA_C,
+    /*0x22*/ &INSTR_PREFIX_SLA_D,
+    /*0x23*/ &INSTR_PREFIX_SLA_E,
+    /*0x24*/ &INSTR_PREFIX_SLA_H,
+    /*0x25*/ &INSTR_PREFIX_SLA_L,
+    /*0x26*/ &INSTR_PREFIX_SLA_DEREF_HL,
+    /*0x27*/ &INSTR_PREFIX_SLA_A,
+    /*0x28*/ &INSTR_PREFIX_SRA_B,
+    /*0x29*/ &INSTR_PREFIX_SRA_C,
+    /*0x2A*/ &INSTR_PREFIX_SRA_D,
+    /*0x2B*/ &INSTR_PREFIX_SRA_E,
+    /*0x2C*/ &INSTR_PREFIX_SRA_H,
+    /*0x2D*/ &INSTR_PREFIX_SRA_L,
+    /*0x2E*/ &INSTR_PREFIX_SRA_DEREF_HL,
+    /*0x2F*/ &INSTR_PREFIX_SRA_A,
+    /*0x30*/ &INSTR_PREFIX_SWAP_B,
+    /*0x31*/ &INSTR_PREFIX_SWAP_C,
+    /*0x32*/ &INSTR_PREFIX_SWAP_D,
+    /*0x33*/ &INSTR_PREFIX_SWAP_E,
+    /*0x34*/ &INSTR_PREFIX_SWAP_H,
+    /*0x35*/ &INSTR_PREFIX_SWAP_L,
+    /*0x36*/ &INSTR_PREFIX_SWAP_DEREF_HL,
+    /*0x37*/ &INSTR_PREFIX_SWAP_A,
+    /*0x38*/ &INSTR_PREFIX_SRL_B,
+    /*0x39*/ &INSTR_PREFIX_SRL_C,
+    /*0x3A*/ &INSTR_PREFIX_SRL_D,
+    /*0x3B*/ &INSTR_PREFIX_SRL_E,
+    /*0x3C*/ &INSTR_PREFIX_SRL_H,
+    /*0x3D*/ &INSTR_PREFIX_SRL_L,
+    /*0x3E*/ &INSTR_PREFIX_SRL_DEREF_HL,
+    /*0x3F*/ &INSTR_PREFIX_SRL_A,
+    /*0x40*/ &INSTR_PREFIX_BIT_0_B,
+    /*0x41*/ &INSTR_PREFIX_BIT_0_C,
+    /*0x42*/ &INSTR_PREFIX_BIT_0_D,
+    /*0x43*/ &INSTR_PREFIX_BIT_0_E,
+    /*0x44*/ &INSTR_PREFIX_BIT_0_H,
+    /*0x45*/ &INSTR_PREFIX_BIT_0_L,
+    /*0x46*/ &INSTR_PREFIX_BIT_0_DEREF_HL,
+    /*0x47*/ &INSTR_PREFIX_BIT_0_A,
+    /*0x48*/ &INSTR_PREFIX_BIT_1_B,
+    /*0x49*/ &INSTR_PREFIX_BIT_1_C,
+    /*0x4A*/ &INSTR_PREFIX_BIT_1_D,
+    /*0x4B*/ &INSTR_PREFIX_BIT_1_E,
+    /*0x4C*/ &INSTR_PREFIX_BIT_1_H,
+    /*0x4D*/ &INSTR_PREFIX_BIT_1_L,
+    /*0x4E*/ &INSTR_PREFIX_BIT_1_DEREF_HL,
+    /*0x4F*/ &INSTR_PREFIX_BIT_1_A,
+    /*0x50*/ &INSTR_PREFIX_BIT_2_B,
+    /*0x51*/ &INSTR_PREFIX_BIT_2_C,
+    /*0x52*/ &INSTR_PREFIX_BIT_2_D,
+    /*0x53*/ &INSTR_PREFIX_BIT_2_E,
+    /*0x54*/ &INSTR_PREFIX_BIT_2_H,
+    /*0x55*/ &INSTR_PREFIX_BIT_2_L,
+    /*0x56*/ &INSTR_PREFIX_BIT_2_DEREF_HL,
+    /*0x57*/ &INSTR_PREFIX_BIT_2_A,
+    /*0x58*/ &INSTR_PREFIX_BIT_3_B,
+    /*0x59*/ &INSTR_PREFIX_BIT_3_C,
+    /*0x5A*/ &INSTR_PREFIX_BIT_3_D,
+    /*0x5B*/ &INSTR_PREFIX_BIT_3_E,
+    /*0x5C*/ &INSTR_PREFIX_BIT_3_H,
+    /*0x5D*/ &INSTR_PREFIX_BIT_3_L,
+    /*0x5E*/ &INSTR_PREFIX_BIT_3_DEREF_HL,
+    /*0x5F*/ &INSTR_PREFIX_BIT_3_A,
+    /*0x60*/ &INSTR_PREFIX_BIT_4_B,
+    /*0x61*/ &INSTR_PREFIX_BIT_4_C,
+    /*0x62*/ &INSTR_PREFIX_BIT_4_D,
+    /*0x63*/ &INSTR_PREFIX_BIT_4_E,
+    /*0x64*/ &INSTR_PREFIX_BIT_4_H,
+    /*0x65*/ &INSTR_PREFIX_BIT_4_L,
+    /*0x66*/ &INSTR_PREFIX_BIT_4_DEREF_HL,
+    /*0x67*/ &INSTR_PREFIX_BIT_4_A,
+    /*0x68*/ &INSTR_PREFIX_BIT_5_B,
+    /*0x69*/ &INSTR_PREFIX_BIT_5_C,
+    /*0x6A*/ &INSTR_PREFIX_BIT_5_D,
+    /*0x6B*/ &INSTR_PREFIX_BIT_5_E,
+    /*0x6C*/ &INSTR_PREFIX_BIT_5_H,
+    /*0x6D*/ &INSTR_PREFIX_BIT_5_L,
+    /*0x6E*/ &INSTR_PREFIX_BIT_5_DEREF_HL,
+    /*0x6F*/ &INSTR_PREFIX_BIT_5_A,
+    /*0x70*/ &INSTR_PREFIX_BIT_6_B,
+    /*0x71*/ &INSTR_PREFIX_BIT_6_C,
+    /*0x72*/ &INSTR_PREFIX_BIT_6_D,
+    /*0x73*/ &INSTR_PREFIX_BIT_6_E,
+    /*0x74*/ &INSTR_PREFIX_BIT_6_H,
+    /*0x75*/ &INSTR_PREFIX_BIT_6_L,
+    /*0x76*/ &INSTR_PREFIX_BIT_6_DEREF_HL,
+    /*0x77*/ &INSTR_PREFIX_BIT_6_A,
+    /*0x78*/ &INSTR_PREFIX_BIT_7_B,
+    /*0x79*/ &INSTR_PREFIX_BIT_7_C,
+    /*0x7A*/ &INSTR_PREFIX_BIT_7_D,
+    /*0x7B*/ &INSTR_PREFIX_BIT_7_E,
+    /*0x7C*/ &INSTR_PREFIX_BIT_7_H,
+    /*0x7D*/ &INSTR_PREFIX_BIT_7_L,
+    /*0x7E*/ &INSTR_PREFIX_BIT_7_DEREF_HL,
+    /*0x7F*/ &INSTR_PREFIX_BIT_7_A,
+    /*0x80*/ &INSTR_PREFIX_RES_0_B,
+    /*0x81*/ &INSTR_PREFIX_RES_0_C,
+    /*0x82*/ &INSTR_PREFIX_RES_0_D,
+    /*0x83*/ &INSTR_PREFIX_RES_0_E,
+    /*0x84*/ &INSTR_PREFIX_RES_0_H,
+    /*0x85*/ &INSTR_PREFIX_RES_0_L,
+    /*0x86*/ &INSTR_PREFIX_RES_0_DEREF_HL,
+    /*0x87*/ &INSTR_PREFIX_RES_0_A,
+    /*0x88*/ &INSTR_PREFIX_RES_1_B,
+    /*0x89*/ &INSTR_PREFIX_RES_1_C,
+    /*0x8A*/ &INSTR_PREFIX_RES_1_D,
+    /*0x8B*/ &INSTR_PREFIX_RES_1_E,
+    /*0x8C*/ &INSTR_PREFIX_RES_1_H,
+    /*0x8D*/ &INSTR_PREFIX_RES_1_L,
+    /*0x8E*/ &INSTR_PREFIX_RES_1_DEREF_HL,
+    /*0x8F*/ &INSTR_PREFIX_RES_1_A,
+    /*0x90*/ &INSTR_PREFIX_RES_2_B,
+    /*0x91*/ &INSTR_PREFIX_RES_2_C,
+    /*0x92*/ &INSTR_PREFIX_RES_2_D,
+    /*0x93*/ &INSTR_PREFIX_RES_2_E,
+    /*0x94*/ &INSTR_PREFIX_RES_2_H,
+    /*0x95*/ &INSTR_PREFIX_RES_2_L,
+    /*0x96*/ &INSTR_PREFIX_RES_2_DEREF_HL,
+    /*0x97*/ &INSTR_PREFIX_RES_2_A,
+    /*0x98*/ &INSTR_PREFIX_RES_3_B,
+    /*0x99*/ &INSTR_PREFIX_RES_3_C,
+    /*0x9A*/ &INSTR_PREFIX_RES_3_D,
+    /*0x9B*/ &INSTR_PREFIX_RES_3_E,
+    /*0x9C*/ &INSTR_PREFIX_RES_3_H,
+    /*0x9D*/ &INSTR_PREFIX_RES_3_L,
+    /*0x9E*/ &INSTR_PREFIX_RES_3_DEREF_HL,
+    /*0x9F*/ &INSTR_PREFIX_RES_3_A,
+    /*0xA0*/ &INSTR_PREFIX_RES_4_B,
+    /*0xA1*/ &INSTR_PREFIX_RES_4_C,
+    /*0xA2*/ &INSTR_PREFIX_RES_4_D,
+    /*0xA3*/ &INSTR_PREFIX_RES_4_E,
+    /*0xA4*/ &INSTR_PREFIX_RES_4_H,
+    /*0xA5*/ &INSTR_PREFIX_RES_4_L,
+    /*0xA6*/ &INSTR_PREFIX_RES_4_DEREF_HL,
+    /*0xA7*/ &INSTR_PREFIX_RES_4_A,
+    /*0xA8*/ &INSTR_PREFIX_RES_5_B,
+    /*0xA9*/ &INSTR_PREFIX_RES_5_C,
+    /*0xAA*/ &INSTR_PREFIX_RES_5_D,
+    /*0xAB*/ &INSTR_PREFIX_RES_5_E,
+    /*0xAC*/ &INSTR_PREFIX_RES_5_H,
+    /*0xAD*/ &INSTR_PREFIX_RES_5_L,
+    /*0xAE*/ &INSTR_PREFIX_RES_5_DEREF_HL,
+    /*0xAF*/ &INSTR_PREFIX_RES_5_A,
+    /*0xB0*/ &INSTR_PREFIX_RES_6_B,
+    /*0xB1*/ &INSTR_PREFIX_RES_6_C,
+    /*0xB2*/ &INSTR_PREFIX_RES_6_D,
+    /*0xB3*/ &INSTR_PREFIX_RES_6_E,
+    /*0xB4*/ &INSTR_PREFIX_RES_6_H,
+    /*0xB5*/ &INSTR_PREFIX_RES_6_L,
+    /*0xB6*/ &INSTR_PREFIX_RES_6_DEREF_HL,
+    /*0xB7*/ &INSTR_PREFIX_RES_6_A,
+    /*0xB8*/ &INSTR_PREFIX_RES_7_B,
+    /*0xB9*/ &INSTR_PREFIX_RES_7_C,
+    /*0xBA*/ &INSTR_PREFIX_RES_7_D,
+    /*0xBB*/ &INSTR_PREFIX_RES_7_E,
+    /*0xBC*/ &INSTR_PREFIX_RES_7_H,
+    /*0xBD*/ &INSTR_PREFIX_RES_7_L,
+    /*0xBE*/ &INSTR_PREFIX_RES_7_DEREF_HL,
+    /*0xBF*/ &INSTR_PREFIX_RES_7_A,
+    /*0xC0*/ &INSTR_PREFIX_SET_0_B,
+    /*0xC1*/ &INSTR_PREFIX_SET_0_C,
+    /*0xC2*/ &INSTR_PREFIX_SET_0_D,
+    /*0xC3*/ &INSTR_PREFIX_SET_0_E,
+    /*0xC4*/ &INSTR_PREFIX_SET_0_H,
+    /*0xC5*/ &INSTR_PREFIX_SET_0_L,
+    /*0xC6*/ &INSTR_PREFIX_SET_0_DEREF_HL,
+    /*0xC7*/ &INSTR_PREFIX_SET_0_A,
+    /*0xC8*/ &INSTR_PREFIX_SET_1_B,
+    /*0xC9*/ &INSTR_PREFIX_SET_1_C,
+    /*0xCA*/ &INSTR_PREFIX_SET_1_D,
+    /*0xCB*/ &INSTR_PREFIX_SET_1_E,
+    /*0xCC*/ &INSTR_PREFIX_SET_1_H,
+    /*0xCD*/ &INSTR_PREFIX_SET_1_L,
+    /*0xCE*/ &INSTR_PREFIX_SET_1_DEREF_HL,
+    /*0xCF*/ &INSTR_PREFIX_SET_1_A,
+    /*0xD0*/ &INSTR_PREFIX_SET_2_B,
+    /*0xD1*/ &INSTR_PREFIX_SET_2_C,
+    /*0xD2*/ &INSTR_PREFIX_SET_2_D,
+    /*0xD3*/ &INSTR_PREFIX_SET_2_E,
+    /*0xD4*/ &INSTR_PREFIX_SET_2_H,
+    /*0xD5*/ &INSTR_PREFIX_SET_2_L,
+    /*0xD6*/ &INSTR_PREFIX_SET_2_DEREF_HL,
+    /*0xD7*/ &INSTR_PREFIX_SET_2_A,
+    /*0xD8*/ &INSTR_PREFIX_SET_3_B,
+    /*0xD9*/ &INSTR_PREFIX_SET_3_C,
+    /*0xDA*/ &INSTR_PREFIX_SET_3_D,
+    /*0xDB*/ &INSTR_PREFIX_SET_3_E,
+    /*0xDC*/ &INSTR_PREFIX_SET_3_H,
+    /*0xDD*/ &INSTR_PREFIX_SET_3_L,
+    /*0xDE*/ &INSTR_PREFIX_SET_3_DEREF_HL,
+    /*0xDF*/ &INSTR_PREFIX_SET_3_A,
+    /*0xE0*/ &INSTR_PREFIX_SET_4_B,
+    /*0xE1*/ &INSTR_PREFIX_SET_4_C,
+    /*0xE2*/ &INSTR_PREFIX_SET_4_D,
+    /*0xE3*/ &INSTR_PREFIX_SET_4_E,
+    /*0xE4*/ &INSTR_PREFIX_SET_4_H,
+    /*0xE5*/ &INSTR_PREFIX_SET_4_L,
+    /*0xE6*/ &INSTR_PREFIX_SET_4_DEREF_HL,
+    /*0xE7*/ &INSTR_PREFIX_SET_4_A,
+    /*0xE8*/ &INSTR_PREFIX_SET_5_B,
+    /*0xE9*/ &INSTR_PREFIX_SET_5_C,
+    /*0xEA*/ &INSTR_PREFIX_SET_5_D,
+    /*0xEB*/ &INSTR_PREFIX_SET_5_E,
+    /*0xEC*/ &INSTR_PREFIX_SET_5_H,
+    /*0xED*/ &INSTR_PREFIX_SET_5_L,
+    /*0xEE*/ &INSTR_PREFIX_SET_5_DEREF_HL,
+    /*0xEF*/ &INSTR_PREFIX_SET_5_A,
+    /*0xF0*/ &INSTR_PREFIX_SET_6_B,
+    /*0xF1*/ &INSTR_PREFIX_SET_6_C,
+    /*0xF2*/ &INSTR_PREFIX_SET_6_D,
+    /*0xF3*/ &INSTR_PREFIX_SET_6_E,
+    /*0xF4*/ &INSTR_PREFIX_SET_6_H,
+    /*0xF5*/ &INSTR_PREFIX_SET_6_L,
+    /*0xF6*/ &INSTR_PREFIX_SET_6_DEREF_HL,
+    /*0xF7*/ &INSTR_PREFIX_SET_6_A,
+    /*0xF8*/ &INSTR_PREFIX_SET_7_B,
+    /*0xF9*/ &INSTR_PREFIX_SET_7_C,
+    /*0xFA*/ &INSTR_PREFIX_SET_7_D,
+    /*0xFB*/ &INSTR_PREFIX_SET_7_E,
+    /*0xFC*/ &INSTR_PREFIX_SET_7_H,
+    /*0xFD*/ &INSTR_PREFIX_SET_7_L,
+    /*0xFE*/ &INSTR_PREFIX_SET_7_DEREF_HL,
+    /*0xFF*/ &INSTR_PREFIX_SET_7_A,
 ];
 
 pub fn decode(op: u8) -> &'static Sm83Instr {
     INSTRUCTIONS[op as usize]
 }
+
+pub fn decode_prefix(op: u8) -> &'static Sm83PrefixInstr {
+    PREFIX_INSTRUCTIONS[op as usize]
+}
@@ -1,16 +1,77 @@
-use crate::arch::sm83::{INSTR_INVALID, INSTRUCTIONS, SM83_NUM_INSTRUCTIONS};
+use crate::arch::sm83::{
+    INSTR_ADD_SP_IMMEDIATE, INSTR_CP_IMMEDIATE, INSTR_INVALID, INSTR_JR_IF_NZ,
+    INSTR_LD_TO_A_FROM_DEREF_HL_INC, INSTR_LD_TO_DEREF_LABEL_FROM_A, INSTR_LDH_TO_A_FROM_IMMEDIATE,
+    INSTRUCTIONS, PREFIX_INSTRUCTIONS,
+};
+
+/// The op codes that are not defined on the SM83.
+const ILLEGAL_OP_CODES: [u8; 11] = [
+    0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD,
+];
 
 #[test]
 fn test_op_code_matches_index() -> Result<(), String> {
-    for i in 0..SM83_NUM_INSTRUCTIONS {
-        let instr = INSTRUCTIONS[i];
-        if instr.op_code != INSTR_INVALID.op_code {
-            assert_eq!(
-                instr.op_code, i as u8,
-                "o_code=0x{:x}, index=0x{:x}",
-                instr.op_code, i
+    for (i, instr) in INSTRUCTIONS.iter().enumerate() {
+        if ILLEGAL_OP_CODES.contains(&(i as u8)) {
+            assert!(
+                std::ptr::eq(*instr, &INSTR_INVALID),
+                "illegal op_code=0x{:x} not mapped to INSTR_INVALID",
+                i
             );
+            continue;
         }
+        assert!(
+            !std::ptr::eq(*instr, &INSTR_INVALID),
+            "legal op_code=0x{:x} mapped to INSTR_INVALID",
+            i
+        );
+        assert_eq!(
+            instr.op_code, i as u8,
+            "o_code=0x{:x}, index=0x{:x}",
+            instr.op_code, i
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_text() -> Result<(), String> {
+    assert_eq!(INSTR_CP_IMMEDIATE.text(None), "(cp %a n8)");
+    assert_eq!(INSTR_CP_IMMEDIATE.text(Some(&[0xFE, 0x90])), "(cp %a 0x90)");
+    assert_eq!(
+        INSTR_LD_TO_DEREF_LABEL_FROM_A.text(Some(&[0xEA, 0x40, 0xFF])),
+        "(ld (0xff40) %a)"
+    );
+    assert_eq!(
+        INSTR_LDH_TO_A_FROM_IMMEDIATE.text(Some(&[0xF0, 0x44])),
+        "(ldh %a (0xff44))"
+    );
+    assert_eq!(INSTR_JR_IF_NZ.text(Some(&[0x20, 0xFB])), "(jr #nz -5)");
+    assert_eq!(
+        INSTR_JR_IF_NZ.text_at(&[0x20, 0xFB], 0x150),
+        "(jr #nz 0x14d)"
+    );
+    assert_eq!(INSTR_JR_IF_NZ.text_at(&[0x20, 0xFB], 0x1), "(jr #nz -2)");
+    assert_eq!(
+        INSTR_ADD_SP_IMMEDIATE.text_at(&[0xE8, 0xFE], 0x150),
+        "(add %sp -2)"
+    );
+    assert_eq!(
+        INSTR_LD_TO_A_FROM_DEREF_HL_INC.text(Some(&[0x2A])),
+        "(ld %a (%hl +))"
+    );
+    assert_eq!(INSTR_CP_IMMEDIATE.text(Some(&[0xFE])), "(cp %a ERR)");
+    Ok(())
+}
+
+#[test]
+fn test_prefix_op_code_matches_index() -> Result<(), String> {
+    for (i, instr) in PREFIX_INSTRUCTIONS.iter().enumerate() {
+        assert_eq!(
+            instr.op_code, i as u8,
+            "o_code=0x{:x}, index=0x{:x}",
+            instr.op_code, i
+        );
     }
     Ok(())
 }
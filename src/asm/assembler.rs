@@ -2,26 +2,98 @@
 #[path = "./assembler_test.rs"]
 mod assembler_test;
 
-use crate::arch::sm83::{
-    self, INSTR_DEC_A, INSTR_DEC_B, INSTR_DEC_BC, INSTR_DEC_DE, INSTR_DEC_HL, INSTR_INC_A,
-    INSTR_INC_BC, INSTR_INC_DE, INSTR_INC_HL, INSTR_LD_TO_A_FROM_B, INSTR_LD_TO_A_FROM_DEREF_DE,
-    INSTR_LD_TO_A_FROM_DEREF_HL, INSTR_LD_TO_A_FROM_DEREF_HL_INC, INSTR_LD_TO_A_FROM_DEREF_LABEL,
-    INSTR_LD_TO_A_FROM_IMMEDIATE, INSTR_LD_TO_B_FROM_IMMEDIATE, INSTR_LD_TO_BC_FROM_IMMEDIATE,
-    INSTR_LD_TO_DE_FROM_IMMEDIATE, INSTR_LD_TO_DEREF_DE_FROM_A, INSTR_LD_TO_DEREF_HL_FROM_A,
-    INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE, INSTR_LD_TO_DEREF_HL_INC_FROM_A,
-    INSTR_LD_TO_DEREF_LABEL_FROM_A, INSTR_LD_TO_HL_FROM_IMMEDIATE, INSTR_NOP, INSTR_OR_A_C,
-    INSTR_RET,
+use crate::arch::gb::{self, Licensee};
+use crate::asm::encoder::{self, EncodedRef};
+use crate::asm::interpreter::{
+    CONDITIONAL_OPS, CONST_OPS, LOOP_OPS, conditional_forms, contains_label, eval, eval_const,
+    fold_constants, loop_values,
 };
-use crate::asm::interpreter::{CONST_OP_BITWISE_OR, CONST_OP_SHIFT_LEFT, eval_aar, eval_const};
-use crate::asm::parser::{Address, Form, Label, SExp, Symbol, TopLevel, parse_from_file};
-use std::collections::HashMap;
+use crate::asm::macros::{Macro, define_macro};
+use crate::asm::object::{Object, ObjectPatch, ObjectSection, ObjectSymbol, Patch};
+use crate::asm::parser::{
+    Address, Form, Label, SExp, Span, Symbol, TopLevel, parse_from_source, parse_number,
+};
+use crate::asm::scope::Scopes;
+use crate::asm::stdlib::{EMBEDDED_STDLIB, embedded};
+use crate::asm::vfs::{FileSystem, OsFileSystem};
+use crate::error::{Error, ErrorKind};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
 
 pub struct Options {
     pub flat: bool,
     pub out: PathBuf,
+    pub env: Environment,
+}
+
+/// The settings of an assembly besides the source text.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    /// constants defined before the first form, e.g. from -D on the command line
+    pub defines: HashMap<String, i64>,
+    /// the directories searched for an include after the directory of the
+    /// including file, e.g. from -I on the command line
+    pub include_paths: Vec<PathBuf>,
+    /// the stdlib directory for (include :std ...), the stdlib embedded
+    /// in the binary if not set
+    pub stdlib: Option<PathBuf>,
+    /// the byte for the gaps between the sections of a flat image and the
+    /// unused bytes of a section, e.g. from --fill on the command line
+    pub fill: u8,
+}
+
+/// Parses a command line define NAME[=value] into the constant +NAME+ and
+/// its value. The value defaults to 1, the + around the name are optional.
+pub fn parse_define(define: &str) -> Result<(String, i64), Error> {
+    let (name, value) = match define.split_once('=') {
+        Some((name, value)) => (name.trim(), value.trim()),
+        None => (define.trim(), "1"),
+    };
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "()'%#;`,\"".contains(c)) {
+        return Err(Error::syntax(format!("illegal define: {}", define)));
+    }
+    let name = if name.len() > 1 && name.starts_with('+') && name.ends_with('+') {
+        name.to_string()
+    } else {
+        format!("+{}+", name.trim_matches('+'))
+    };
+    let value = match value.strip_prefix('-') {
+        Some(abs) => parse_number(abs).map(|v| -v),
+        None => parse_number(value),
+    }
+    .map_err(|e| Error::syntax(format!("illegal define value for {}: {}", name, e)))?;
+    Ok((name, value))
+}
+
+/// Parses a command line fill byte, e.g. 0xFF.
+pub fn parse_fill(fill: &str) -> Result<u8, Error> {
+    parse_number(fill.trim())
+        .ok()
+        .and_then(|value| u8::try_from(value).ok())
+        .ok_or_else(|| Error::syntax(format!("illegal fill byte: {}, must be 0x00-0xFF", fill)))
+}
+
+/// The result of a flat assembly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assembly {
+    /// the sections placed at their offsets, gaps are filled with the fill byte
+    pub image: Vec<u8>,
+    /// the address of every label, by name without the quote
+    pub labels: BTreeMap<String, u64>,
+    /// the sections ordered by offset
+    pub sections: Vec<SectionLayout>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionLayout {
+    pub name: String,
+    pub offset: u64,
+    pub length: Option<u64>,
+    pub label_only: bool,
+    /// the number of bytes defined in the section
+    pub used: u64,
 }
 
 #[derive(Debug)]
@@ -31,8 +103,13 @@ struct Memory {
 }
 
 impl Memory {
+    /// Writes the byte, the memory of a section without length grows.
     fn push_u8(&mut self, v: u8) {
-        self.mem[self.mem_ptr] = v;
+        if self.mem_ptr < self.mem.len() {
+            self.mem[self.mem_ptr] = v;
+        } else {
+            self.mem.push(v);
+        }
         self.mem_ptr += 1;
     }
 
@@ -46,38 +123,121 @@ impl Memory {
 #[derive(Debug)]
 struct Section {
     name: String,
+    /// 0 for a floating section until it is placed
     offset: Address,
+    /// the section grows with its content if not set
     length: Option<u64>,
     label_only: bool,
     memory: Memory,
+    /// the bytes reserved in a label-only section
+    reserved: u64,
+    floating: Option<Floating>,
+    /// the alignment of the content, from align
+    data_align: u64,
+    /// the content must start at the offset, from org and gb-header
+    pinned: bool,
+}
+
+impl Section {
+    /// The number of bytes the section takes in the address space.
+    fn extent(&self) -> u64 {
+        match self.length {
+            Some(length) => length,
+            None if self.label_only => self.reserved,
+            None => self.memory.mem_ptr as u64,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct State {
+/// The placement constraints of a section without offset, it is placed
+/// into a free block of the region after all forms are assembled. In an
+/// object the section stays unplaced, the linker places it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Floating {
+    pub region: String,
+    pub align: u64,
+    pub bank: Option<u64>,
+}
+
+/// A memory region floating sections are placed into, defined with
+/// (def-region name :offset o :length l [:bank b]). Regions of the same
+/// name must differ in the bank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub name: String,
+    pub offset: u64,
+    pub length: u64,
+    pub bank: Option<u64>,
+}
+
+struct State<'a> {
+    fs: &'a dyn FileSystem,
     sections: Vec<Section>,
     current_section_name: Option<String>,
     current_section_address: Address,
     label_addresses: HashMap<Label, Address>,
+    label_sections: HashMap<Label, String>,
     const_values: HashMap<String, i64>,
-}
-
-type RefCheck = fn(i32) -> Result<(), String>;
+    macros: HashMap<String, Macro>,
+    /// the number of macro expansions currently being assembled
+    macro_depth: usize,
+    scopes: Scopes,
+    include_paths: Vec<PathBuf>,
+    stdlib: Option<PathBuf>,
+    /// the files from the root to the file currently included
+    include_stack: Vec<String>,
+    /// all files included so far, for include-once
+    included: HashSet<String>,
+    /// a (gb-header) was written, the checksums are computed after assembly
+    gb_header: bool,
+    regions: Vec<Region>,
+    /// the sections are placed by the linker, no address is final
+    relocatable: bool,
+    /// the final label addresses of the pass before, they size the
+    /// ld instructions
+    sizing_addresses: Option<HashMap<Label, Address>>,
+}
+
+/// The top-level forms that are not instructions, they cannot be redefined
+/// by a macro.
+const BUILTIN_FORMS: [&str; 17] = [
+    "include",
+    "include-once",
+    "def-region",
+    "incbin",
+    "scope",
+    "defmacro",
+    "def-section",
+    "def-constant",
+    "section",
+    "db",
+    "dw",
+    "ds",
+    "align",
+    "org",
+    "gb-header",
+    "label",
+    "sub-section",
+];
+
+const MAX_MACRO_DEPTH: usize = 64;
+
+/// The passes to size the ld instructions, see assemble_passes.
+const MAX_PASSES: usize = 4;
+
+type RefCheck = fn(i32) -> Result<(), Error>;
 
 #[derive(Debug)]
 enum Ref {
-    Relative(Address, Label, RefCheck),
+    /// the distance from the address to the target address expression
+    Relative(Address, SExp, RefCheck),
     Expression(SExp),
-}
-
-impl Ref {
-    /// Creates a Ref::Expression from a label.
-    fn from_label(label: Label) -> Ref {
-        Ref::Expression(SExp::Symbol(Symbol::Label(label)))
-    }
-
-    fn from_form(form: Form) -> Ref {
-        Ref::Expression(SExp::Form(form))
-    }
+    /// an address expression in the high page, the low byte is written
+    HighPage(SExp),
+    /// an 8-bit value expression
+    Immediate8(SExp),
+    /// a 16-bit data word expression
+    Immediate16(SExp),
 }
 
 #[derive(Debug)]
@@ -85,149 +245,510 @@ struct LabelRef {
     reference: Ref,
     sec_name: String,
     patch_index: usize,
+    /// the form with the reference, for the error location
+    span: Span,
 }
 
-impl State {
-    fn new() -> State {
+impl<'a> State<'a> {
+    fn new(fs: &'a dyn FileSystem) -> State<'a> {
         State {
+            fs,
             sections: Vec::new(),
             current_section_name: None,
             current_section_address: Address(0),
             label_addresses: HashMap::new(),
+            label_sections: HashMap::new(),
             const_values: HashMap::new(),
+            macros: HashMap::new(),
+            macro_depth: 0,
+            scopes: Scopes::new(),
+            include_paths: Vec::new(),
+            stdlib: None,
+            include_stack: Vec::new(),
+            included: HashSet::new(),
+            gb_header: false,
+            regions: Vec::new(),
+            relocatable: false,
+            sizing_addresses: None,
         }
     }
 
+    fn with_env(fs: &'a dyn FileSystem, env: &Environment) -> State<'a> {
+        let mut state = State::new(fs);
+        state.const_values = env.defines.clone();
+        state.include_paths = env.include_paths.clone();
+        state.stdlib = env.stdlib.clone();
+        state
+    }
+
     fn lookup_section(&self, name: &str) -> Option<&Section> {
-        for section in &self.sections {
-            if section.name == name {
-                return Some(section);
-            }
-        }
-        None
+        self.sections.iter().find(|section| section.name == name)
     }
 
     fn lookup_section_mut(&mut self, name: &str) -> Option<&mut Section> {
-        for section in &mut self.sections {
-            if section.name == name {
-                return Some(section);
-            }
-        }
-        None
+        self.sections
+            .iter_mut()
+            .find(|section| section.name == name)
     }
 }
 
-pub fn assemble(pasm: TopLevel, options: Options) -> Result<(), String> {
-    let mut state = State::new();
-    assemble_in_state(pasm, &mut state)?;
-
+pub fn assemble(pasm: TopLevel, options: Options) -> Result<(), Error> {
     if options.flat {
-        state_to_flat(&mut state, &options.out)
+        let assembly = assemble_flat(pasm, &OsFileSystem, &options.env)?;
+        let out_name = options.out.display().to_string();
+        std::fs::write(&options.out, assembly.image).map_err(|e| Error::io(&out_name, e))
     } else {
-        Err("object file assembly currently not supported".to_string())
-    }
-}
-
-fn state_to_flat(state: &mut State, out: &Path) -> Result<(), String> {
+        let new_state = || {
+            let mut state = State::with_env(&OsFileSystem, &options.env);
+            state.relocatable = true;
+            state
+        };
+        let (state, label_refs) = assemble_passes(pasm, new_state, |pasm, state| {
+            let label_refs = assemble_forms(pasm, state)?;
+            // the floating sections are placed by the linker
+            check_section_overlaps(state.sections.iter().filter(|sec| sec.floating.is_none()))?;
+            Ok(label_refs)
+        })?;
+        let obj = state_to_object(&state, label_refs)?;
+        let out_name = options.out.display().to_string();
+        let mut out_file = File::create(&options.out).map_err(|e| Error::io(&out_name, e))?;
+        write!(out_file, "{}", obj).map_err(|e| Error::io(&out_name, e))
+    }
+}
+
+/// Assembles the forms into a flat image, the included files are read
+/// from the file system.
+pub fn assemble_flat(
+    pasm: TopLevel,
+    fs: &dyn FileSystem,
+    env: &Environment,
+) -> Result<Assembly, Error> {
+    let (mut state, ()) = assemble_passes(pasm, || State::with_env(fs, env), assemble_in_state)?;
+    let mut assembly = state_to_assembly(&mut state, env.fill)?;
+    if state.gb_header {
+        gb::fix_checksums(&mut assembly.image)
+            .map_err(|e| Error::new(ErrorKind::Range, format!("gb-header: {}", e)))?;
+    }
+    Ok(assembly)
+}
+
+fn state_to_assembly(state: &mut State, fill: u8) -> Result<Assembly, Error> {
     state.sections.sort_by_key(|section| section.offset);
 
-    let mut out_file = File::create(out).map_err(|e| e.to_string())?;
-
-    let mut last_written = 0;
+    let mut image = Vec::new();
+    let mut sections = Vec::new();
     for sec in &state.sections {
+        sections.push(SectionLayout {
+            name: sec.name.clone(),
+            offset: sec.offset.0,
+            length: sec.length,
+            label_only: sec.label_only,
+            used: sec.memory.mem_ptr as u64,
+        });
         if sec.label_only {
             continue;
         }
 
-        let fill_length = sec.offset.0 - last_written;
-        if fill_length > 0 {
-            let fill = vec![0; fill_length as usize];
-            out_file.write(&fill).map_err(|e| e.to_string())?;
+        // sections in different banks share their addresses
+        if image.len() as u64 > sec.offset.0 {
+            return Err(Error::new(
+                ErrorKind::Range,
+                format!(
+                    "flat assembly has no banks, section .{} (0x{:04X}) overlaps the sections before",
+                    sec.name, sec.offset.0
+                ),
+            ));
         }
-        out_file.write(&sec.memory.mem).map_err(|e| e.to_string())?;
-        last_written = sec.offset.0
-            + sec
-                .length
-                .expect("flat assembly needs sections with specified length");
+        image.resize(sec.offset.0 as usize, fill);
+        image.extend_from_slice(&sec.memory.mem[..sec.memory.mem_ptr]);
+        image.resize((sec.offset.0 + sec.extent()) as usize, fill);
     }
 
-    Ok(())
+    let labels = state
+        .label_addresses
+        .iter()
+        .map(|(label, address)| (label.name().to_string(), address.0))
+        .collect();
+
+    Ok(Assembly {
+        image,
+        labels,
+        sections,
+    })
 }
 
-fn assemble_in_state(pasm: TopLevel, state: &mut State) -> Result<(), String> {
-    let mut label_refs: Vec<LabelRef> = Vec::new();
+/// Converts the assembled state into a relocatable object. All label references
+/// are kept as patches, they are resolved by the linker.
+fn state_to_object(state: &State, label_refs: Vec<LabelRef>) -> Result<Object, Error> {
+    let mut sections = Vec::new();
+    for sec in &state.sections {
+        sections.push(ObjectSection {
+            name: sec.name.clone(),
+            offset: sec.offset,
+            length: sec.length,
+            label_only: sec.label_only,
+            data: sec.memory.mem[0..sec.memory.mem_ptr].to_vec(),
+            reserved: if sec.label_only { sec.reserved } else { 0 },
+            floating: sec.floating.clone(),
+            data_align: sec.data_align,
+            pinned: sec.pinned,
+        });
+    }
+
+    let mut symbols = Vec::new();
+    for (label, address) in &state.label_addresses {
+        let symbol = match state
+            .label_sections
+            .get(label)
+            .and_then(|name| state.lookup_section(name))
+        {
+            Some(sec) => ObjectSymbol {
+                label: label.clone(),
+                section: Some(sec.name.clone()),
+                offset: address.0 - sec.offset.0,
+            },
+            None => ObjectSymbol {
+                label: label.clone(),
+                section: None,
+                offset: address.0,
+            },
+        };
+        symbols.push(symbol);
+    }
+    // stable output, independent of the label hashing
+    symbols.sort_by(|a, b| a.label.name().cmp(b.label.name()));
 
-    for form in pasm.forms {
-        // define the label with an adress if the form is labeled
-        if let Some(lbl) = &form.label {
-            define_label(state, lbl.clone())?;
+    let mut patches = Vec::new();
+    for label_ref in label_refs {
+        let patch = match label_ref.reference {
+            Ref::Relative(from, target, _) => {
+                let sec = state
+                    .lookup_section(&label_ref.sec_name)
+                    .expect("source section not found");
+                Patch::Relative8(from.0 - sec.offset.0, target)
+            }
+            Ref::Expression(exp) => Patch::Absolute16(exp),
+            Ref::HighPage(exp) => Patch::High8(exp),
+            Ref::Immediate8(exp) => Patch::Immediate8(exp),
+            Ref::Immediate16(exp) => Patch::Immediate16(exp),
+        };
+        patches.push(ObjectPatch {
+            section: label_ref.sec_name,
+            patch_index: label_ref.patch_index,
+            patch,
+        });
+    }
+
+    Ok(Object {
+        sections,
+        regions: state.regions.clone(),
+        symbols,
+        patches,
+        gb_header: state.gb_header,
+    })
+}
+
+/// Assembles the forms in passes until the ld instructions are sized. A
+/// pass encodes a ld from or to a label in the high page as ldh if the
+/// label was there in the pass before, also if it is defined after the
+/// instruction. The passes end once the final label addresses do not
+/// change anymore.
+fn assemble_passes<'a, T>(
+    pasm: TopLevel,
+    new_state: impl Fn() -> State<'a>,
+    pass: impl Fn(TopLevel, &mut State<'a>) -> Result<T, Error>,
+) -> Result<(State<'a>, T), Error> {
+    let mut sizing_addresses = None;
+    for n in 1..=MAX_PASSES {
+        let mut state = new_state();
+        state.sizing_addresses = sizing_addresses;
+        let result = pass(pasm.clone(), &mut state)?;
+        let addresses = final_label_addresses(&state);
+        let stable = match &state.sizing_addresses {
+            Some(before) => *before == addresses,
+            None => addresses.is_empty(),
+        };
+        if stable || n == MAX_PASSES {
+            return Ok((state, result));
         }
+        sizing_addresses = Some(addresses);
+    }
+    unreachable!("the last pass returns")
+}
 
-        let may_label_ref = match &form.op {
-            Symbol::Sym(sym_name) => {
-                if sym_name == "def-section" {
-                    def_section(state, form)?
-                } else if sym_name == "def-constant" {
-                    def_constant(state, form)?
-                } else if sym_name == "include" {
-                    include(state, form)?
-                } else if sym_name == "section" {
-                    section(state, form)?
-                } else if sym_name == "db" {
-                    db(state, form)?
-                } else if sym_name == "dw" {
-                    dw(state, form)?
-                } else if sym_name == "ds" {
-                    ds(state, form)?
-                } else if sym_name == "label" {
-                    label(state, form)?
-                } else if sym_name == "sub-section" {
-                    sub_section(state)?
-                // the following forms are tempoarily handled here. Plan is
-                // to convert this to macros that emits bytes with low-level primitives
-                } else if sym_name == "ld" {
-                    //machine specific, should not be handled here
-                    ld(state, form)?
-                } else if sym_name == "jp" {
-                    jp(state, form)?
-                } else if sym_name == "inc" {
-                    inc(state, form)?
-                } else if sym_name == "dec" {
-                    dec(state, form)?
-                } else if sym_name == "jr" {
-                    jr(state, form)?
-                } else if sym_name == "cp" {
-                    cp(state, form)?
-                } else if sym_name == "or" {
-                    or(state, form)?
-                } else if sym_name == "call" {
-                    call(state, form)?
-                } else if sym_name == "nop" {
-                    nop(state, form)?
-                } else if sym_name == "ret" {
-                    ret(state, form)?
-                } else {
-                    return Err(format!("unknown top-level: {:?}", sym_name));
-                }
-            }
-            sym => return Err(format!("illegal top-level form: {:?}", sym)),
+/// The label addresses that do not change anymore. In an object only
+/// the labels of the label-only sections with an offset and the labels
+/// outside of sections have their final address, the linker moves all
+/// other sections.
+fn final_label_addresses(state: &State) -> HashMap<Label, Address> {
+    state
+        .label_addresses
+        .iter()
+        .filter(|(label, _)| {
+            !state.relocatable
+                || state
+                    .label_sections
+                    .get(*label)
+                    .and_then(|name| state.lookup_section(name))
+                    .is_none_or(|sec| sec.label_only && sec.floating.is_none())
+        })
+        .map(|(label, address)| (label.clone(), *address))
+        .collect()
+}
+
+fn assemble_in_state(pasm: TopLevel, state: &mut State) -> Result<(), Error> {
+    let mut label_refs = assemble_forms(pasm, state)?;
+    place_sections(state, &mut label_refs)?;
+    resolve_labels(label_refs, state)
+}
+
+/// Places the floating sections, in the order of their definition, into
+/// the first free block of their region with the alignment. The labels of
+/// a section and the relative references from it move with the section.
+fn place_sections(state: &mut State, label_refs: &mut [LabelRef]) -> Result<(), Error> {
+    // the blocks taken by the fixed sections, in all banks
+    let mut taken: Vec<Block> = state
+        .sections
+        .iter()
+        .filter(|sec| sec.floating.is_none())
+        .map(|sec| Block {
+            start: sec.offset.0,
+            end: sec.offset.0 + sec.extent(),
+            bank: None,
+        })
+        .collect();
+
+    for ix in 0..state.sections.len() {
+        let sec = &state.sections[ix];
+        let Some(floating) = &sec.floating else {
+            continue;
         };
+        let size = sec.extent();
+        let block = find_free_block(&state.regions, floating, size, &taken).ok_or_else(|| {
+            Error::new(
+                ErrorKind::Range,
+                format!(
+                    "section .{} (0x{:X} bytes, align {}) does not fit into region {}{}",
+                    sec.name,
+                    size,
+                    floating.align,
+                    floating.region,
+                    floating
+                        .bank
+                        .map(|bank| format!(" bank {}", bank))
+                        .unwrap_or_default()
+                ),
+            )
+        })?;
+
+        let name = sec.name.clone();
+        let sec = &mut state.sections[ix];
+        sec.offset = Address(block.start);
+        if let Some(floating) = &mut sec.floating {
+            floating.bank = block.bank;
+        }
+        for (label, address) in state.label_addresses.iter_mut() {
+            if state.label_sections.get(label) == Some(&name) {
+                address.add_bytes(block.start);
+            }
+        }
+        for label_ref in label_refs.iter_mut() {
+            if let Ref::Relative(from, _, _) = &mut label_ref.reference
+                && label_ref.sec_name == name
+            {
+                from.add_bytes(block.start);
+            }
+        }
+        taken.push(block);
+    }
+    check_section_overlaps(state.sections.iter())
+}
+
+/// An address range [start, end), in one bank or in all.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Block {
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    pub(crate) bank: Option<u64>,
+}
+
+impl Block {
+    pub(crate) fn overlaps(&self, other: &Block) -> bool {
+        let same_bank = self.bank.is_none() || other.bank.is_none() || self.bank == other.bank;
+        same_bank && self.start < other.end && other.start < self.end
+    }
+}
+
+/// The first block of size bytes with the alignment in the region of the
+/// floating section that does not overlap the taken blocks.
+pub(crate) fn find_free_block(
+    regions: &[Region],
+    floating: &Floating,
+    size: u64,
+    taken: &[Block],
+) -> Option<Block> {
+    let align_up = |address: u64| address.div_ceil(floating.align) * floating.align;
+    for region in regions.iter().filter(|region| {
+        region.name == floating.region && (floating.bank.is_none() || region.bank == floating.bank)
+    }) {
+        let region_end = region.offset + region.length;
+        let mut start = align_up(region.offset);
+        while start + size <= region_end {
+            let block = Block {
+                start,
+                end: start + size,
+                bank: region.bank,
+            };
+            match taken
+                .iter()
+                .filter(|other| block.overlaps(other))
+                .map(|other| other.end)
+                .max()
+            {
+                Some(end) => start = align_up(end),
+                None => return Some(block),
+            }
+        }
+    }
+    None
+}
 
-        if let Some(label_ref) = may_label_ref {
-            label_refs.push(label_ref);
+/// Checks that the sections with data do not overlap once the growing
+/// sections have their size and the floating sections are placed.
+fn check_section_overlaps<'a>(sections: impl Iterator<Item = &'a Section>) -> Result<(), Error> {
+    let data_sections: Vec<(&Section, Block)> = sections
+        .filter(|sec| !sec.label_only && sec.extent() > 0)
+        .map(|sec| {
+            let block = Block {
+                start: sec.offset.0,
+                end: sec.offset.0 + sec.extent(),
+                bank: sec.floating.as_ref().and_then(|floating| floating.bank),
+            };
+            (sec, block)
+        })
+        .collect();
+    for (ix, (sec, block)) in data_sections.iter().enumerate() {
+        for (other, other_block) in &data_sections[..ix] {
+            if block.overlaps(other_block) {
+                return Err(Error::new(
+                    ErrorKind::Range,
+                    format!(
+                        "section .{} (0x{:04X}-0x{:04X}) overlaps section .{} (0x{:04X}-0x{:04X})",
+                        sec.name,
+                        block.start,
+                        block.end - 1,
+                        other.name,
+                        other_block.start,
+                        other_block.end - 1
+                    ),
+                ));
+            }
         }
     }
+    Ok(())
+}
 
-    resolve_labels(label_refs, state)?;
+/// Assembles the forms, the returned label references are not resolved yet.
+/// Errors are reported with the location of the form.
+fn assemble_forms(pasm: TopLevel, state: &mut State) -> Result<Vec<LabelRef>, Error> {
+    let mut label_refs: Vec<LabelRef> = Vec::new();
 
-    Ok(())
+    for form in pasm.forms {
+        let span = form.span.clone();
+        let form = scope_form(state, form).map_err(|e| e.at(&span))?;
+        if form.op == Symbol::Sym("include".to_string())
+            || form.op == Symbol::Sym("include-once".to_string())
+        {
+            label_refs.append(&mut include(state, form).map_err(|e| e.at(&span))?);
+            continue;
+        }
+        if is_macro_call(state, &form) {
+            label_refs.append(&mut expand_macro(state, form).map_err(|e| e.at(&span))?);
+            continue;
+        }
+        if form.op == Symbol::Sym("scope".to_string()) {
+            label_refs.append(&mut scope(state, form).map_err(|e| e.at(&span))?);
+            continue;
+        }
+        if is_conditional(&form) {
+            label_refs.append(&mut conditional(state, form).map_err(|e| e.at(&span))?);
+            continue;
+        }
+        if is_loop(&form) {
+            label_refs.append(&mut assemble_loop(state, form).map_err(|e| e.at(&span))?);
+            continue;
+        }
+
+        label_refs.append(&mut assemble_form(state, form).map_err(|e| e.at(&span))?);
+    }
+
+    Ok(label_refs)
 }
 
-fn define_label(state: &mut State, label: Label) -> Result<(), String> {
+fn assemble_form(state: &mut State, form: Form) -> Result<Vec<LabelRef>, Error> {
+    // define the label with an adress if the form is labeled
+    if let Some(lbl) = &form.label {
+        define_label(state, lbl.clone())?;
+    }
+
+    // the data directives may reference more than one label
+    if form.op == Symbol::Sym("db".to_string()) {
+        return db(state, form);
+    } else if form.op == Symbol::Sym("dw".to_string()) {
+        return dw(state, form);
+    }
+
+    let may_label_ref = match &form.op {
+        Symbol::Sym(sym_name) => {
+            if sym_name == "def-section" {
+                def_section(state, form)
+            } else if sym_name == "def-region" {
+                def_region(state, form)
+            } else if sym_name == "defmacro" {
+                def_macro(state, form)
+            } else if sym_name == "def-constant" {
+                def_constant(state, form)
+            } else if sym_name == "section" {
+                section(state, form)
+            } else if sym_name == "ds" {
+                ds(state, form)
+            } else if sym_name == "align" {
+                align(state, form)
+            } else if sym_name == "org" {
+                org(state, form)
+            } else if sym_name == "gb-header" {
+                gb_header(state, form)
+            } else if sym_name == "incbin" {
+                incbin(state, form)
+            } else if sym_name == "label" {
+                label(state, form)
+            } else if sym_name == "sub-section" {
+                sub_section(state)
+            // the following forms are tempoarily handled here. Plan is
+            // to convert this to macros that emits bytes with low-level primitives
+            } else if encoder::is_instruction(sym_name) {
+                //machine specific, should not be handled here
+                instruction(state, form)
+            } else {
+                Err(Error::syntax(format!("unknown top-level: {:?}", sym_name)))
+            }
+        }
+        sym => Err(Error::syntax(format!("illegal top-level form: {:?}", sym))),
+    }?;
+    Ok(may_label_ref.into_iter().collect())
+}
+
+fn define_label(state: &mut State, label: Label) -> Result<(), Error> {
     if state.label_addresses.contains_key(&label) {
-        return Err(format!("duplicate label definition: '{}", label.name()));
+        return Err(Error::new(
+            ErrorKind::DuplicateLabel,
+            format!("duplicate label definition: '{}", label.name()),
+        ));
     }
 
+    if let Some(sec_name) = &state.current_section_name {
+        state.label_sections.insert(label.clone(), sec_name.clone());
+    }
     state
         .label_addresses
         .insert(label, state.current_section_address);
@@ -235,34 +756,65 @@ fn define_label(state: &mut State, label: Label) -> Result<(), String> {
     Ok(())
 }
 
-fn resolve_labels(label_refs: Vec<LabelRef>, state: &mut State) -> Result<(), String> {
+fn resolve_labels(label_refs: Vec<LabelRef>, state: &mut State) -> Result<(), Error> {
     for label_ref in label_refs {
-        match &label_ref.reference {
-            Ref::Relative(relative_from, label, check) => {
-                let lbl_address = expect_label_address(state, &label)?;
-                let dist = lbl_address.0 as i32 - relative_from.0 as i32;
-                (check)(dist)?;
-                let sec = state
-                    .lookup_section_mut(&label_ref.sec_name)
-                    .expect("source section not found");
-                sec.memory.mem[label_ref.patch_index] = dist as u8;
-            }
-            Ref::Expression(sexp) => {
-                let address = eval_aar(sexp, &state.label_addresses)?;
-                check_and_write_address(state, &label_ref, address)?;
-            }
-        };
+        resolve_label(&label_ref, state).map_err(|e| e.at(&label_ref.span))?;
     }
     Ok(())
 }
 
+fn resolve_label(label_ref: &LabelRef, state: &mut State) -> Result<(), Error> {
+    match &label_ref.reference {
+        Ref::Relative(relative_from, target, check) => {
+            let address = eval(target, &state.const_values, &state.label_addresses)?;
+            let dist = address as i32 - relative_from.0 as i32;
+            (check)(dist)?;
+            let sec = state
+                .lookup_section_mut(&label_ref.sec_name)
+                .expect("source section not found");
+            sec.memory.mem[label_ref.patch_index] = dist as u8;
+        }
+        Ref::Expression(sexp) => {
+            let address = eval(sexp, &state.const_values, &state.label_addresses)?;
+            check_and_write_address(state, label_ref, address)?;
+        }
+        Ref::HighPage(sexp) => {
+            let address = eval(sexp, &state.const_values, &state.label_addresses)?;
+            check_high_page_address(address)?;
+            let sec = state
+                .lookup_section_mut(&label_ref.sec_name)
+                .expect("source section not found");
+            sec.memory.mem[label_ref.patch_index] = address as u8;
+        }
+        Ref::Immediate8(sexp) => {
+            let v = eval(sexp, &state.const_values, &state.label_addresses)?;
+            check_8_bit_value(v)?;
+            let sec = state
+                .lookup_section_mut(&label_ref.sec_name)
+                .expect("source section not found");
+            sec.memory.mem[label_ref.patch_index] = v as u8;
+        }
+        Ref::Immediate16(sexp) => {
+            let v = eval(sexp, &state.const_values, &state.label_addresses)?;
+            check_16_bit_value(v)?;
+            let bytes = (v as u16).to_le_bytes();
+            let sec = state
+                .lookup_section_mut(&label_ref.sec_name)
+                .expect("source section not found");
+            sec.memory.mem[label_ref.patch_index] = bytes[0];
+            sec.memory.mem[label_ref.patch_index + 1] = bytes[1];
+        }
+    };
+    Ok(())
+}
+
 fn check_and_write_address(
     state: &mut State,
     label_ref: &LabelRef,
-    label_address: Address,
-) -> Result<(), String> {
-    check_16_bit_address_range(label_address.0 as i32)?;
-    let bytes = (label_address.0 as u16).to_le_bytes();
+    address: i64,
+) -> Result<(), Error> {
+    check_16_bit_address_range(address)?;
+    let bytes = (address as u16).to_le_bytes();
     let sec = state
         .lookup_section_mut(&label_ref.sec_name)
         .expect("source section not found");
@@ -271,44 +823,336 @@ fn check_and_write_address(
     Ok(())
 }
 
-fn include(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() < 2 {
+/// Assembles the included file, errors in the file are located in the
+/// file with a note where it was included from. An include-once of a file
+/// that was included before is skipped.
+fn include(state: &mut State, form: Form) -> Result<Vec<LabelRef>, Error> {
+    if let Some(lbl) = &form.label {
+        define_label(state, lbl.clone())?;
+    }
+    let (file_name, text) = read_include(state, &form)?;
+    if form.op.to_string() == "include-once" && state.included.contains(&file_name) {
+        return Ok(Vec::new());
+    }
+
+    let root = state.include_stack.is_empty();
+    if root {
+        let root_name = normalize_path(Path::new(&form.span.source.name));
+        state.included.insert(root_name.clone());
+        state.include_stack.push(root_name);
+    }
+    if state.include_stack.contains(&file_name) {
+        let mut cycle = state.include_stack.clone();
+        cycle.push(file_name);
+        if root {
+            state.include_stack.clear();
+        }
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("include: include cycle {}", cycle.join(" -> ")),
+        ));
+    }
+
+    state.included.insert(file_name.clone());
+    state.include_stack.push(file_name.clone());
+    let included_from = |e: Error| e.with_note(format!("included from {}", form.span));
+    let result = parse_from_source(&file_name, &text)
+        .and_then(|tl| assemble_forms(tl, state))
+        .map_err(included_from);
+    state.include_stack.pop();
+    if root {
+        state.include_stack.clear();
+    }
+    result
+}
+
+/// Finds and reads the included file. A file is searched relative to the
+/// directory of the including file first and then in the include paths,
+/// a :std file in the stdlib directory or the embedded stdlib.
+fn read_include(state: &State, form: &Form) -> Result<(String, String), Error> {
+    let (std, file_name) = include_file_name(form).map_err(Error::syntax)?;
+    let candidates: Vec<String> = if std {
+        match &state.stdlib {
+            Some(dir) => vec![normalize_path(&dir.join(&file_name))],
+            None => vec![format!("{}/{}", EMBEDDED_STDLIB, file_name)],
+        }
+    } else {
+        search_paths(state, form, &file_name)
+    };
+    read_first(&candidates, |path| read_source(state, path)).map_err(|e| e.prefixed("include"))
+}
+
+/// The paths of the file name used in the form, relative to the directory
+/// of the file of the form first and then relative to the include paths.
+fn search_paths(state: &State, form: &Form, file_name: &str) -> Vec<String> {
+    let including = Path::new(&form.span.source.name);
+    let dir = including.parent().unwrap_or(Path::new(""));
+    std::iter::once(dir)
+        .chain(state.include_paths.iter().map(|path| path.as_path()))
+        .map(|dir| normalize_path(&dir.join(file_name)))
+        .collect()
+}
+
+/// Reads the first of the candidate paths that exists. If none exists the
+/// error is the one of the first candidate with a note of the searched paths.
+fn read_first<T>(
+    candidates: &[String],
+    read: impl Fn(&str) -> io::Result<T>,
+) -> Result<(String, T), Error> {
+    let mut not_found = None;
+    for candidate in candidates {
+        match read(candidate) {
+            Ok(content) => return Ok((candidate.clone(), content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                not_found.get_or_insert(e);
+            }
+            Err(e) => return Err(Error::io(candidate, e)),
+        }
+    }
+    let err = Error::io(&candidates[0], not_found.expect("a candidate"));
+    if candidates.len() > 1 {
+        return Err(err.with_note(format!("searched {}", candidates.join(", "))));
+    }
+    Err(err)
+}
+
+fn read_source(state: &State, path: &str) -> io::Result<String> {
+    match path
+        .strip_prefix(EMBEDDED_STDLIB)
+        .and_then(|p| p.strip_prefix('/'))
+    {
+        Some(std_path) => embedded(std_path)
+            .map(str::to_string)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not in the embedded stdlib")),
+        None => state.fs.read_to_string(path),
+    }
+}
+
+/// The path without . and with the .. resolved where possible, the name
+/// of the file for include-once and the cycle detection.
+fn normalize_path(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut root = String::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => root.push_str(&prefix.as_os_str().to_string_lossy()),
+            Component::RootDir => root.push('/'),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if parts.last().is_some_and(|part| part != "..") {
+                    parts.pop();
+                } else if root.is_empty() {
+                    parts.push("..".to_string());
+                }
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+        }
+    }
+    format!("{}{}", root, parts.join("/"))
+}
+
+fn is_macro_call(state: &State, form: &Form) -> bool {
+    match &form.op {
+        Symbol::Sym(name) => state.macros.contains_key(name),
+        _ => false,
+    }
+}
+
+/// Assembles the expansion of the macro call, errors in the expansion are
+/// located in the macro definition with a note where it was called.
+fn expand_macro(state: &mut State, form: Form) -> Result<Vec<LabelRef>, Error> {
+    if let Some(lbl) = &form.label {
+        define_label(state, lbl.clone())?;
+    }
+    let name = form.op.to_string();
+    if state.macro_depth >= MAX_MACRO_DEPTH {
+        return Err(Error::syntax(format!(
+            "{}: more than {} nested macro expansions, is the macro recursive?",
+            name, MAX_MACRO_DEPTH
+        )));
+    }
+    let forms = state.macros[&name].expand(&form).map_err(Error::syntax)?;
+
+    state.macro_depth += 1;
+    state.scopes.push();
+    let result = assemble_forms(TopLevel { forms }, state);
+    state.scopes.pop();
+    state.macro_depth -= 1;
+    result.map_err(|e| e.with_note(format!("in expansion of {} at {}", name, form.span)))
+}
+
+/// Replaces the written labels of the form by their unique names, see
+/// Scopes. The bodies of the forms that contain other forms are replaced
+/// when the forms are assembled, in their own scope.
+fn scope_form(state: &mut State, mut form: Form) -> Result<Form, Error> {
+    let label = match form.label.take() {
+        Some(lbl) => Some(state.scopes.define(&lbl).map_err(Error::syntax)?),
+        None => None,
+    };
+    let mut form = match &form.op {
+        Symbol::Sym(name) if name == "label" => {
+            if let Some(SExp::Symbol(Symbol::Label(lbl))) = form.exps.first_mut() {
+                *lbl = state.scopes.define(lbl).map_err(Error::syntax)?;
+            }
+            form
+        }
+        Symbol::Sym(name) if name == "scope" || name == "defmacro" => form,
+        _ if is_conditional(&form) || is_loop(&form) => form,
+        _ => state.scopes.resolve_form(form).map_err(Error::syntax)?,
+    };
+    form.label = label;
+    Ok(form)
+}
+
+/// Assembles the forms of (scope form...), the local labels of the forms
+/// are not visible outside.
+fn scope(state: &mut State, form: Form) -> Result<Vec<LabelRef>, Error> {
+    if let Some(lbl) = &form.label {
+        define_label(state, lbl.clone())?;
+    }
+    let forms = form
+        .exps
+        .into_iter()
+        .map(|exp| match exp {
+            SExp::Form(form) => Ok(form),
+            exp => Err(format!("scope: form expected, got {}", exp)),
+        })
+        .collect::<Result<Vec<Form>, String>>()
+        .map_err(Error::syntax)?;
+
+    state.scopes.push();
+    let result = assemble_forms(TopLevel { forms }, state);
+    state.scopes.pop();
+    result
+}
+
+fn is_conditional(form: &Form) -> bool {
+    match &form.op {
+        Symbol::Sym(name) => CONDITIONAL_OPS.contains(&name.as_str()),
+        _ => false,
+    }
+}
+
+/// Assembles the forms selected by the conditional form. The conditions
+/// are evaluated when the form is reached, only constants defined before
+/// are known.
+fn conditional(state: &mut State, form: Form) -> Result<Vec<LabelRef>, Error> {
+    if let Some(lbl) = &form.label {
+        define_label(state, lbl.clone())?;
+    }
+    let forms = conditional_forms(&form, &state.const_values)?;
+    assemble_forms(TopLevel { forms }, state)
+}
+
+fn is_loop(form: &Form) -> bool {
+    match &form.op {
+        Symbol::Sym(name) => LOOP_OPS.contains(&name.as_str()),
+        _ => false,
+    }
+}
+
+/// Assembles the body of the loop form once per iteration. The loop
+/// variable is a constant while the body is assembled, a constant of the
+/// same name is restored afterwards.
+fn assemble_loop(state: &mut State, form: Form) -> Result<Vec<LabelRef>, Error> {
+    if let Some(lbl) = &form.label {
+        define_label(state, lbl.clone())?;
+    }
+    let lp = loop_values(&form, &state.const_values)?;
+    let shadowed = lp
+        .var
+        .as_ref()
+        .and_then(|var| state.const_values.get(var).copied());
+
+    let mut label_refs = Vec::new();
+    let mut result = Ok(());
+    for (i, value) in lp.values.iter().enumerate() {
+        if let Some(var) = &lp.var {
+            state.const_values.insert(var.clone(), *value);
+        }
+        let forms = lp.body.clone();
+        match assemble_forms(TopLevel { forms }, state) {
+            Ok(mut refs) => label_refs.append(&mut refs),
+            Err(e) => {
+                let note = match &lp.var {
+                    Some(var) => {
+                        format!("in {} with {} = {} at {}", form.op, var, value, form.span)
+                    }
+                    None => format!("in {} iteration {} at {}", form.op, i, form.span),
+                };
+                result = Err(e.with_note(note));
+                break;
+            }
+        }
+    }
+
+    if let Some(var) = &lp.var {
+        match shadowed {
+            Some(value) => state.const_values.insert(var.clone(), value),
+            None => state.const_values.remove(var),
+        };
+    }
+    result.map(|_| label_refs)
+}
+
+fn def_macro(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    let mac = define_macro(&form).map_err(Error::syntax)?;
+    if BUILTIN_FORMS.contains(&mac.name.as_str())
+        || CONDITIONAL_OPS.contains(&mac.name.as_str())
+        || LOOP_OPS.contains(&mac.name.as_str())
+        || encoder::is_instruction(&mac.name)
+    {
+        return Err(Error::syntax(format!(
+            "defmacro: {} is a built-in form",
+            mac.name
+        )));
+    }
+    if state.macros.contains_key(&mac.name) {
+        return Err(Error::syntax(format!(
+            "defmacro: duplicate macro definition: {}",
+            mac.name
+        )));
+    }
+    state.macros.insert(mac.name.clone(), mac);
+    Ok(None)
+}
+
+/// The file of the include form, whether it is a :std file and the path
+/// with the .asm extension.
+fn include_file_name(form: &Form) -> Result<(bool, String), String> {
+    if form.exps.is_empty() {
         return Err("include must at least provide file to include".to_string());
     }
 
-    let file_name = if is_keyword(&form.exps[0], "std") {
+    if is_keyword(&form.exps[0], "std") {
         expect_has_sexp_at(&form.exps, 1, "std include path required")?;
         let std_file = expect_string(&form.exps[1])?;
-        format!("stdlib/{}.asm", std_file)
+        Ok((true, format!("{}.asm", std_file)))
     } else {
         expect_has_sexp_at(&form.exps, 0, "include path required")?;
         let file = expect_string(&form.exps[0])?;
-        format!("{}.asm", file)
-    };
-
-    let mut file = File::open(file_name).map_err(|e| e.to_string())?;
-    let tl = parse_from_file(&mut file)?;
-    assemble_in_state(tl, state)?;
-    Ok(None)
+        Ok((false, format!("{}.asm", file)))
+    }
 }
 
-fn def_section(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() == 0 {
-        return Err("illegal def-section".to_string());
+fn def_section(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    if form.exps.is_empty() {
+        return Err(Error::syntax("illegal def-section"));
     }
 
-    let name = expect_section_name(&form.exps[0])?;
-    let offset_val = expect_immediate_value_or(key_value(&form.exps, "offset")?, 0)?;
+    let name = expect_section_name(&form.exps[0]).map_err(Error::syntax)?;
+    let may_offset = key_value(&form.exps, "offset").map_err(Error::syntax)?;
+    let offset_val = expect_immediate_value_or(may_offset, 0).map_err(Error::syntax)?;
     if offset_val.is_negative() {
-        return Err("def-section: offset must be positive".to_string());
+        return Err(Error::syntax("def-section: offset must be positive"));
     }
     let offset = Address(offset_val as u64);
 
-    let may_length = key_value(&form.exps, "length")?;
+    let may_length = key_value(&form.exps, "length").map_err(Error::syntax)?;
     let length = if let Some(exp) = may_length {
-        let immediate = expect_immediate(exp)?;
+        let immediate = expect_immediate(exp).map_err(Error::syntax)?;
         if immediate.is_negative() {
-            return Err("def-section: length must be positive".to_string());
+            return Err(Error::syntax("def-section: length must be positive"));
         }
         Some(immediate as u64)
     } else {
@@ -316,8 +1160,19 @@ fn def_section(state: &mut State, form: Form) -> Result<Option<LabelRef>, String
     };
 
     let false_default = &sym_false();
-    let label_only_sym = expect_symbol_or(key_value(&form.exps, "label-only")?, false_default)?;
-    let label_only = expect_bool_sym(label_only_sym)?;
+    let label_only_sym = expect_symbol_or(
+        key_value(&form.exps, "label-only").map_err(Error::syntax)?,
+        false_default,
+    )
+    .map_err(Error::syntax)?;
+    let label_only = expect_bool_sym(label_only_sym).map_err(Error::syntax)?;
+
+    let floating = floating_placement(state, &form).map_err(Error::syntax)?;
+    if floating.is_some() && may_offset.is_some() {
+        return Err(Error::syntax(
+            "def-section: a section has either an offset or a region",
+        ));
+    }
 
     let memory = if let Some(len) = length {
         Memory {
@@ -331,628 +1186,793 @@ fn def_section(state: &mut State, form: Form) -> Result<Option<LabelRef>, String
         }
     };
 
-    state.sections.push(Section {
+    let section = Section {
         name,
         offset,
         length,
         label_only,
         memory,
+        reserved: 0,
+        floating,
+        data_align: 1,
+        pinned: false,
+    };
+    check_section_placement(state, &section)?;
+    state.sections.push(section);
+    Ok(None)
+}
+
+/// The :region, :align and :bank of a floating section.
+fn floating_placement(state: &State, form: &Form) -> Result<Option<Floating>, String> {
+    let align = key_value(&form.exps, "align")?;
+    let bank = key_value(&form.exps, "bank")?;
+    let region = match key_value(&form.exps, "region")? {
+        Some(exp) => expect_region_name(exp)?,
+        None if align.is_some() || bank.is_some() => {
+            return Err("def-section: :align and :bank need a :region".to_string());
+        }
+        None => return Ok(None),
+    };
+    if !state.regions.iter().any(|r| r.name == region) {
+        return Err(format!("def-section: no such region: {}", region));
+    }
+
+    let align = expect_immediate_value_or(align, 1)?;
+    if align < 1 {
+        return Err("def-section: align must be at least 1".to_string());
+    }
+    let bank = match bank {
+        Some(exp) => {
+            let bank = expect_immediate(exp)?;
+            if bank.is_negative() {
+                return Err("def-section: bank must be positive".to_string());
+            }
+            if !state
+                .regions
+                .iter()
+                .any(|r| r.name == region && r.bank == Some(bank as u64))
+            {
+                return Err(format!(
+                    "def-section: region {} has no bank {}",
+                    region, bank
+                ));
+            }
+            Some(bank as u64)
+        }
+        None => None,
+    };
+    Ok(Some(Floating {
+        region,
+        align: align as u64,
+        bank,
+    }))
+}
+
+/// (def-region name :offset o :length l [:bank b])
+fn def_region(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    if form.exps.is_empty() {
+        return Err(Error::syntax("def-region: region name required"));
+    }
+    let name = expect_region_name(&form.exps[0]).map_err(Error::syntax)?;
+    let offset = expect_immediate(
+        key_value(&form.exps, "offset")
+            .map_err(Error::syntax)?
+            .ok_or_else(|| Error::syntax("def-region: offset required"))?,
+    )
+    .map_err(Error::syntax)?;
+    let length = expect_immediate(
+        key_value(&form.exps, "length")
+            .map_err(Error::syntax)?
+            .ok_or_else(|| Error::syntax("def-region: length required"))?,
+    )
+    .map_err(Error::syntax)?;
+    if offset.is_negative() || length.is_negative() {
+        return Err(Error::syntax(
+            "def-region: offset and length must be positive",
+        ));
+    }
+    let bank = match key_value(&form.exps, "bank").map_err(Error::syntax)? {
+        Some(exp) => {
+            let bank = expect_immediate(exp).map_err(Error::syntax)?;
+            if bank.is_negative() {
+                return Err(Error::syntax("def-region: bank must be positive"));
+            }
+            Some(bank as u64)
+        }
+        None => None,
+    };
+    check_address_space(offset as u64, length as u64, &format!("region {}", name))?;
+    if state
+        .regions
+        .iter()
+        .any(|r| r.name == name && (r.bank == bank || r.bank.is_none() || bank.is_none()))
+    {
+        return Err(Error::syntax(format!(
+            "def-region: duplicate region definition: {}",
+            name
+        )));
+    }
+    state.regions.push(Region {
+        name,
+        offset: offset as u64,
+        length: length as u64,
+        bank,
     });
     Ok(None)
 }
 
-fn def_constant(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
+pub(crate) fn expect_region_name(exp: &SExp) -> Result<String, String> {
+    match exp {
+        SExp::Symbol(Symbol::Sym(name)) if !name.is_empty() => Ok(name.clone()),
+        exp => Err(format!("region name expected, got {}", exp)),
+    }
+}
+
+/// Checks that the section is within the address space and does not
+/// overlap a section with data. Label-only sections name a memory region
+/// and may overlap other sections.
+fn check_section_placement(state: &State, section: &Section) -> Result<(), Error> {
+    if state.lookup_section(&section.name).is_some() {
+        return Err(Error::syntax(format!(
+            "duplicate section definition: .{}",
+            section.name
+        )));
+    }
+    if section.floating.is_some() {
+        return Ok(());
+    }
+    let what = format!("section .{}", section.name);
+    let Some(length) = section.length else {
+        return check_address_space(section.offset.0, 0, &what);
+    };
+    check_address_space(section.offset.0, length, &what)?;
+    if section.label_only || length == 0 {
+        return Ok(());
+    }
+
+    let start = section.offset.0;
+    let end = start + length;
+    for other in &state.sections {
+        let Some(other_length) = other.length.filter(|_| other.floating.is_none()) else {
+            continue;
+        };
+        let other_start = other.offset.0;
+        let other_end = other_start + other_length;
+        if !other.label_only && start < other_end && other_start < end {
+            return Err(Error::new(
+                ErrorKind::Range,
+                format!(
+                    "section .{} (0x{:04X}-0x{:04X}) overlaps section .{} (0x{:04X}-0x{:04X})",
+                    section.name,
+                    start,
+                    end - 1,
+                    other.name,
+                    other_start,
+                    other_end - 1
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the section or region, e.g. "section .rom0", is within
+/// the address space.
+fn check_address_space(offset: u64, length: u64, what: &str) -> Result<(), Error> {
+    if offset + length > 0x10000 || offset > 0xFFFF {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "{} (0x{:04X}, length 0x{:X}) exceeds the address space 0x0000-0xFFFF",
+                what, offset, length
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn def_constant(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
     if form.exps.len() != 2 {
-        return Err("illegal def-constant".to_string());
+        return Err(Error::syntax("illegal def-constant"));
     }
 
-    let const_name = expect_constant_name(&form.exps[0])?;
+    let const_name = expect_constant_name(&form.exps[0]).map_err(Error::syntax)?;
     let const_val = eval_const(&form.exps[1], &state.const_values)?;
     state.const_values.insert(const_name, const_val);
     Ok(None)
 }
 
-fn section(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
+fn section(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
     if form.exps.len() != 1 {
-        return Err("illegal section".to_string());
+        return Err(Error::syntax("illegal section"));
     }
 
-    let name = expect_section_name(&form.exps[0])?;
+    let name = expect_section_name(&form.exps[0]).map_err(Error::syntax)?;
     let may_section = state.lookup_section(&name);
     if let Some(section) = may_section {
-        let addr = section.offset;
+        // a section continues after the bytes it already has
+        let used = if section.label_only {
+            section.reserved
+        } else {
+            section.memory.mem_ptr as u64
+        };
+        let addr = Address(section.offset.0 + used);
         let name = section.name.clone();
         state.current_section_address = addr;
         state.current_section_name = Some(name);
         Ok(None)
     } else {
-        Err(format!("no such section: {}", name))
+        Err(Error::syntax(format!("no such section: {}", name)))
     }
 }
 
-fn db(state: &mut State, db: Form) -> Result<Option<LabelRef>, String> {
+/// A db or dw operand, an operand with a label is written once the
+/// labels are resolved.
+enum DataValue {
+    Known(i64),
+    Deferred(SExp),
+}
+
+fn data_value(state: &State, exp: &SExp, bits: u32, form: &Form) -> Result<DataValue, Error> {
+    if contains_label(exp) {
+        return Ok(DataValue::Deferred(fold_constants(
+            exp,
+            &state.const_values,
+        )?));
+    }
+    let v = eval_const(exp, &state.const_values)?;
+    check_immediate_range(v, bits, form)?;
+    Ok(DataValue::Known(v))
+}
+
+fn db(state: &mut State, db: Form) -> Result<Vec<LabelRef>, Error> {
     expect_in_section(state)?;
+    let mut label_refs = Vec::new();
     if !db.exps.is_empty() {
+        check_section_space(state, db.exps.len())?;
+        expect_in_w_sec(state)?;
         state
             .current_section_address
             .add_bytes(db.exps.len() as u64);
-        let sec = expect_in_w_sec(state)?;
+        let mut values = Vec::with_capacity(db.exps.len());
         for exp in &db.exps {
-            let v = expect_immediate(exp)?;
-            sec.memory.push_u8(v as u8);
+            values.push(data_value(state, exp, 8, &db)?);
+        }
+        let sec = expect_in_w_sec(state)?;
+        for value in values {
+            match value {
+                DataValue::Known(v) => sec.memory.push_u8(v as u8),
+                DataValue::Deferred(exp) => {
+                    label_refs.push(LabelRef {
+                        reference: Ref::Immediate8(exp),
+                        sec_name: sec.name.clone(),
+                        patch_index: sec.memory.mem_ptr,
+                        span: db.span.clone(),
+                    });
+                    sec.memory.push_u8(0);
+                }
+            }
         }
     } else {
         // only advance address so that marking locations also
         // works in read-only memory
+        if is_label_only(state) {
+            check_section_space(state, 1)?;
+        }
         state.current_section_address.add_bytes(1);
+        reserve_label_only(state);
     }
-    Ok(None)
+    Ok(label_refs)
 }
 
-fn dw(state: &mut State, db: Form) -> Result<Option<LabelRef>, String> {
+fn dw(state: &mut State, db: Form) -> Result<Vec<LabelRef>, Error> {
     expect_in_section(state)?;
+    let mut label_refs = Vec::new();
     if !db.exps.is_empty() {
+        check_section_space(state, db.exps.len() * 2)?;
+        expect_in_w_sec(state)?;
         state
             .current_section_address
             .add_bytes((db.exps.len() * 2) as u64);
-        let sec = expect_in_w_sec(state)?;
+        let mut values = Vec::with_capacity(db.exps.len());
         for exp in &db.exps {
-            if let Some(v) = is_immediate(exp) {
-                sec.memory.push_u16(v as u16);
-            } else if let Some(tile) = is_gameboy_tile(exp) {
-                let v = tile_to_u16(tile)?;
-                sec.memory.push_u16(v);
+            if let Some(tile) = is_gameboy_tile(exp) {
+                values.push(DataValue::Known(
+                    tile_to_u16(tile).map_err(Error::syntax)? as i64
+                ));
             } else {
-                return Err(
-                    format!("dw: immediate or tile data expected, got {:?}", exp).to_string(),
-                );
+                values.push(data_value(state, exp, 16, &db)?);
+            }
+        }
+        let sec = expect_in_w_sec(state)?;
+        for value in values {
+            match value {
+                DataValue::Known(v) => sec.memory.push_u16(v as u16),
+                DataValue::Deferred(exp) => {
+                    label_refs.push(LabelRef {
+                        reference: Ref::Immediate16(exp),
+                        sec_name: sec.name.clone(),
+                        patch_index: sec.memory.mem_ptr,
+                        span: db.span.clone(),
+                    });
+                    sec.memory.push_u16(0);
+                }
             }
         }
     } else {
+        if is_label_only(state) {
+            check_section_space(state, 2)?;
+        }
         state.current_section_address.add_bytes(2);
+        reserve_label_only(state);
     }
-    Ok(None)
+    Ok(label_refs)
 }
 
-fn ds(state: &mut State, ds: Form) -> Result<Option<LabelRef>, String> {
+/// (ds len [:fill b]) writes len times the fill byte, 0 by default.
+fn ds(state: &mut State, ds: Form) -> Result<Option<LabelRef>, Error> {
     expect_in_section(state)?;
 
     if ds.exps.is_empty() {
-        return Err("ds: needs at least a len".to_string());
+        return Err(Error::syntax("ds: needs at least a len"));
     }
 
-    let len = expect_immediate(&ds.exps[0])?;
+    let len = expect_immediate(&ds.exps[0]).map_err(Error::syntax)?;
     if len.is_negative() {
-        return Err("ds: len must be positive".to_string());
-    }
-    state.current_section_address.add_bytes(len as u64);
-    let sec = expect_in_w_sec(state)?;
-    for _ in 0..len {
-        sec.memory.push_u8(0);
+        return Err(Error::syntax("ds: len must be positive"));
     }
-
+    let fill = fill_byte(state, &ds, "ds")?;
+    expect_in_w_sec(state)?;
+    skip_bytes(state, len as u64, fill)?;
     Ok(None)
 }
 
-fn label(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() < 1 {
-        return Err("label: needs at one argument".to_string());
-    }
-
-    let may_label = is_label(&form.exps[0]);
-    if let Some(lbl) = may_label {
-        define_label(state, lbl.clone())?;
-        Ok(None)
-    } else {
-        Err("label: need a label as argument".to_string())
-    }
-}
-
-fn sub_section(state: &mut State) -> Result<Option<LabelRef>, String> {
-    println!("!sub-section");
-    Ok(None)
-}
-
-// non-primitive forms, temporarily implemented in Rust directly
-
-fn cp(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() < 1 {
-        return Err(format!("cp: needs exactly one argument"));
-    }
-
-    match &form.exps[0] {
-        SExp::Immediate(im_val) => {
-            state.current_section_address.add_bytes(2);
-            let sec = expect_in_w_sec(state)?;
-            sec.memory.push_u8(sm83::INSTR_CP_IMMEDIATE.op_code);
-            sec.memory.push_u8(*im_val as u8);
-        }
-        illegal => return Err(format!("cp: illegal parameters: {:?}", illegal)),
-    }
-
+/// (align n [:fill b]) moves the current address to the next multiple
+/// of n. The address of a floating section is only known relative to
+/// its :align.
+fn align(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    expect_in_section(state)?;
+    let n = eval_const(
+        form.exps
+            .first()
+            .ok_or_else(|| Error::syntax("align: alignment required"))?,
+        &state.const_values,
+    )?;
+    if n < 1 {
+        return Err(Error::syntax(format!(
+            "align: alignment must be at least 1, got {}",
+            n
+        )));
+    }
+    let n = n as u64;
+    let fill = fill_byte(state, &form, "align")?;
+    let sec = current_section(state);
+    if let Some(floating) = &sec.floating
+        && floating.align % n != 0
+    {
+        return Err(Error::syntax(format!(
+            "align: section .{} is placed with align {}, cannot align to {}",
+            sec.name, floating.align, n
+        )));
+    }
+    let sec = current_section_mut(state);
+    sec.data_align = lcm(sec.data_align, n);
+    let address = state.current_section_address.0;
+    skip_bytes(state, address.div_ceil(n) * n - address, fill)?;
     Ok(None)
 }
 
-fn or(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() != 2 {
-        return Err(format!("or: needs exactly two arguments"));
+/// The least common multiple of two alignments.
+pub(crate) fn lcm(a: u64, b: u64) -> u64 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
     }
-
-    let exp_0 = &form.exps[0];
-    let exp_1 = &form.exps[1];
-    let op = match (exp_0, exp_1) {
-        (SExp::Symbol(Symbol::Reg(reg_0)), SExp::Symbol(Symbol::Reg(reg_1))) => {
-            match (reg_0.as_str(), reg_1.as_str()) {
-                (sm83::REG_A, sm83::REG_C) => INSTR_OR_A_C.op_code,
-                illegal => return Err(format!("or: illegal registers: {:?}", illegal)),
-            }
-        }
-        (reg_0, reg_1) => return Err(format!("or: illegal parameters: {:?} {:?}", reg_0, reg_1)),
-    };
-
-    state.current_section_address.add_bytes(1);
-    let sec = expect_in_w_sec(state)?;
-    sec.memory.push_u8(op);
-    Ok(None)
+    a / x * b
 }
 
-fn nop(state: &mut State, mut _form: Form) -> Result<Option<LabelRef>, String> {
-    state.current_section_address.add_bytes(1);
-    let sec = expect_in_w_sec(state)?;
-    sec.memory.push_u8(INSTR_NOP.op_code);
+/// (org address [:fill b]) moves the current address forward to the
+/// address in a section with an offset.
+fn org(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    expect_in_section(state)?;
+    let target = eval_const(
+        form.exps
+            .first()
+            .ok_or_else(|| Error::syntax("org: address required"))?,
+        &state.const_values,
+    )?;
+    let fill = fill_byte(state, &form, "org")?;
+    let sec = current_section(state);
+    if sec.floating.is_some() {
+        return Err(Error::syntax(format!(
+            "org: section .{} has no fixed offset",
+            sec.name
+        )));
+    }
+    let address = state.current_section_address.0;
+    if target < address as i64 {
+        return Err(Error::syntax(format!(
+            "org: 0x{:04X} is before the current address 0x{:04X}",
+            target, address
+        )));
+    }
+    current_section_mut(state).pinned = true;
+    skip_bytes(state, target as u64 - address, fill)?;
     Ok(None)
 }
 
-fn ld(state: &mut State, mut form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() < 2 {
-        return Err(format!("ld: needs at least two arguments"));
-    }
-    let exp_1 = form.exps.pop().unwrap();
-    let exp_0 = form.exps.pop().unwrap();
-    match (exp_0, exp_1) {
-        (SExp::Symbol(Symbol::Reg(dst_reg)), SExp::Symbol(Symbol::Label(lbl))) => {
-            let op = match dst_reg.as_str() {
-                sm83::REG_BC => INSTR_LD_TO_BC_FROM_IMMEDIATE.op_code,
-                sm83::REG_DE => INSTR_LD_TO_DE_FROM_IMMEDIATE.op_code,
-                sm83::REG_HL => INSTR_LD_TO_HL_FROM_IMMEDIATE.op_code,
-                _ => return Err(format!("ld: unknown target register: {}", dst_reg)),
-            };
-
-            state.current_section_address.add_bytes(3);
-
-            let sec = expect_in_w_sec(state)?;
-            sec.memory.push_u8(op);
-            sec.memory.push_u16(0);
-            Ok(Some(LabelRef {
-                reference: Ref::from_label(lbl),
-                sec_name: sec.name.clone(),
-                patch_index: sec.memory.mem_ptr - 2,
-            }))
-        }
-        (SExp::Symbol(Symbol::Reg(dst_reg)), SExp::Immediate(im_value)) => {
-            ld_immediate_to_reg(state, dst_reg, im_value)
-        }
-        (SExp::Symbol(Symbol::Reg(dst_reg)), SExp::Form(form)) => {
-            if let Symbol::Sym(sym) = &form.op {
-                if is_const_expression_op(sym) {
-                    let val = eval_const(&SExp::Form(form), &state.const_values)?;
-                    ld_immediate_to_reg(state, dst_reg, val)
-                } else {
-                    // deref from label
-                    let op = match dst_reg.as_str() {
-                        sm83::REG_A => INSTR_LD_TO_A_FROM_DEREF_LABEL.op_code, // label address will be derefed and the value taken
-                        sm83::REG_BC => INSTR_LD_TO_BC_FROM_IMMEDIATE.op_code, // label address will be taken as immediate
-                        _ => return Err("ld: illegal dest reg in label deref".to_string()),
-                    };
-
-                    state.current_section_address.add_bytes(3);
-
-                    let sec = expect_in_w_sec(state)?;
-                    sec.memory.push_u8(op);
-                    sec.memory.push_u16(0);
-
-                    let label_ref = if sym == "" && form.label.is_some() {
-                        let form_label = form.label.expect("form label");
-                        LabelRef {
-                            reference: Ref::from_label(form_label),
-                            sec_name: sec.name.clone(),
-                            patch_index: sec.memory.mem_ptr - 2,
-                        }
-                    } else {
-                        LabelRef {
-                            reference: Ref::from_form(form),
-                            sec_name: sec.name.clone(),
-                            patch_index: sec.memory.mem_ptr - 2,
-                        }
-                    };
-
-                    Ok(Some(label_ref))
-                }
+/// (gb-header [:title "T"] [:cgb b] [:sgb true] [:cartridge-type b]
+/// [:rom-size b] [:ram-size b] [:licensee "NN" or b]) writes the cartridge
+/// header at 0x0104, after the entry point. The checksums are computed
+/// from the ROM after assembly or linking.
+fn gb_header(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    expect_in_section(state)?;
+    if state.gb_header {
+        return Err(Error::syntax("gb-header: duplicate header"));
+    }
+    let sec = current_section(state);
+    let address = state.current_section_address.0;
+    if sec.floating.is_some() || address != gb::LOGO_OFFSET as u64 {
+        return Err(Error::syntax(format!(
+            "gb-header: the header starts at 0x{:04X}, the current address is {}",
+            gb::LOGO_OFFSET,
+            if sec.floating.is_some() {
+                format!("floating in .{}", sec.name)
             } else {
-                let (src_reg, deref_mod) = expect_deref_reg(&form)?;
-                let op = match (dst_reg.as_str(), src_reg, deref_mod) {
-                    (sm83::REG_A, sm83::REG_DE, None) => INSTR_LD_TO_A_FROM_DEREF_DE.op_code,
-                    (sm83::REG_A, sm83::REG_HL, None) => INSTR_LD_TO_A_FROM_DEREF_HL.op_code,
-                    (sm83::REG_A, sm83::REG_HL, Some("+")) => {
-                        INSTR_LD_TO_A_FROM_DEREF_HL_INC.op_code
-                    }
-                    _ => {
-                        return Err(format!(
-                            "ld: unknown source register: {}, {:?}",
-                            dst_reg, form
-                        ));
-                    }
-                };
-
-                state.current_section_address.add_bytes(1);
-
-                let sec = expect_in_w_sec(state)?;
-                sec.memory.push_u8(op);
-                Ok(None)
+                format!("0x{:04X}", address)
             }
-        }
-        (SExp::Form(form), SExp::Immediate(im_value)) => {
-            let op = match &form.op {
-                Symbol::Reg(reg) => match reg.as_str() {
-                    sm83::REG_HL => INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE.op_code,
-                    _ => return Err(format!("ld: unknown deref register: {}", reg)),
-                },
-                illegal => return Err(format!("ld: illegal deref: {:?}", illegal)),
-            };
-
-            state.current_section_address.add_bytes(2);
-
-            // TODO check range of immediate value!
-            let sec = expect_in_w_sec(state)?;
-            sec.memory.push_u8(op);
-            sec.memory.push_u8(im_value as u8);
+        )));
+    }
 
-            Ok(None)
+    let title = match key_value(&form.exps, "title").map_err(Error::syntax)? {
+        Some(exp) => {
+            expect_string(exp).map_err(|e| Error::syntax(format!("gb-header: title {}", e)))?
         }
-        (SExp::Form(form), SExp::Symbol(Symbol::Reg(src_reg))) => {
-            if let Symbol::Sym(sym) = &form.op {
-                if sym != "" || form.label.is_none() {
-                    return Err(format!("ld: illegal dest label deref: {:?}", form));
-                }
-
-                let op = match src_reg.as_str() {
-                    sm83::REG_A => INSTR_LD_TO_DEREF_LABEL_FROM_A.op_code,
-                    _ => return Err("ld: illegal source reg in label deref".to_string()),
-                };
-
-                state.current_section_address.add_bytes(3);
-                let form_label = form.label.expect("form label");
-
-                let sec = expect_in_w_sec(state)?;
-                sec.memory.push_u8(op);
-                sec.memory.push_u16(0);
-                Ok(Some(LabelRef {
-                    reference: Ref::from_label(form_label),
-                    sec_name: sec.name.clone(),
-                    patch_index: sec.memory.mem_ptr - 2,
-                }))
-            } else {
-                let (dst_reg, deref_mod) = expect_deref_reg(&form)?;
-                let op = match (dst_reg, deref_mod, src_reg.as_str()) {
-                    (sm83::REG_DE, None, sm83::REG_A) => INSTR_LD_TO_DEREF_DE_FROM_A.op_code,
-                    (sm83::REG_HL, None, sm83::REG_A) => INSTR_LD_TO_DEREF_HL_FROM_A.op_code,
-                    (sm83::REG_HL, Some("+"), sm83::REG_A) => {
-                        INSTR_LD_TO_DEREF_HL_INC_FROM_A.op_code
-                    }
-                    illegal => return Err(format!("ld: illegal deref from reg: {:?}", illegal)),
-                };
-
-                state.current_section_address.add_bytes(1);
-
-                let sec = expect_in_w_sec(state)?;
-                sec.memory.push_u8(op);
-                Ok(None)
-            }
+        None => String::new(),
+    };
+    let sgb = match key_value(&form.exps, "sgb").map_err(Error::syntax)? {
+        Some(SExp::Symbol(sym)) => expect_bool_sym(sym).map_err(Error::syntax)?,
+        Some(exp) => {
+            return Err(Error::syntax(format!(
+                "gb-header: sgb must be true or false, got {}",
+                exp
+            )));
         }
-        (SExp::Symbol(Symbol::Reg(dst_reg)), SExp::Symbol(Symbol::Reg(src_reg))) => {
-            let op = match (dst_reg.as_str(), src_reg.as_str()) {
-                (sm83::REG_A, sm83::REG_B) => INSTR_LD_TO_A_FROM_B.op_code,
-                illegal => {
-                    return Err(format!(
-                        "ld: unknown target/source register combination: {:?}",
-                        illegal
-                    ));
-                }
-            };
-
-            state.current_section_address.add_bytes(1);
-
-            let sec = expect_in_w_sec(state)?;
-            sec.memory.push_u8(op);
+        None => false,
+    };
+    let licensee = match key_value(&form.exps, "licensee").map_err(Error::syntax)? {
+        Some(SExp::String(code)) => Licensee::New(
+            code.as_bytes()
+                .try_into()
+                .map_err(|_| {
+                    format!(
+                        "gb-header: licensee must be two characters or a byte, got {:?}",
+                        code
+                    )
+                })
+                .map_err(Error::syntax)?,
+        ),
+        Some(_) => Licensee::Old(header_byte(state, &form, "licensee")?),
+        None => Licensee::Old(0),
+    };
+    let header = gb::Header {
+        title,
+        cgb: header_byte(state, &form, "cgb")?,
+        sgb,
+        cartridge_type: header_byte(state, &form, "cartridge-type")?,
+        rom_size: header_byte(state, &form, "rom-size")?,
+        ram_size: header_byte(state, &form, "ram-size")?,
+        licensee,
+    };
+    let bytes = header
+        .to_bytes()
+        .map_err(|e| Error::syntax(format!("gb-header: {}", e)))?;
 
-            Ok(None)
-        }
-        (a1, a2) => return Err(format!("ld: illegal parameters: {:?} {:?}", a1, a2)),
+    expect_in_w_sec(state)?;
+    check_section_space(state, bytes.len())?;
+    state.current_section_address.add_bytes(bytes.len() as u64);
+    let sec = expect_in_w_sec(state)?;
+    for b in bytes {
+        sec.memory.push_u8(b);
     }
+    sec.pinned = true;
+    state.gb_header = true;
+    Ok(None)
 }
 
-fn ld_immediate_to_reg(
-    state: &mut State,
-    dst_reg: String,
-    im_value: i64,
-) -> Result<Option<LabelRef>, String> {
-    let (op, len) = match dst_reg.as_str() {
-        sm83::REG_A => (INSTR_LD_TO_A_FROM_IMMEDIATE.op_code, 2),
-        sm83::REG_B => (INSTR_LD_TO_B_FROM_IMMEDIATE.op_code, 2),
-        sm83::REG_HL => (INSTR_LD_TO_HL_FROM_IMMEDIATE.op_code, 3),
-        _ => return Err(format!("ld: unknown target register: {}", dst_reg)),
+/// The byte value of the key in the gb-header, 0 if not given.
+fn header_byte(state: &State, form: &Form, key: &str) -> Result<u8, Error> {
+    let Some(exp) = key_value(&form.exps, key).map_err(Error::syntax)? else {
+        return Ok(0);
     };
-
+    let value = eval_const(exp, &state.const_values)?;
+    u8::try_from(value).map_err(|_| {
+        Error::new(
+            ErrorKind::Range,
+            format!("gb-header: {} must be a byte, got {}", key, value),
+        )
+    })
+}
+
+/// The :fill byte of the form, 0 if not given.
+fn fill_byte(state: &State, form: &Form, op: &str) -> Result<u8, Error> {
+    let Some(exp) = key_value(&form.exps, "fill").map_err(Error::syntax)? else {
+        return Ok(0);
+    };
+    let fill = eval_const(exp, &state.const_values)?;
+    u8::try_from(fill).map_err(|_| {
+        Error::new(
+            ErrorKind::Range,
+            format!("{}: fill must be a byte, got {}", op, fill),
+        )
+    })
+}
+
+/// Moves the current address len bytes forward, the bytes are written
+/// with the fill byte unless the section is label-only.
+fn skip_bytes(state: &mut State, len: u64, fill: u8) -> Result<(), Error> {
+    check_section_space(state, len as usize)?;
     state.current_section_address.add_bytes(len);
-
-    // TODO check range of immediate value!
+    if is_label_only(state) {
+        reserve_label_only(state);
+        return Ok(());
+    }
     let sec = expect_in_w_sec(state)?;
-    sec.memory.push_u8(op);
-    if len == 2 {
-        sec.memory.push_u8(im_value as u8);
-    } else if len == 3 {
-        sec.memory.push_u16(im_value as u16);
+    for _ in 0..len {
+        sec.memory.push_u8(fill);
     }
-    Ok(None)
+    Ok(())
 }
 
-fn expect_deref_reg(form: &Form) -> Result<(&str, Option<&str>), String> {
-    let dst_reg = match &form.op {
-        Symbol::Reg(reg) => reg,
-        illegal => return Err(format!("illegal deref: {:?}", illegal)),
-    };
-
-    if form.exps.len() > 1 {
-        return Err(format!("illegal deref, too many args: {:?}", form));
+/// (incbin "path" [:offset n] [:length m]) writes the bytes of the file,
+/// from the offset to the end of the file or length bytes. The file is
+/// searched like an included file, relative to the including file first.
+fn incbin(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    expect_in_section(state)?;
+    if form.exps.is_empty() {
+        return Err(Error::syntax("incbin: file path required"));
     }
-
-    let deref_mod = if form.exps.is_empty() {
-        None
-    } else {
-        let exp = &form.exps[0];
-        Some(match exp {
-            SExp::Symbol(Symbol::Sym(name)) => name.as_str(),
-            illegal => return Err(format!("illegal deref mod: {:?}", illegal)),
-        })
+    let file_name =
+        expect_string(&form.exps[0]).map_err(|e| Error::syntax(format!("incbin: {}", e)))?;
+    let offset = match key_value(&form.exps, "offset").map_err(Error::syntax)? {
+        Some(exp) => eval_const(exp, &state.const_values)?,
+        None => 0,
     };
-
-    Ok((dst_reg.as_str(), deref_mod))
+    if offset.is_negative() {
+        return Err(Error::new(
+            ErrorKind::Range,
+            "incbin: offset must be positive".to_string(),
+        ));
+    }
+    let length = match key_value(&form.exps, "length").map_err(Error::syntax)? {
+        Some(exp) => Some(eval_const(exp, &state.const_values)?),
+        None => None,
+    };
+    if length.is_some_and(|length| length.is_negative()) {
+        return Err(Error::new(
+            ErrorKind::Range,
+            "incbin: length must be positive".to_string(),
+        ));
+    }
+
+    let candidates = search_paths(state, &form, &file_name);
+    let (_, data) =
+        read_first(&candidates, |path| state.fs.read(path)).map_err(|e| e.prefixed("incbin"))?;
+    let start = offset as usize;
+    if start > data.len() {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "incbin: offset {} beyond the end of {} ({} bytes)",
+                offset,
+                file_name,
+                data.len()
+            ),
+        ));
+    }
+    let end = match length {
+        Some(length) => start.saturating_add(length as usize),
+        None => data.len(),
+    };
+    if end > data.len() {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "incbin: {} bytes from offset {} beyond the end of {} ({} bytes)",
+                end - start,
+                offset,
+                file_name,
+                data.len()
+            ),
+        ));
+    }
+
+    expect_in_w_sec(state)?;
+    check_section_space(state, end - start)?;
+    let sec = expect_in_w_sec(state)?;
+    for byte in &data[start..end] {
+        sec.memory.push_u8(*byte);
+    }
+    state
+        .current_section_address
+        .add_bytes((end - start) as u64);
+    Ok(None)
 }
 
-fn jp(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
+fn label(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
     if form.exps.is_empty() {
-        return Err("jp: needs at least one argument".to_string());
+        return Err(Error::syntax("label: needs at one argument"));
     }
 
-    let (flag_name, lbl) = if form.exps.len() == 1 {
-        (None, expect_label_name(&form.exps[0])?)
-    } else if form.exps.len() == 2 {
-        let flag_name = expect_flag(&form.exps[0])?;
-        let lbl = expect_label_name(&form.exps[1])?;
-        (Some(flag_name), lbl)
+    let may_label = is_label(&form.exps[0]);
+    if let Some(lbl) = may_label {
+        define_label(state, lbl.clone())?;
+        Ok(None)
     } else {
-        // illegal jump
-        return Err("jp: illegal arguments".to_string());
-    };
-
-    state.current_section_address.add_bytes(3);
-
-    let sec = expect_in_w_sec(state)?;
-    write_jp_instr(sec, flag_name, 0)?;
-    Ok(Some(LabelRef {
-        reference: Ref::from_label(lbl),
-        sec_name: sec.name.clone(),
-        patch_index: sec.memory.mem_ptr - 2,
-    }))
+        Err(Error::syntax("label: need a label as argument"))
+    }
 }
 
-fn write_jp_instr(
-    sec: &mut Section,
-    flag_name: Option<&str>,
-    address_val: u16,
-) -> Result<(), String> {
-    let op = match flag_name {
-        None => sm83::INSTR_JP.op_code,
-        Some(sm83::FLAG_C) => sm83::INSTR_JP_IF_C.op_code,
-        Some(sm83::FLAG_NZ) => sm83::INSTR_JP_IF_NZ.op_code,
-        Some(illegal) => return Err(format!("jp: unknown flag '{}'", illegal)),
-    };
-
-    sec.memory.push_u8(op);
-    sec.memory.push_u16(address_val);
-    Ok(())
+fn sub_section(_state: &mut State) -> Result<Option<LabelRef>, Error> {
+    println!("!sub-section");
+    Ok(None)
 }
 
-fn check_16_bit_address_range(dist: i32) -> Result<(), String> {
-    if dist < u16::MIN as i32 {
-        return Err(format!("jp: max {} jumps back, was {}", u16::MIN, dist));
-    }
-    if dist > u16::MAX as i32 {
-        return Err(format!("jp: max {} jumps forward, was {}", u16::MAX, dist));
+/// The current address if it is final, i.e. the current section is
+/// not placed by place_sections or the linker.
+fn placed_address(state: &State) -> Option<Address> {
+    let floating = state
+        .current_section_name
+        .as_ref()
+        .and_then(|name| state.lookup_section(name))
+        .is_some_and(|sec| sec.floating.is_some());
+    if state.relocatable || floating {
+        None
+    } else {
+        Some(state.current_section_address)
     }
-    Ok(())
 }
 
-/// jump relative
-fn jr(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.is_empty() {
-        return Err("jr: needs at least one argument".to_string());
-    }
-
-    let (flag_name, lbl) = if form.exps.len() == 1 {
-        (None, expect_label_name(&form.exps[0])?)
-    } else if form.exps.len() == 2 {
-        let flag_name = expect_flag(&form.exps[0])?;
-        let lbl = expect_label_name(&form.exps[1])?;
-        (Some(flag_name), lbl)
-    } else {
-        // illegal jump
-        return Err("jr: illegal arguments".to_string());
-    };
+// non-primitive forms, temporarily implemented in Rust directly
 
-    state.current_section_address.add_bytes(2);
+/// Assembles a sm83 instruction, see encoder::encode for the
+/// supported operands.
+fn instruction(state: &mut State, form: Form) -> Result<Option<LabelRef>, Error> {
+    let encoded = encoder::encode(
+        &form,
+        placed_address(state),
+        &state.const_values,
+        state
+            .sizing_addresses
+            .as_ref()
+            .unwrap_or(&state.label_addresses),
+    )?;
 
+    expect_in_w_sec(state)?;
+    check_section_space(state, encoded.bytes.len())?;
+    state
+        .current_section_address
+        .add_bytes(encoded.bytes.len() as u64);
     let curr_address = state.current_section_address;
+
     let sec = expect_in_w_sec(state)?;
-    write_jr_instr(sec, flag_name, 0)?;
-    Ok(Some(LabelRef {
-        reference: Ref::Relative(curr_address, lbl.clone(), check_jr_jump),
+    let instr_index = sec.memory.mem_ptr;
+    for b in encoded.bytes {
+        sec.memory.push_u8(b);
+    }
+    Ok(encoded.reference.map(|(ix, reference)| LabelRef {
+        reference: match reference {
+            EncodedRef::Absolute16(exp) => Ref::Expression(exp),
+            EncodedRef::Relative8(exp) => Ref::Relative(curr_address, exp, check_jr_jump),
+            EncodedRef::High8(exp) => Ref::HighPage(exp),
+            EncodedRef::Immediate8(exp) => Ref::Immediate8(exp),
+        },
         sec_name: sec.name.clone(),
-        patch_index: sec.memory.mem_ptr - 1,
+        patch_index: instr_index + ix,
+        span: form.span.clone(),
     }))
 }
 
-fn write_jr_instr(sec: &mut Section, flag: Option<&str>, rel_dist: u8) -> Result<(), String> {
-    let op = match flag {
-        None => sm83::INSTR_JR.op_code,
-        Some(sm83::FLAG_C) => sm83::INSTR_JR_IF_C.op_code,
-        Some(sm83::FLAG_NZ) => sm83::INSTR_JR_IF_NZ.op_code,
-        Some(illegal) => return Err(format!("jr: unknown flag '{}'", illegal)),
-    };
-
-    sec.memory.push_u8(op);
-    sec.memory.push_u8(rel_dist);
+pub(crate) fn check_16_bit_address_range(address: i64) -> Result<(), Error> {
+    if !(0..=0xFFFF).contains(&address) {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("address {} out of range 0x0000-0xFFFF", address),
+        ));
+    }
     Ok(())
 }
 
-fn check_jr_jump(rel_dist: i32) -> Result<(), String> {
-    if rel_dist < -128 {
-        return Err(format!("jr: max -128 jumps back, was {}", rel_dist));
-    }
-    if rel_dist > 127 {
-        return Err(format!("jr: max 127 jumps forward, was {}", rel_dist));
+/// Checks a data word that is only known once the labels are resolved,
+/// as signed or unsigned word.
+pub(crate) fn check_16_bit_value(value: i64) -> Result<(), Error> {
+    if !(-0x8000..=0xFFFF).contains(&value) {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("value {} out of range -32768..65535", value),
+        ));
     }
     Ok(())
 }
 
-fn inc(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() != 1 {
-        return Err("inc: needs exactly one argument".to_string());
+/// The high page 0xFF00-0xFFFF is addressed with its low byte by ldh.
+pub(crate) fn check_high_page_address(address: i64) -> Result<(), Error> {
+    if !(0xFF00..=0xFFFF).contains(&address) {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "ldh: address 0x{:X} not in high page 0xFF00-0xFFFF",
+                address
+            ),
+        ));
     }
-
-    match &form.exps[0] {
-        SExp::Symbol(Symbol::Reg(reg)) => {
-            let op = match reg.as_str() {
-                sm83::REG_A => INSTR_INC_A.op_code,
-                sm83::REG_BC => INSTR_INC_BC.op_code,
-                sm83::REG_DE => INSTR_INC_DE.op_code,
-                sm83::REG_HL => INSTR_INC_HL.op_code,
-                illegal_reg => return Err(format!("inc: unknow register: {}", illegal_reg)),
-            };
-
-            state.current_section_address.add_bytes(1);
-
-            let sec = expect_in_w_sec(state)?;
-            sec.memory.push_u8(op);
-        }
-        illegal => return Err(format!("inc: illegal argument: {:?}", illegal)),
-    }
-    Ok(None)
+    Ok(())
 }
 
-fn dec(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.len() != 1 {
-        return Err("dec: needs exactly one argument".to_string());
-    }
-
-    match &form.exps[0] {
-        SExp::Symbol(Symbol::Reg(reg)) => {
-            let op = match reg.as_str() {
-                sm83::REG_A => INSTR_DEC_A.op_code,
-                sm83::REG_B => INSTR_DEC_B.op_code,
-                sm83::REG_BC => INSTR_DEC_BC.op_code,
-                sm83::REG_DE => INSTR_DEC_DE.op_code,
-                sm83::REG_HL => INSTR_DEC_HL.op_code,
-                illegal_reg => return Err(format!("dec: unknow register: {}", illegal_reg)),
-            };
-
-            state.current_section_address.add_bytes(1);
-
-            let sec = expect_in_w_sec(state)?;
-            sec.memory.push_u8(op);
-        }
-        illegal => return Err(format!("dec: illegal argument: {:?}", illegal)),
+/// Checks that the immediate value fits into the number of bits, as
+/// signed or as unsigned value (e.g. -128..255 for 8 bits). The error
+/// names the value and the form it is used in.
+pub(crate) fn check_immediate_range(value: i64, bits: u32, form: &Form) -> Result<(), Error> {
+    let min = -(1i64 << (bits - 1));
+    let max = (1i64 << bits) - 1;
+    if value < min || value > max {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "{}: immediate value {} out of range {}..{} in {}",
+                form.op, value, min, max, form
+            ),
+        ));
     }
-    Ok(None)
+    Ok(())
 }
 
-fn call(state: &mut State, form: Form) -> Result<Option<LabelRef>, String> {
-    if form.exps.is_empty() {
-        return Err("call: needs at least one argument".to_string());
+/// Checks an 8-bit value that is only known once the labels are
+/// resolved, as signed or unsigned byte.
+pub(crate) fn check_8_bit_value(value: i64) -> Result<(), Error> {
+    if !(-128..=255).contains(&value) {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("value {} out of range -128..255", value),
+        ));
     }
-
-    let (flag_name, lbl) = if form.exps.len() == 1 {
-        (None, expect_label_name(&form.exps[0])?)
-    } else if form.exps.len() == 2 {
-        let flag_name = expect_flag(&form.exps[0])?;
-        let lbl = expect_label_name(&form.exps[1])?;
-        (Some(flag_name), lbl)
-    } else {
-        // illegal call
-        return Err("call: illegal arguments".to_string());
-    };
-
-    state.current_section_address.add_bytes(3);
-
-    let sec = expect_in_w_sec(state)?;
-    write_call_instr(sec, flag_name, 0)?;
-    Ok(Some(LabelRef {
-        reference: Ref::from_label(lbl),
-        sec_name: sec.name.clone(),
-        patch_index: sec.memory.mem_ptr - 2,
-    }))
-}
-
-fn ret(state: &mut State, mut _form: Form) -> Result<Option<LabelRef>, String> {
-    state.current_section_address.add_bytes(1);
-    let sec = expect_in_w_sec(state)?;
-    sec.memory.push_u8(INSTR_RET.op_code);
-    Ok(None)
+    Ok(())
 }
 
-fn write_call_instr(
-    sec: &mut Section,
-    flag_name: Option<&str>,
-    address_val: u16,
-) -> Result<(), String> {
-    let op = match flag_name {
-        None => sm83::INSTR_CALL.op_code,
-        Some(sm83::FLAG_C) => sm83::INSTR_CALL_IF_C.op_code,
-        Some(sm83::FLAG_NZ) => sm83::INSTR_CALL_IF_NZ.op_code,
-        Some(illegal) => return Err(format!("call: unknown flag '{}'", illegal)),
-    };
-
-    sec.memory.push_u8(op);
-    sec.memory.push_u16(address_val);
+pub(crate) fn check_jr_jump(rel_dist: i32) -> Result<(), Error> {
+    if rel_dist < -128 {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("jr: max -128 jumps back, was {}", rel_dist),
+        ));
+    }
+    if rel_dist > 127 {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("jr: max 127 jumps forward, was {}", rel_dist),
+        ));
+    }
     Ok(())
 }
 
 // interpret/assemble helper
 
-static FALSE_SYM_NAME: &'static str = "false";
-static TRUE_SYM_NAME: &'static str = "true";
+static FALSE_SYM_NAME: &str = "false";
+static TRUE_SYM_NAME: &str = "true";
 
 fn sym_false() -> Symbol {
     Symbol::Sym(FALSE_SYM_NAME.to_string())
 }
 
-fn expect_label_address(state: &State, lbl: &Label) -> Result<Address, String> {
-    if let Some(address) = state.label_addresses.get(&lbl) {
-        Ok(*address)
-    } else {
-        Err(format!("no address for label '{}", lbl.name()))
-    }
-}
-
-fn expect_in_section(state: &State) -> Result<(), String> {
+fn expect_in_section(state: &State) -> Result<(), Error> {
     if state.current_section_name.is_none() {
-        Err("not in a section".to_string())
+        Err(Error::syntax("not in a section"))
     } else {
         Ok(())
     }
 }
 
-fn expect_bool_sym(sym: &Symbol) -> Result<bool, String> {
+pub(crate) fn expect_bool_sym(sym: &Symbol) -> Result<bool, String> {
     match sym {
         Symbol::Sym(name) => {
             if name == FALSE_SYM_NAME {
@@ -974,28 +1994,21 @@ fn expect_string(exp: &SExp) -> Result<String, String> {
     }
 }
 
-fn expect_section_name(exp: &SExp) -> Result<String, String> {
+pub(crate) fn expect_section_name(exp: &SExp) -> Result<String, String> {
     match exp {
         SExp::Symbol(Symbol::Section(name)) => Ok(name.clone()),
         _ => Err("section name expected".to_string()),
     }
 }
 
-fn expect_label_name(exp: &SExp) -> Result<Label, String> {
+pub(crate) fn expect_label_name(exp: &SExp) -> Result<Label, String> {
     match exp {
         SExp::Symbol(Symbol::Label(lbl)) => Ok(lbl.clone()),
         _ => Err(format!("label expected, got {:?}", exp)),
     }
 }
 
-fn expect_flag(exp: &SExp) -> Result<&str, String> {
-    match exp {
-        SExp::Symbol(Symbol::Flag(name)) => Ok(name),
-        _ => Err(format!("flag expected, got {:?}", exp)),
-    }
-}
-
-fn expect_immediate(exp: &SExp) -> Result<i64, String> {
+pub(crate) fn expect_immediate(exp: &SExp) -> Result<i64, String> {
     match exp {
         SExp::Immediate(val) => Ok(*val),
         _ => Err(format!("not an immediate value: {:?}", exp)),
@@ -1046,35 +2059,97 @@ fn expect_has_sexp_at(exps: &[SExp], i: usize, err: &str) -> Result<(), String>
     Ok(())
 }
 
-fn expect_in_w_sec<'a>(state: &'a mut State) -> Result<&'a mut Section, String> {
-    let curr_name = state.current_section_name.clone();
-    if let Some(sec_name) = curr_name {
-        let sec = state
-            .lookup_section_mut(&sec_name)
-            .expect("a current section");
-        if sec.label_only {
-            Err(format!("section {} is label-only", sec_name))
-        } else {
-            Ok(sec)
-        }
-    } else {
-        Err("not in a section".to_string())
+/// Remembers how far the address of a label-only section was moved, the
+/// extent of a label-only section without length.
+fn reserve_label_only(state: &mut State) {
+    let address = state.current_section_address;
+    if let Some(sec) = state
+        .current_section_name
+        .clone()
+        .and_then(|name| state.lookup_section_mut(&name))
+        .filter(|sec| sec.label_only)
+    {
+        sec.reserved = sec.reserved.max(address.0 - sec.offset.0);
     }
 }
 
-fn expect_in_r_sec<'a>(state: &'a mut State) -> Result<&'a mut Section, String> {
+/// The section of the current address, after expect_in_section.
+fn current_section<'a>(state: &'a State) -> &'a Section {
+    state
+        .current_section_name
+        .as_ref()
+        .and_then(|name| state.lookup_section(name))
+        .expect("a current section")
+}
+
+fn current_section_mut<'a>(state: &'a mut State) -> &'a mut Section {
+    let name = state
+        .current_section_name
+        .clone()
+        .expect("a current section");
+    state.lookup_section_mut(&name).expect("a current section")
+}
+
+fn is_label_only(state: &State) -> bool {
+    state
+        .current_section_name
+        .as_ref()
+        .and_then(|name| state.lookup_section(name))
+        .is_some_and(|sec| sec.label_only)
+}
+
+fn expect_in_w_sec<'a>(state: &'a mut State) -> Result<&'a mut Section, Error> {
     let curr_name = state.current_section_name.clone();
+    let address = state.current_section_address;
     if let Some(sec_name) = curr_name {
         let sec = state
             .lookup_section_mut(&sec_name)
             .expect("a current section");
-        if !sec.label_only {
-            Err(format!("section {} is not label-only", sec_name))
+        if sec.label_only {
+            Err(Error::syntax(format!(
+                "section .{} is label-only, no bytes can be written at 0x{:04X}",
+                sec_name, address.0
+            )))
         } else {
             Ok(sec)
         }
     } else {
-        Err("not in a section".to_string())
+        Err(Error::syntax("not in a section"))
+    }
+}
+
+/// Checks that len bytes fit into the current section. The bytes of a
+/// label-only section only move the address, which must stay within the
+/// length of the section.
+fn check_section_space(state: &State, len: usize) -> Result<(), Error> {
+    let sec_name = state
+        .current_section_name
+        .as_ref()
+        .ok_or_else(|| Error::syntax("not in a section"))?;
+    let sec = state.lookup_section(sec_name).expect("a current section");
+    let (used, at) = if sec.label_only {
+        let used = state.current_section_address.0 - sec.offset.0;
+        (used, state.current_section_address.0)
+    } else {
+        let used = sec.memory.mem_ptr as u64;
+        (used, sec.offset.0 + used)
+    };
+    match sec.length {
+        Some(length) if used + len as u64 > length => Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "section .{} (0x{:04X}, length 0x{:X}) overflows: {} bytes at 0x{:04X}",
+                sec.name, sec.offset.0, length, len, at
+            ),
+        )),
+        None if sec.floating.is_none() && at + len as u64 > 0x10000 => Err(Error::new(
+            ErrorKind::Range,
+            format!(
+                "section .{} (0x{:04X}) grows beyond the address space 0x0000-0xFFFF: {} bytes at 0x{:04X}",
+                sec.name, sec.offset.0, len, at
+            ),
+        )),
+        _ => Ok(()),
     }
 }
 
@@ -1092,20 +2167,6 @@ fn is_label(exp: &SExp) -> Option<&Label> {
     }
 }
 
-fn is_flag(exp: &SExp) -> Option<&String> {
-    match exp {
-        SExp::Symbol(Symbol::Flag(flg)) => Some(flg),
-        _ => None,
-    }
-}
-
-fn is_immediate(exp: &SExp) -> Option<i64> {
-    match exp {
-        SExp::Immediate(v) => Some(*v),
-        _ => None,
-    }
-}
-
 fn is_gameboy_tile(exp: &SExp) -> Option<&String> {
     match exp {
         SExp::Symbol(Symbol::GameboyTile(tile)) => Some(tile),
@@ -1113,11 +2174,11 @@ fn is_gameboy_tile(exp: &SExp) -> Option<&String> {
     }
 }
 
-fn is_const_expression_op(sym: &str) -> bool {
-    sym == CONST_OP_BITWISE_OR || sym == CONST_OP_SHIFT_LEFT
+pub(crate) fn is_const_expression_op(sym: &str) -> bool {
+    CONST_OPS.contains(&sym)
 }
 
-fn key_value<'a>(exps: &'a [SExp], name: &str) -> Result<Option<&'a SExp>, String> {
+pub(crate) fn key_value<'a>(exps: &'a [SExp], name: &str) -> Result<Option<&'a SExp>, String> {
     let mut i = 0;
     while i < exps.len() {
         if is_keyword(&exps[i], name) {
@@ -1,23 +1,48 @@
+use crate::arch::gb;
 use crate::arch::sm83::{
-    self, INSTR_CP_IMMEDIATE, INSTR_DEC_A, INSTR_DEC_B, INSTR_DEC_BC, INSTR_DEC_DE, INSTR_DEC_HL,
-    INSTR_INC_A, INSTR_INC_BC, INSTR_INC_DE, INSTR_INC_HL, INSTR_LD_TO_A_FROM_B,
-    INSTR_LD_TO_A_FROM_DEREF_DE, INSTR_LD_TO_A_FROM_DEREF_HL, INSTR_LD_TO_A_FROM_DEREF_HL_INC,
-    INSTR_LD_TO_A_FROM_DEREF_LABEL, INSTR_LD_TO_A_FROM_IMMEDIATE, INSTR_LD_TO_B_FROM_IMMEDIATE,
-    INSTR_LD_TO_BC_FROM_IMMEDIATE, INSTR_LD_TO_DE_FROM_IMMEDIATE, INSTR_LD_TO_DEREF_DE_FROM_A,
-    INSTR_LD_TO_DEREF_HL_FROM_A, INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE,
-    INSTR_LD_TO_DEREF_HL_INC_FROM_A, INSTR_LD_TO_DEREF_LABEL_FROM_A, INSTR_LD_TO_HL_FROM_IMMEDIATE,
-    INSTR_OR_A_C,
+    self, INSTR_ADC_A_E, INSTR_ADC_A_IMMEDIATE, INSTR_ADD_A_B, INSTR_ADD_A_DEREF_HL,
+    INSTR_ADD_A_IMMEDIATE, INSTR_ADD_HL_BC, INSTR_ADD_HL_DE, INSTR_ADD_HL_HL, INSTR_ADD_HL_SP,
+    INSTR_ADD_SP_IMMEDIATE, INSTR_AND_A_D, INSTR_AND_A_IMMEDIATE, INSTR_CP_A_B, INSTR_CP_IMMEDIATE,
+    INSTR_DEC_A, INSTR_DEC_B, INSTR_DEC_BC, INSTR_DEC_DE, INSTR_DEC_HL, INSTR_INC_A, INSTR_INC_BC,
+    INSTR_INC_DE, INSTR_INC_HL, INSTR_LD_TO_A_FROM_B, INSTR_LD_TO_A_FROM_DEREF_DE,
+    INSTR_LD_TO_A_FROM_DEREF_HL, INSTR_LD_TO_A_FROM_DEREF_HL_INC, INSTR_LD_TO_A_FROM_DEREF_LABEL,
+    INSTR_LD_TO_A_FROM_IMMEDIATE, INSTR_LD_TO_B_FROM_IMMEDIATE, INSTR_LD_TO_BC_FROM_IMMEDIATE,
+    INSTR_LD_TO_DE_FROM_IMMEDIATE, INSTR_LD_TO_DEREF_DE_FROM_A, INSTR_LD_TO_DEREF_HL_FROM_A,
+    INSTR_LD_TO_DEREF_HL_FROM_IMMEDIATE, INSTR_LD_TO_DEREF_HL_INC_FROM_A,
+    INSTR_LD_TO_DEREF_LABEL_FROM_A, INSTR_LD_TO_HL_FROM_IMMEDIATE, INSTR_OR_A_A, INSTR_OR_A_C,
+    INSTR_OR_A_DEREF_HL, INSTR_OR_A_IMMEDIATE, INSTR_PREFIX_BIT_7_DEREF_HL, INSTR_PREFIX_RES_0_B,
+    INSTR_PREFIX_RLC_B, INSTR_PREFIX_SET_3_E, INSTR_PREFIX_SRL_A, INSTR_PREFIX_SWAP_A,
+    INSTR_SBC_A_IMMEDIATE, INSTR_SBC_A_L, INSTR_SUB_A_DEREF_HL, INSTR_SUB_A_H, INSTR_XOR_A_A,
+    INSTR_XOR_A_IMMEDIATE,
 };
+use crate::asm::assemble_source;
 use crate::asm::assembler::{
-    Form, Label, LabelRef, Memory, Ref, Section, State, assemble_in_state, call, check_jr_jump, cp,
-    db, dec, def_constant, ds, dw, expect_label_name, inc, jp, jr, ld, nop, or, resolve_labels,
-    ret, tile_to_u16,
+    Environment, Floating, Form, Label, LabelRef, Memory, Ref, Region, Section, SectionLayout,
+    State, assemble_forms, assemble_in_state, assemble_passes, check_jr_jump, db, def_constant, ds,
+    dw, expect_label_name, instruction, normalize_path, parse_define, parse_fill, resolve_label,
+    resolve_labels, state_to_object, tile_to_u16,
 };
+use crate::asm::object::{ObjectSymbol, Patch};
+
+use crate::asm::parser::{Address, SExp, Span, Symbol, parse_from_string};
+use crate::asm::vfs::{FileSystem, MemoryFileSystem, OsFileSystem};
+use crate::error::{Error, ErrorKind};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+impl Ref {
+    /// Creates a Ref::Expression from a label.
+    fn from_label(label: Label) -> Ref {
+        Ref::Expression(SExp::Symbol(Symbol::Label(label)))
+    }
 
-use crate::asm::parser::{Address, SExp, Symbol, parse_from_string};
+    fn from_form(form: Form) -> Ref {
+        Ref::Expression(SExp::Form(form))
+    }
+}
 
 #[test]
-fn test_def_constant_fails() -> Result<(), String> {
+fn test_def_constant_fails() -> Result<(), Error> {
     let cases = [
         ("(def-constant)", "illegal def-constant"),
         ("(def-constant +k+)", "illegal def-constant"),
@@ -39,14 +64,14 @@ fn test_def_constant_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
 
     Ok(())
 }
 
 #[test]
-fn test_def_constant_ok() -> Result<(), String> {
+fn test_def_constant_ok() -> Result<(), Error> {
     // actual const expression evaluation is tested in interpreter_test
     // and not repeated here
     let cases = [("(def-constant +x+ 666)", ("+x+", 666))];
@@ -61,7 +86,7 @@ fn test_def_constant_ok() -> Result<(), String> {
         if let Some(val) = maybe_val {
             assert_eq!(*val, want_val);
         } else {
-            assert!(false, "no constant val for name {}", name);
+            panic!("no constant val for name {}", name);
         }
     }
 
@@ -69,7 +94,7 @@ fn test_def_constant_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_ds_fail() -> Result<(), String> {
+fn test_ds_fail() -> Result<(), Error> {
     let cases = [("(ds)", "ds: needs at least a len")];
 
     for (exp, err) in cases {
@@ -84,14 +109,14 @@ fn test_ds_fail() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
 
     Ok(())
 }
 
 #[test]
-fn test_ds_ok() -> Result<(), String> {
+fn test_ds_ok() -> Result<(), Error> {
     let cases = [("(ds 0)", 0), ("(ds 1", 1), ("(ds 66)", 66)];
 
     for (exp, mem_ptr) in cases {
@@ -100,7 +125,7 @@ fn test_ds_ok() -> Result<(), String> {
 
         ds(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem_ptr, mem_ptr);
     }
 
@@ -108,13 +133,16 @@ fn test_ds_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_db_ok() -> Result<(), String> {
-    let cases: [(&str, &[u8], usize); 5] = [
+fn test_db_ok() -> Result<(), Error> {
+    let cases: [(&str, &[u8], usize); 8] = [
         ("(db)", &[0], 0),
         ("(db 0)", &[0], 1),
         ("(db 1)", &[1], 1),
         ("(db 0xFF)", &[0xFF], 1),
+        ("(db -1)", &[0xFF], 1),
+        ("(db -128)", &[0x80], 1),
         ("(db 0 1 2 3 4 0xFF)", &[0, 1, 2, 3, 4, 0xFF], 6),
+        ("(db (* 2 3) (hi 0x1234) (- 1))", &[6, 0x12, 0xFF], 3),
     ];
 
     for (exp, mem_vals, mem_ptr) in cases {
@@ -123,7 +151,7 @@ fn test_db_ok() -> Result<(), String> {
 
         db(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem_ptr, mem_ptr, "exp={}", exp);
         assert_eq!(&sec.memory.mem[0..(mem_vals.len())], mem_vals);
     }
@@ -132,9 +160,35 @@ fn test_db_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_dw_ok() -> Result<(), String> {
-    let cases: [(&str, &[u8], usize); 6] = [
+fn test_db_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(db 1 256)",
+            "db: immediate value 256 out of range -128..255 in (db 1 256)",
+        ),
+        (
+            "(db -129)",
+            "db: immediate value -129 out of range -128..255 in (db -129)",
+        ),
+    ];
+
+    for (exp, err) in cases {
+        let mut state = test_state();
+        let mut tl = parse_from_string(exp)?;
+
+        let r = db(&mut state, tl.forms.pop().unwrap());
+
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dw_ok() -> Result<(), Error> {
+    let cases: [(&str, &[u8], usize); 8] = [
         ("(dw)", &[0, 0], 0),
+        ("(dw -1)", &[0xFF, 0xFF], 2),
         ("(dw 0)", &[0, 0], 2),
         ("(dw 1)", &[1, 0], 2),
         ("(dw 0xFF)", &[0xFF, 0], 2),
@@ -144,6 +198,11 @@ fn test_dw_ok() -> Result<(), String> {
             &[0, 0, 1, 0, 2, 0, 3, 0, 4, 0, 0xFF, 0xAA, 0xFF, 0xFF],
             14,
         ),
+        (
+            "(dw (<< 1 15) (| 0x12 0x3400))",
+            &[0x00, 0x80, 0x12, 0x34],
+            4,
+        ),
     ];
 
     for (exp, mem_vals, mem_ptr) in cases {
@@ -152,7 +211,7 @@ fn test_dw_ok() -> Result<(), String> {
 
         dw(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem_ptr, mem_ptr, "exp={}", exp);
         assert_eq!(&sec.memory.mem[0..mem_vals.len()], mem_vals);
     }
@@ -161,17 +220,42 @@ fn test_dw_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_nop_ok() -> Result<(), String> {
+fn test_dw_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(dw 0x10000)",
+            "dw: immediate value 65536 out of range -32768..65535 in (dw 65536)",
+        ),
+        (
+            "(dw 1 -32769)",
+            "dw: immediate value -32769 out of range -32768..65535 in (dw 1 -32769)",
+        ),
+    ];
+
+    for (exp, err) in cases {
+        let mut state = test_state();
+        let mut tl = parse_from_string(exp)?;
+
+        let r = dw(&mut state, tl.forms.pop().unwrap());
+
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_nop_ok() -> Result<(), Error> {
     let cases = [("(nop)", sm83::INSTR_NOP.op_code)];
 
     for (exp, op_code) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let got_label_ref = nop(&mut state, tl.forms.pop().unwrap())?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
 
         assert!(got_label_ref.is_none());
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(
             sec.memory.mem[0], op_code,
             "op_code was {:x}, expected {:?}, expression={:?}",
@@ -183,14 +267,18 @@ fn test_nop_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_jr_fails() -> Result<(), String> {
+fn test_jr_fails() -> Result<(), Error> {
     let cases = [
-        ("(jr)", Address(0), "jr: needs at least one argument"),
+        (
+            "(jr)",
+            Address(0),
+            "jr: illegal arguments: (jr), valid forms: (jr e8) (jr #nz|#z|#nc|#c e8)",
+        ),
         (
             // unknown flag
             "(jr #tz 'lbl)",
             Address(0x4000 + 2),
-            "jr: unknown flag 'tz'",
+            "jr: unknown flag #tz, valid flags: #nz #z #nc #c",
         ),
     ];
 
@@ -199,11 +287,12 @@ fn test_jr_fails() -> Result<(), String> {
         let mut tl = parse_from_string(exp)?;
 
         if !tl.forms[0].exps.is_empty() {
-            let lbl = expect_label_name(&tl.forms[0].exps[tl.forms[0].exps.len() - 1])?;
+            let lbl = expect_label_name(&tl.forms[0].exps[tl.forms[0].exps.len() - 1])
+                .map_err(Error::syntax)?;
             state.label_addresses.insert(lbl, lbl_address);
         }
 
-        let r = jr(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
         assert!(
             r.is_err(),
@@ -211,13 +300,13 @@ fn test_jr_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
     Ok(())
 }
 
 #[test]
-fn test_jr_ok() -> Result<(), String> {
+fn test_jr_ok() -> Result<(), Error> {
     let cases = [
         // jump to self
         (
@@ -225,11 +314,12 @@ fn test_jr_ok() -> Result<(), String> {
             Some(LabelRef {
                 reference: Ref::Relative(
                     Address(16386),
-                    Label::from_string("lbl".to_string()),
+                    SExp::Symbol(Symbol::Label(Label::from_string("lbl".to_string()))),
                     check_jr_jump,
                 ),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JR.op_code,
         ),
@@ -239,11 +329,12 @@ fn test_jr_ok() -> Result<(), String> {
             Some(LabelRef {
                 reference: Ref::Relative(
                     Address(16386),
-                    Label::from_string("lbl2".to_string()),
+                    SExp::Symbol(Symbol::Label(Label::from_string("lbl2".to_string()))),
                     check_jr_jump,
                 ),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JR_IF_NZ.op_code,
         ),
@@ -253,11 +344,12 @@ fn test_jr_ok() -> Result<(), String> {
             Some(LabelRef {
                 reference: Ref::Relative(
                     Address(16386),
-                    Label::from_string("lbl3".to_string()),
+                    SExp::Symbol(Symbol::Label(Label::from_string("lbl3".to_string()))),
                     check_jr_jump,
                 ),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JR_IF_C.op_code,
         ),
@@ -267,25 +359,54 @@ fn test_jr_ok() -> Result<(), String> {
             Some(LabelRef {
                 reference: Ref::Relative(
                     Address(16386),
-                    Label::from_string("lbl4".to_string()),
+                    SExp::Symbol(Symbol::Label(Label::from_string("lbl4".to_string()))),
                     check_jr_jump,
                 ),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JR_IF_C.op_code,
         ),
+        (
+            "(jr #z 'lbl5)",
+            Some(LabelRef {
+                reference: Ref::Relative(
+                    Address(16386),
+                    SExp::Symbol(Symbol::Label(Label::from_string("lbl5".to_string()))),
+                    check_jr_jump,
+                ),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_JR_IF_Z.op_code,
+        ),
+        (
+            "(jr #nc 'lbl6)",
+            Some(LabelRef {
+                reference: Ref::Relative(
+                    Address(16386),
+                    SExp::Symbol(Symbol::Label(Label::from_string("lbl6".to_string()))),
+                    check_jr_jump,
+                ),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_JR_IF_NC.op_code,
+        ),
     ];
 
     for (exp, expect_label_ref, op_code) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let got_label_ref = jr(&mut state, tl.forms.pop().unwrap())?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
 
         assert_eq_label_ref(got_label_ref, expect_label_ref);
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(
             sec.memory.mem[0], op_code,
             "op_code was {:x}, expected {:?}, expression={:?}",
@@ -302,17 +423,23 @@ fn test_jr_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_jp_fails() -> Result<(), String> {
+fn test_jp_fails() -> Result<(), Error> {
     let cases = [
-        ("(jp)", "jp: needs at least one argument"),
-        ("(jp #c 'foo 'bar)", "jp: illegal arguments"),
+        (
+            "(jp)",
+            "jp: illegal arguments: (jp), valid forms: (jp #nz|#z|#nc|#c a16) (jp a16|%hl)",
+        ),
+        (
+            "(jp #c 'foo 'bar)",
+            "jp: illegal arguments: (jp #c 'foo 'bar), valid forms: (jp #nz|#z|#nc|#c a16) (jp a16|%hl)",
+        ),
     ];
 
     for (exp, err) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let r = jp(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
         assert!(
             r.is_err(),
@@ -320,13 +447,13 @@ fn test_jp_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
     Ok(())
 }
 
 #[test]
-fn test_jp_ok() -> Result<(), String> {
+fn test_jp_ok() -> Result<(), Error> {
     let cases = [
         (
             "(jp 'forward)",
@@ -334,6 +461,7 @@ fn test_jp_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("forward".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JP.op_code,
         ),
@@ -344,6 +472,7 @@ fn test_jp_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("wait".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JP_IF_C.op_code,
         ),
@@ -353,20 +482,41 @@ fn test_jp_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("wait".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_JP_IF_NZ.op_code,
         ),
+        (
+            "(jp #z 'wait)",
+            Some(LabelRef {
+                reference: Ref::from_label(Label::from_string("wait".to_string())),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_JP_IF_Z.op_code,
+        ),
+        (
+            "(jp #nc 'wait)",
+            Some(LabelRef {
+                reference: Ref::from_label(Label::from_string("wait".to_string())),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_JP_IF_NC.op_code,
+        ),
     ];
 
     for (exp, expect_label_ref, op_code) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let got_label_ref = jp(&mut state, tl.forms.pop().unwrap())?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
 
         assert_eq_label_ref(got_label_ref, expect_label_ref);
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(
             sec.memory.mem[0], op_code,
             "op_code was {:x}, expected {:?}",
@@ -387,21 +537,37 @@ fn test_jp_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_ld_fails() -> Result<(), String> {
-    let cases = [("(ld)", Address(0), "ld: needs at least two arguments")];
+fn test_ld_fails() -> Result<(), Error> {
+    let cases = [
+        ("(ld)", "ld: illegal arguments: (ld), valid forms: "),
+        (
+            "(ld %a (%hl *))",
+            "ld: illegal arguments: (ld %a (%hl *)), valid forms: ",
+        ),
+        ("(ld %c 'x)", "ld: 'x not allowed as n8 argument"),
+        (
+            "(ld %c 256)",
+            "ld: immediate value 256 out of range -128..255 in (ld %c 256)",
+        ),
+        (
+            "(ld (%hl) -129)",
+            "ld: immediate value -129 out of range -128..255 in (ld (%hl) -129)",
+        ),
+        (
+            "(ld %bc 0x10000)",
+            "ld: immediate value 65536 out of range -32768..65535 in (ld %bc 65536)",
+        ),
+    ];
 
-    for (exp, lbl_address, err) in cases {
+    for (exp, err) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        if !tl.forms[0].exps.is_empty() {
-            let lbl = expect_label_name(&tl.forms[0].exps[tl.forms[0].exps.len() - 1])?;
-            state.label_addresses.insert(lbl, lbl_address);
-        }
-
-        let r = ld(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
-        assert_eq!(r.unwrap_err(), err);
+        // the valid forms of ld are long, only the start is checked
+        let got_err = r.unwrap_err().to_string();
+        assert!(got_err.starts_with(err), "exp={:?}, err={}", exp, got_err);
     }
     Ok(())
 }
@@ -410,7 +576,7 @@ fn test_ld_fails() -> Result<(), String> {
 /// +c1+ = 0x01
 /// +c2+ = 0x10
 #[test]
-fn test_ld_ok() -> Result<(), String> {
+fn test_ld_ok() -> Result<(), Error> {
     let cases = [
         // load hl mem to reg forward
         (
@@ -419,6 +585,7 @@ fn test_ld_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("forward".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_HL_FROM_IMMEDIATE.op_code,
@@ -431,6 +598,7 @@ fn test_ld_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("lbl".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_BC_FROM_IMMEDIATE.op_code,
@@ -447,9 +615,11 @@ fn test_ld_ok() -> Result<(), String> {
                         SExp::Symbol(Symbol::Label(Label::from_str("lbl2"))),
                         SExp::Symbol(Symbol::Label(Label::from_str("lbl1"))),
                     ],
+                    span: Span::default(),
                 }),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_BC_FROM_IMMEDIATE.op_code,
@@ -462,6 +632,7 @@ fn test_ld_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("lbl".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_DE_FROM_IMMEDIATE.op_code,
@@ -474,6 +645,7 @@ fn test_ld_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("lbl".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_HL_FROM_IMMEDIATE.op_code,
@@ -561,6 +733,7 @@ fn test_ld_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("lblX".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_A_FROM_DEREF_LABEL.op_code,
@@ -597,6 +770,7 @@ fn test_ld_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("lbl".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1,
+                span: Span::default(),
             }),
             3,
             INSTR_LD_TO_DEREF_LABEL_FROM_A.op_code,
@@ -619,12 +793,12 @@ fn test_ld_ok() -> Result<(), String> {
         state.const_values.insert("+c2+".to_string(), 0x10);
 
         let mut tl = parse_from_string(exp)?;
-        let got_label_ref =
-            ld(&mut state, tl.forms.pop().unwrap()).map_err(|e| format!("{}: {}", exp, e))?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())
+            .map_err(|e| Error::syntax(format!("{}: {}", exp, e)))?;
 
         assert_eq_label_ref(got_label_ref, expect_label_ref);
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(
             sec.memory.mem[0], op_code,
             "ld expression={:?}, op_code was {:x}, expected {:?}",
@@ -647,25 +821,31 @@ fn test_ld_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_inc_fails() -> Result<(), String> {
+fn test_inc_fails() -> Result<(), Error> {
     let cases = [
-        ("(inc)", "inc: needs exactly one argument"),
-        ("(inc 42)", "inc: illegal argument: Immediate(42)"),
+        (
+            "(inc)",
+            "inc: illegal arguments: (inc), valid forms: (inc %bc|%b|%c|%de|%d|%e|%hl|%h|%l|%sp|(%hl)|%a)",
+        ),
+        (
+            "(inc 42)",
+            "inc: illegal arguments: (inc 42), valid forms: (inc %bc|%b|%c|%de|%d|%e|%hl|%h|%l|%sp|(%hl)|%a)",
+        ),
     ];
 
     for (exp, err) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let r = inc(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
-        assert_eq!(r.unwrap_err(), err);
+        assert_eq!(r.unwrap_err().to_string(), err);
     }
     Ok(())
 }
 
 #[test]
-fn test_inc_ok() -> Result<(), String> {
+fn test_inc_ok() -> Result<(), Error> {
     let cases = [
         ("(inc %a)", 1, INSTR_INC_A.op_code),
         ("(inc %bc)", 1, INSTR_INC_BC.op_code),
@@ -675,9 +855,9 @@ fn test_inc_ok() -> Result<(), String> {
     for (exp, byte_size, op) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
-        inc(&mut state, tl.forms.pop().unwrap())?;
+        instruction(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem[0], op, "inc expression={:?}", exp);
 
         assert_eq!(state.current_section_address.0, TEST_SEC_ADDR.0 + byte_size);
@@ -686,25 +866,31 @@ fn test_inc_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_dec_fails() -> Result<(), String> {
+fn test_dec_fails() -> Result<(), Error> {
     let cases = [
-        ("(dec)", "dec: needs exactly one argument"),
-        ("(dec 42)", "dec: illegal argument: Immediate(42)"),
+        (
+            "(dec)",
+            "dec: illegal arguments: (dec), valid forms: (dec %b|%bc|%c|%d|%de|%e|%h|%hl|%l|(%hl)|%sp|%a)",
+        ),
+        (
+            "(dec 42)",
+            "dec: illegal arguments: (dec 42), valid forms: (dec %b|%bc|%c|%d|%de|%e|%h|%hl|%l|(%hl)|%sp|%a)",
+        ),
     ];
 
     for (exp, err) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let r = dec(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
-        assert_eq!(r.unwrap_err(), err);
+        assert_eq!(r.unwrap_err().to_string(), err);
     }
     Ok(())
 }
 
 #[test]
-fn test_dec_ok() -> Result<(), String> {
+fn test_dec_ok() -> Result<(), Error> {
     let cases = [
         ("(dec %a)", 1, INSTR_DEC_A.op_code),
         ("(dec %b)", 1, INSTR_DEC_B.op_code),
@@ -715,9 +901,9 @@ fn test_dec_ok() -> Result<(), String> {
     for (exp, byte_size, op) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
-        dec(&mut state, tl.forms.pop().unwrap())?;
+        instruction(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem[0], op, "dec expression={:?}", exp);
 
         assert_eq!(state.current_section_address.0, TEST_SEC_ADDR.0 + byte_size);
@@ -726,13 +912,26 @@ fn test_dec_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_cp_fails() -> Result<(), String> {
-    let cases = [("(cp)", "cp: needs exactly one argument")];
+fn test_cp_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(cp)",
+            "cp: illegal arguments: (cp), valid forms: (cp %a %b|%c|%d|%e|%h|%l|(%hl)|%a|n8)",
+        ),
+        (
+            "(cp %bc)",
+            "cp: illegal arguments: (cp %bc), valid forms: (cp %a %b|%c|%d|%e|%h|%l|(%hl)|%a|n8)",
+        ),
+        (
+            "(cp 0x100)",
+            "cp: immediate value 256 out of range -128..255 in (cp 256)",
+        ),
+    ];
 
     for (exp, err) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
-        let r = cp(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
         assert!(
             r.is_err(),
@@ -740,20 +939,20 @@ fn test_cp_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
     Ok(())
 }
 
 #[test]
-fn test_cp_ok() -> Result<(), String> {
+fn test_cp_ok() -> Result<(), Error> {
     let cases = [("(cp 144)", 2, INSTR_CP_IMMEDIATE.op_code, 144)];
     for (exp, byte_size, op, arg1) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
-        cp(&mut state, tl.forms.pop().unwrap())?;
+        instruction(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem[0], op, "cp expression={:?}", exp);
         assert_eq!(
             sec.memory.mem[1], arg1,
@@ -767,17 +966,22 @@ fn test_cp_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_or_fails() -> Result<(), String> {
+fn test_or_fails() -> Result<(), Error> {
     let cases = [
-        ("(or)", "or: needs exactly two arguments"),
-        ("(or %a)", "or: needs exactly two arguments"),
-        ("(or %a %b %c)", "or: needs exactly two arguments"),
+        (
+            "(or)",
+            "or: illegal arguments: (or), valid forms: (or %a %b|%c|%d|%e|%h|%l|(%hl)|%a|n8)",
+        ),
+        (
+            "(or %a %b %c)",
+            "or: illegal arguments: (or %a %b %c), valid forms: (or %a %b|%c|%d|%e|%h|%l|(%hl)|%a|n8)",
+        ),
     ];
 
     for (exp, err) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
-        let r = or(&mut state, tl.forms.pop().unwrap());
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
         assert!(
             r.is_err(),
@@ -785,20 +989,23 @@ fn test_or_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
     Ok(())
 }
 
 #[test]
-fn test_or_ok() -> Result<(), String> {
-    let cases = [("(or %a %c)", INSTR_OR_A_C.op_code)];
+fn test_or_ok() -> Result<(), Error> {
+    let cases = [
+        ("(or %a %c)", INSTR_OR_A_C.op_code),
+        ("(or %a)", INSTR_OR_A_A.op_code),
+    ];
     for (exp, op) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
-        or(&mut state, tl.forms.pop().unwrap())?;
+        instruction(&mut state, tl.forms.pop().unwrap())?;
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(sec.memory.mem[0], op, "or expression={:?}", exp);
 
         assert_eq!(state.current_section_address.0, TEST_SEC_ADDR.0 + 1);
@@ -806,202 +1013,1754 @@ fn test_or_ok() -> Result<(), String> {
     Ok(())
 }
 
+/// +c1+ is a predefined constant in the test with value 0x01
 #[test]
-fn test_label() -> Result<(), String> {
-    // start at 0 so the label address computation gets easier
-    let mut state = test_state_with_section_offset(Address(0x00));
-    let lt = parse_from_string("(ld %a 0) (cp 144) (label 'test) (ld %b 0)")?;
-    assemble_in_state(lt, &mut state)?;
-
-    let lbl_address = state
-        .label_addresses
-        .get(&Label::from_string("test".to_string()));
+fn test_alu_ok() -> Result<(), Error> {
+    let cases = [
+        ("(add %a %b)", vec![INSTR_ADD_A_B.op_code]),
+        ("(add %a (%hl))", vec![INSTR_ADD_A_DEREF_HL.op_code]),
+        ("(add %a 0x10)", vec![INSTR_ADD_A_IMMEDIATE.op_code, 0x10]),
+        ("(adc %a %e)", vec![INSTR_ADC_A_E.op_code]),
+        ("(adc 1)", vec![INSTR_ADC_A_IMMEDIATE.op_code, 0x01]),
+        ("(sub %a %h)", vec![INSTR_SUB_A_H.op_code]),
+        ("(sub (%hl))", vec![INSTR_SUB_A_DEREF_HL.op_code]),
+        ("(sbc %a %l)", vec![INSTR_SBC_A_L.op_code]),
+        ("(sbc %a 0xFF)", vec![INSTR_SBC_A_IMMEDIATE.op_code, 0xFF]),
+        ("(and %a %d)", vec![INSTR_AND_A_D.op_code]),
+        ("(and 0x0F)", vec![INSTR_AND_A_IMMEDIATE.op_code, 0x0F]),
+        ("(xor %a %a)", vec![INSTR_XOR_A_A.op_code]),
+        ("(xor +c1+)", vec![INSTR_XOR_A_IMMEDIATE.op_code, 0x01]),
+        ("(or (%hl))", vec![INSTR_OR_A_DEREF_HL.op_code]),
+        ("(or %a 0x80)", vec![INSTR_OR_A_IMMEDIATE.op_code, 0x80]),
+        ("(cp %a %b)", vec![INSTR_CP_A_B.op_code]),
+        ("(cp (| +c1+ 0x10))", vec![INSTR_CP_IMMEDIATE.op_code, 0x11]),
+        // 16-bit
+        ("(add %hl %bc)", vec![INSTR_ADD_HL_BC.op_code]),
+        ("(add %hl %de)", vec![INSTR_ADD_HL_DE.op_code]),
+        ("(add %hl %hl)", vec![INSTR_ADD_HL_HL.op_code]),
+        ("(add %hl %sp)", vec![INSTR_ADD_HL_SP.op_code]),
+        ("(add %sp 2)", vec![INSTR_ADD_SP_IMMEDIATE.op_code, 0x02]),
+        ("(add %sp -2)", vec![INSTR_ADD_SP_IMMEDIATE.op_code, 0xFE]),
+    ];
+    for (exp, bytes) in cases {
+        let mut state = test_state();
+        state.const_values.insert("+c1+".to_string(), 0x01);
+        let mut tl = parse_from_string(exp)?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
+        assert!(got_label_ref.is_none());
 
-    assert_eq!(lbl_address, Some(&Address(4)));
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+        assert_eq!(
+            sec.memory.mem[0..bytes.len()],
+            bytes,
+            "alu expression={:?}",
+            exp
+        );
+        assert_eq!(
+            state.current_section_address.0,
+            TEST_SEC_ADDR.0 + bytes.len() as u64
+        );
+    }
     Ok(())
 }
 
 #[test]
-fn test_resolve_label_fails() -> Result<(), String> {
-    let test_label = Label::from_string("lbl".to_string());
+fn test_alu_fails() -> Result<(), Error> {
     let cases = [
         (
-            LabelRef {
-                reference: Ref::Relative(TEST_SEC_ADDR, test_label.clone(), check_jr_jump),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1,
-            },
-            Address(TEST_SEC_ADDR.0 - 129),
-            "jr: max -128 jumps back, was -129",
+            "(add %b %c)",
+            "add: illegal arguments: (add %b %c), valid forms: ",
         ),
         (
-            LabelRef {
-                reference: Ref::Relative(TEST_SEC_ADDR, test_label.clone(), check_jr_jump),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1,
-            },
-            Address(TEST_SEC_ADDR.0 + 130),
-            "jr: max 127 jumps forward, was 130",
+            "(sub %hl %bc)",
+            "sub: illegal arguments: (sub %hl %bc), valid forms: (sub %a %b|%c|%d|%e|%h|%l|(%hl)|%a|n8)",
         ),
+        ("(add %sp -129)", "add: offset -129 out of range -128..127"),
+        ("(and %a 'lbl)", "and: 'lbl not allowed as n8 argument"),
     ];
 
-    for (label_ref, label_address, err) in cases {
-        let mut label_refs = Vec::new();
-        label_refs.push(label_ref);
-
+    for (exp, err) in cases {
         let mut state = test_state();
-        state
-            .label_addresses
-            .insert(test_label.clone(), label_address);
+        let mut tl = parse_from_string(exp)?;
 
-        let r = resolve_labels(label_refs, &mut state);
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
-        assert!(r.is_err(), "expected error '{}'", err);
-        assert_eq!(r.unwrap_err(), err);
+        let got_err = r.unwrap_err().to_string();
+        assert!(got_err.starts_with(err), "exp={:?}, err={}", exp, got_err);
     }
-
     Ok(())
 }
 
 #[test]
-fn test_resolve_label_ok() -> Result<(), String> {
-    let test_label = Label::from_string("lbl".to_string());
-
+fn test_prefix_fails() -> Result<(), Error> {
     let cases = [
         (
-            "jump maximum back",
-            LabelRef {
-                reference: Ref::Relative(TEST_SEC_ADDR, test_label.clone(), check_jr_jump),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1,
-            },
-            Address(TEST_SEC_ADDR.0 - 126),
-            (-126i32) as u8,
-            0x00,
+            "(swap)",
+            "swap: illegal arguments: (swap), valid forms: (swap %b|%c|%d|%e|%h|%l|(%hl)|%a)",
         ),
         (
-            "jump maximum forward",
-            LabelRef {
-                reference: Ref::Relative(TEST_SEC_ADDR, test_label.clone(), check_jr_jump),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1,
-            },
-            Address(TEST_SEC_ADDR.0 + 127),
-            127,
-            0x00,
+            "(swap %bc)",
+            "swap: illegal arguments: (swap %bc), valid forms: (swap %b|%c|%d|%e|%h|%l|(%hl)|%a)",
         ),
         (
-            "absolute jump",
-            LabelRef {
-                reference: Ref::from_label(test_label.clone()),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1,
-            },
-            Address(0x5001),
-            0x01,
-            0x50,
+            "(bit 8 %a)",
+            "bit: illegal arguments: (bit 8 %a), valid forms: (bit 0|1|2|3|4|5|6|7 %b|%c|%d|%e|%h|%l|(%hl)|%a)",
         ),
         (
-            // check ref, Ref::Expression evaluation is tested in interpreter_test.rs
-            "arith address 1",
-            LabelRef {
-                reference: Ref::from_label(test_label.clone()),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1,
-            },
-            Address(0x6754),
-            0x54,
-            0x67,
+            "(set %a)",
+            "set: illegal arguments: (set %a), valid forms: (set 0|1|2|3|4|5|6|7 %b|%c|%d|%e|%h|%l|(%hl)|%a)",
         ),
     ];
-    for (test, label_ref, label_address, mem_1, mem_2) in cases {
-        let patch_ix = label_ref.patch_index;
-        let mut label_refs = Vec::new();
-        label_refs.push(label_ref);
 
+    for (exp, err) in cases {
         let mut state = test_state();
-        state
-            .label_addresses
-            .insert(test_label.clone(), label_address);
+        let mut tl = parse_from_string(exp)?;
 
-        resolve_labels(label_refs, &mut state)?;
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
-        assert_eq!(
-            sec.memory.mem[patch_ix], mem_1,
-            "mem_1 was {:x}, expected {:x}, test: {}",
-            sec.memory.mem[patch_ix], mem_1, test,
-        );
-        assert_eq!(
-            sec.memory.mem[patch_ix + 1],
-            mem_2,
-            "mem_2 was {:x}, expected {:x}, test: {}",
-            sec.memory.mem[patch_ix + 1],
-            mem_2,
-            test,
-        );
+        assert_eq!(r.unwrap_err().to_string(), err);
     }
     Ok(())
 }
 
-// helper
-
-static TEST_SEC_NAME: &'static str = "test-section";
-static TEST_SEC_ADDR: Address = Address(0x4000);
-
-fn assert_eq_label_ref(got: Option<LabelRef>, expect: Option<LabelRef>) {
-    assert!(got.is_some() == expect.is_some());
-
-    if let Some(expect_ref) = expect {
-        let got_ref = got.expect("got is some");
-        assert_eq_ref(&got_ref.reference, &expect_ref.reference);
-        assert_eq!(got_ref.sec_name, expect_ref.sec_name, "sec_name");
-        assert_eq!(got_ref.patch_index, expect_ref.patch_index, "patch_index");
-    }
-}
-
 #[test]
-fn test_call_fails() -> Result<(), String> {
+fn test_prefix_ok() -> Result<(), Error> {
     let cases = [
-        ("(call)", "call: needs at least one argument"),
-        ("(call #c 'foo 'bar)", "call: illegal arguments"),
+        ("(swap %a)", INSTR_PREFIX_SWAP_A.op_code),
+        ("(rlc %b)", INSTR_PREFIX_RLC_B.op_code),
+        ("(srl %a)", INSTR_PREFIX_SRL_A.op_code),
+        ("(bit 7 (%hl))", INSTR_PREFIX_BIT_7_DEREF_HL.op_code),
+        ("(bit 0x7 (%hl))", INSTR_PREFIX_BIT_7_DEREF_HL.op_code),
+        ("(res 0 %b)", INSTR_PREFIX_RES_0_B.op_code),
+        ("(set 3 %e)", INSTR_PREFIX_SET_3_E.op_code),
     ];
-
-    for (exp, err) in cases {
+    for (exp, op) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
+        instruction(&mut state, tl.forms.pop().unwrap())?;
 
-        let r = call(&mut state, tl.forms.pop().unwrap());
-
-        assert!(
-            r.is_err(),
-            "expected error '{}' on expression = {:?}",
-            err,
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+        assert_eq!(
+            sec.memory.mem[0..2],
+            [0xCB, op],
+            "prefix expression={:?}",
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+
+        assert_eq!(state.current_section_address.0, TEST_SEC_ADDR.0 + 2);
     }
     Ok(())
 }
 
 #[test]
-fn test_call_ok() -> Result<(), String> {
-    let cases = [
-        (
-            "(call 'fn)",
-            Some(LabelRef {
-                reference: Ref::from_label(Label::from_string("fn".to_string())),
-                sec_name: TEST_SEC_NAME.to_string(),
-                patch_index: 1, // address bytes start at byte 1
-            }),
-            sm83::INSTR_CALL.op_code,
-        ),
-        (
+fn test_label() -> Result<(), Error> {
+    // start at 0 so the label address computation gets easier
+    let mut state = test_state_with_section_offset(Address(0x00));
+    let lt = parse_from_string("(ld %a 0) (cp 144) (label 'test) (ld %b 0)")?;
+    assemble_in_state(lt, &mut state)?;
+
+    let lbl_address = state
+        .label_addresses
+        .get(&Label::from_string("test".to_string()));
+
+    assert_eq!(lbl_address, Some(&Address(4)));
+    Ok(())
+}
+
+#[test]
+fn test_expression_operands() -> Result<(), Error> {
+    let mut state = test_state_with_section_offset(Address(0x00));
+    let src = "(def-constant +tile-size+ 16)
+               (ld %hl (+ 'tiles (* +tile-size+ 2)))
+               (ld %a (hi 'buffer))
+               (ld %b (lo (- 'buffer 0x10)))
+               ('tiles db 0)
+               ('buffer label 'buffer-end)";
+    let tl = parse_from_string(src)?;
+    let label_refs = assemble_forms(tl, &mut state)?;
+    // the constants are replaced in the deferred expressions
+    match &label_refs[0].reference {
+        Ref::Expression(exp) => assert_eq!(exp.to_string(), "(+ 'tiles 32)"),
+        reference => panic!("expression expected, got {:?}", reference),
+    }
+    resolve_labels(label_refs, &mut state)?;
+
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            INSTR_LD_TO_HL_FROM_IMMEDIATE.op_code,
+            0x27,
+            0x00,
+            INSTR_LD_TO_A_FROM_IMMEDIATE.op_code,
+            0x00,
+            INSTR_LD_TO_B_FROM_IMMEDIATE.op_code,
+            0xF8,
+            0,
+        ]
+    );
+
+    let mut state = test_state();
+    let err =
+        assemble_in_state(parse_from_string("(ld %a (+ 'x 1)) ('x nop)")?, &mut state).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "<string>:1:1: value 16387 out of range -128..255\n  (ld %a (+ 'x 1)) ('x nop)\n  ^"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_data_label_refs() -> Result<(), Error> {
+    let mut state = test_state_with_section_offset(Address(0x00));
+    let src = "(def-constant +entry-size+ 2)
+               ('states dw 'state-title 'state-play (+ 'state-play +entry-size+))
+               (db (lo 'handler) (hi 'handler) 0x10)
+               ('state-title nop)
+               ('state-play nop)";
+    let label_refs = assemble_forms(parse_from_string(src)?, &mut state)?;
+    assert_eq!(label_refs.len(), 5);
+    assert_eq!(
+        label_refs
+            .iter()
+            .map(|label_ref| label_ref.patch_index)
+            .collect::<Vec<_>>(),
+        vec![0, 2, 4, 6, 7]
+    );
+
+    state
+        .label_addresses
+        .insert(Label::from_str("handler"), Address(0x1234));
+    resolve_labels(label_refs, &mut state)?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            0x09, 0x00, 0x0A, 0x00, 0x0C, 0x00, // dw
+            0x34, 0x12, 0x10, // db
+            0x00, 0x00, // nops
+        ]
+    );
+
+    // a data word may be a negative distance
+    let mut state = test_state_with_section_offset(Address(0x00));
+    assemble_in_state(
+        parse_from_string("('start dw (- 'start 'end)) ('end nop)")?,
+        &mut state,
+    )?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(sec.memory.mem[0..sec.memory.mem_ptr], [0xFE, 0xFF, 0x00]);
+
+    // the data references are patches in the object
+    let mut state = test_state_with_section_offset(Address(0x00));
+    let label_refs = assemble_forms(parse_from_string("(dw 'a) (db (hi 'a))")?, &mut state)?;
+    let obj = state_to_object(&state, label_refs)?;
+    assert_eq!(
+        obj.patches
+            .iter()
+            .map(|patch| (patch.patch_index, &patch.patch))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                0,
+                &Patch::Immediate16(SExp::Symbol(Symbol::Label(Label::from_str("a"))))
+            ),
+            (2, &Patch::Immediate8(must_parse_exp("(hi 'a)"))),
+        ]
+    );
+
+    let cases = [
+        ("(db 'lbl) ('lbl nop)", "value 16385 out of range -128..255"),
+        ("(db (lo 'undefined))", "undefined label: 'undefined"),
+        (
+            "(dw (- 'lbl 0xD000)) ('lbl nop)",
+            "value -36862 out of range -32768..65535",
+        ),
+    ];
+    for (src, err) in cases {
+        let mut state = test_state();
+        let got_err = assemble_in_state(parse_from_string(src)?, &mut state).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+        assert_eq!(got_err.span.map(|span| span.col), Some(1), "src={:?}", src);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_macro() -> Result<(), Error> {
+    let mut state = test_state_with_section_offset(Address(0x00));
+    let src = "(defmacro load-a (value) `(ld %a ,value))
+               (defmacro twice (&rest forms) `(,@forms) `(,@forms))
+               (load-a 1)
+               ('here twice load-a 2)
+               (jr 'here)";
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            INSTR_LD_TO_A_FROM_IMMEDIATE.op_code,
+            1,
+            INSTR_LD_TO_A_FROM_IMMEDIATE.op_code,
+            2,
+            INSTR_LD_TO_A_FROM_IMMEDIATE.op_code,
+            2,
+            sm83::INSTR_JR.op_code,
+            (-6i8) as u8,
+        ]
+    );
+    assert_eq!(
+        state.label_addresses.get(&Label::from_str("here")),
+        Some(&Address(2))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_macro_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(defmacro ld (a) `(nop))",
+            "defmacro: ld is a built-in form",
+        ),
+        (
+            "(defmacro db (a) `(nop))",
+            "defmacro: db is a built-in form",
+        ),
+        (
+            "(defmacro m () `(nop)) (defmacro m () `(halt))",
+            "defmacro: duplicate macro definition: m",
+        ),
+        (
+            "(defmacro loop () `(loop)) (loop)",
+            "loop: more than 64 nested macro expansions, is the macro recursive?",
+        ),
+    ];
+
+    for (src, err) in cases {
+        let mut state = test_state();
+        let got_err = assemble_in_state(parse_from_string(src)?, &mut state).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+    }
+
+    // located in the macro body, with the call as note
+    let mut state = test_state();
+    let src = "(defmacro load-a (value)\n  `(ld %a ,value))\n(nop)\n(load-a %sp)";
+    let err = assemble_in_state(parse_from_string(src)?, &mut state).unwrap_err();
+    let got_err = err.to_string();
+    assert!(
+        got_err.starts_with("<string>:2:4: ld: illegal arguments: (ld %a %sp)"),
+        "err={}",
+        got_err
+    );
+    assert!(
+        got_err.ends_with(
+            "\n    `(ld %a ,value))\n     ^\n  note: in expansion of load-a at <string>:4:1"
+        ),
+        "err={}",
+        got_err
+    );
+
+    let mut state = test_state();
+    let err = assemble_in_state(parse_from_string("(nop)\n(load-a 1)")?, &mut state).unwrap_err();
+    assert_eq!(err.message, "unknown top-level: \"load-a\"");
+    Ok(())
+}
+
+#[test]
+fn test_conditional() -> Result<(), Error> {
+    let src = "(def-constant +cgb+ 1)
+               (ifndef +debug+ (def-constant +debug+ 0))
+               (if +cgb+ (ld %a 0x11) (ld %a 0x01))
+               ('check when +debug+ (halt) (halt))
+               (cond ((= +cgb+ 0) (db 1)) (else (db 2)))
+               (defmacro assert-a (value) `(when +debug+ (cp ,value) (jr #nz 'check)))
+               (assert-a 3)
+               (jr 'check)";
+    let mut state = test_state_with_section_offset(Address(0x00));
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            INSTR_LD_TO_A_FROM_IMMEDIATE.op_code,
+            0x11,
+            2,
+            sm83::INSTR_JR.op_code,
+            (-3i8) as u8,
+        ]
+    );
+    assert_eq!(
+        state.label_addresses.get(&Label::from_str("check")),
+        Some(&Address(2))
+    );
+
+    // a define decides before the first form
+    let mut state = test_state_with_section_offset(Address(0x00));
+    state.const_values.insert("+debug+".to_string(), 1);
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(sec.memory.mem_ptr, 11);
+
+    let mut state = test_state();
+    let err =
+        assemble_in_state(parse_from_string("(nop)\n(if +x+ (nop))")?, &mut state).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "<string>:2:1: no constant value for symbol: +x+\n  (if +x+ (nop))\n  ^"
+    );
+
+    let mut state = test_state();
+    let err =
+        assemble_in_state(parse_from_string("(defmacro when () `(nop))")?, &mut state).unwrap_err();
+    assert_eq!(err.message, "defmacro: when is a built-in form");
+    Ok(())
+}
+
+#[test]
+fn test_loop() -> Result<(), Error> {
+    let src = "(def-constant +i+ 7)
+               (repeat 2 (nop))
+               ('table dotimes (+i+ 3) (db (* +i+ 2)))
+               (for (+x+ 0x10 0 -8) (dw (+ 'table +x+)))
+               (db +i+)";
+    let mut state = test_state_with_section_offset(Address(0x00));
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            sm83::INSTR_NOP.op_code,
+            sm83::INSTR_NOP.op_code,
+            0,
+            2,
+            4,
+            0x12,
+            0x00,
+            0x0A,
+            0x00,
+            7
+        ]
+    );
+    assert_eq!(state.const_values.get("+x+"), None);
+
+    let mut state = test_state();
+    let err = assemble_in_state(
+        parse_from_string("(for (+v+ 254 258)\n  (db +v+))")?,
+        &mut state,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "<string>:2:3: db: immediate value 256 out of range -128..255 in (db +v+)\n    (db +v+))\n    ^\n  note: in for with +v+ = 256 at <string>:1:1"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_local_labels() -> Result<(), Error> {
+    let src = "(defmacro wait (n) `(ld %b ,n) `('.loop dec %b) `(jr #nz '.loop))
+               ('copy ld %b 2)
+               ('.loop dec %b)
+               (jr #nz '.loop)
+               ('fill wait 3)
+               (scope ('.loop nop) (jr '.loop))
+               ('@ dec %b)
+               (jr #z '@+)
+               (jr #nz '@-)
+               ('@ jp 'copy.loop)";
+    let mut state = test_state_with_section_offset(Address(0x00));
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            INSTR_LD_TO_B_FROM_IMMEDIATE.op_code,
+            2,
+            INSTR_DEC_B.op_code, // copy.loop
+            sm83::INSTR_JR_IF_NZ.op_code,
+            (-3i8) as u8,
+            INSTR_LD_TO_B_FROM_IMMEDIATE.op_code, // fill
+            3,
+            INSTR_DEC_B.op_code, // the .loop of the expansion
+            sm83::INSTR_JR_IF_NZ.op_code,
+            (-3i8) as u8,
+            sm83::INSTR_NOP.op_code, // the .loop of the scope form
+            sm83::INSTR_JR.op_code,
+            (-3i8) as u8,
+            INSTR_DEC_B.op_code, // the first anonymous label
+            sm83::INSTR_JR_IF_Z.op_code,
+            2,
+            sm83::INSTR_JR_IF_NZ.op_code,
+            (-5i8) as u8,
+            sm83::INSTR_JP.op_code, // the second anonymous label
+            0x02,
+            0x00,
+        ]
+    );
+    let label_names: Vec<&str> = state
+        .label_addresses
+        .keys()
+        .map(|lbl| lbl.name())
+        .filter(|name| name.contains('.') || name.starts_with('@'))
+        .collect::<std::collections::BTreeSet<&str>>()
+        .into_iter()
+        .collect();
+    assert_eq!(
+        label_names,
+        ["@0", "@1", "@scope0.loop", "@scope1.loop", "copy.loop"]
+    );
+
+    let cases = [
+        (
+            "('copy nop) (scope ('.loop nop)) (jr '.loop)",
+            "undefined label: 'copy.loop",
+        ),
+        (
+            "('copy nop) ('.loop nop) ('.loop nop)",
+            "duplicate label definition: 'copy.loop",
+        ),
+        (
+            "(jr '.loop)",
+            "local label '.loop outside of a scope, define a global label or use (scope ...) before",
+        ),
+        ("(scope 1)", "scope: form expected, got 1"),
+        (
+            "(defmacro scope () `(nop))",
+            "defmacro: scope is a built-in form",
+        ),
+    ];
+    for (src, err) in cases {
+        let mut state = test_state();
+        let got_err = assemble_in_state(parse_from_string(src)?, &mut state).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_section_checks() -> Result<(), Error> {
+    let rom = "(def-section .rom :offset 0x100 :length 4)\n";
+    let ram = "(def-section .ram :offset 0xC000 :length 2 :label-only true)\n";
+    let cases = [
+        (
+            "(section .rom) (db 1 2 3) (dw 4)",
+            "section .rom (0x0100, length 0x4) overflows: 2 bytes at 0x0103",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .rom) (ld %a 1) (ld %bc 2)",
+            "section .rom (0x0100, length 0x4) overflows: 3 bytes at 0x0102",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .rom) (ds 5)",
+            "section .rom (0x0100, length 0x4) overflows: 5 bytes at 0x0100",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .ram) (db) (dw)",
+            "section .ram (0xC000, length 0x2) overflows: 2 bytes at 0xC001",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .ram) (db) (db 1)",
+            "section .ram is label-only, no bytes can be written at 0xC001",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .ram) (nop)",
+            "section .ram is label-only, no bytes can be written at 0xC000",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-section .code :offset 0xFFFE) (section .code) (ld %a 1) (nop)",
+            "section .code (0xFFFE) grows beyond the address space 0x0000-0xFFFF: 1 bytes at 0x10000",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .rom :offset 0x200 :length 4)",
+            "duplicate section definition: .rom",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-section .rom2 :offset 0x103 :length 4)",
+            "section .rom2 (0x0103-0x0106) overlaps section .rom (0x0100-0x0103)",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .rom2 :offset 0xFE :length 4)",
+            "section .rom2 (0x00FE-0x0101) overlaps section .rom (0x0100-0x0103)",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .hi :offset 0xFFF0 :length 0x11)",
+            "section .hi (0xFFF0, length 0x11) exceeds the address space 0x0000-0xFFFF",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .hi :offset 0x10000 :label-only true)",
+            "section .hi (0x10000, length 0x0) exceeds the address space 0x0000-0xFFFF",
+            ErrorKind::Range,
+        ),
+    ];
+    for (src, err, kind) in cases {
+        let src = format!("{}{}{}", rom, ram, src);
+        let mut state = State::new(&OsFileSystem);
+        let got_err = assemble_in_state(parse_from_string(&src)?, &mut state).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+        assert_eq!(got_err.kind, kind, "src={:?}", src);
+    }
+
+    // adjacent sections and label-only sections on top of others are fine
+    let src = format!(
+        "{}{}(def-section .rom2 :offset 0x104 :length 4)
+         (def-section .view :offset 0x100 :length 8 :label-only true)
+         (section .rom) (db 1 2 3 4) (section .ram) (db) (db)",
+        rom, ram
+    );
+    let mut state = State::new(&OsFileSystem);
+    assemble_in_state(parse_from_string(&src)?, &mut state)?;
+
+    // located at the form that overflows
+    let src = format!("{}(section .rom)\n(db 1 2)\n(db 3 4 5)", rom);
+    let mut state = State::new(&OsFileSystem);
+    let err = assemble_in_state(parse_from_string(&src)?, &mut state).unwrap_err();
+    assert_eq!(
+        err.span.map(|span| span.to_string()),
+        Some("<string>:4:1".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_growable_and_floating_sections() -> Result<(), Error> {
+    let src = "(def-region rom :offset 0x100 :length 0x20)
+               (def-region ram :offset 0xC000 :length 0x10)
+               (def-section .fixed :offset 0x104)
+               (def-section .code :region rom :align 8)
+               (def-section .data :region rom)
+               (def-section .vars :region ram :label-only true)
+               (section .fixed) (db 1 2 3)
+               (section .code) ('start nop) (jr 'start) (jp 'value)
+               (section .data) ('value db 9)
+               (section .vars) ('counter db) ('pointer dw)
+               (section .fixed) (ld %a ('counter)) (dw 'pointer)
+               (section .code) (label 'here) (jp 'here)
+               (section .vars) ('flag db)";
+    let mut state = State::new(&OsFileSystem);
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+
+    let placed = |name: &str| {
+        let sec = state.lookup_section(name).unwrap();
+        (sec.offset.0, sec.extent())
+    };
+    assert_eq!(placed("fixed"), (0x104, 8));
+    assert_eq!(placed("code"), (0x110, 9));
+    assert_eq!(placed("data"), (0x100, 1));
+    assert_eq!(placed("vars"), (0xC000, 4));
+    let address = |name: &str| state.label_addresses[&Label::from_str(name)].0;
+    assert_eq!(address("start"), 0x110);
+    assert_eq!(address("value"), 0x100);
+    assert_eq!(address("pointer"), 0xC001);
+    // a section continues after its bytes when it is entered again
+    assert_eq!(address("here"), 0x116);
+    assert_eq!(address("flag"), 0xC003);
+    assert_eq!(
+        state.lookup_section("code").unwrap().memory.mem,
+        vec![0x00, 0x18, 0xFD, 0xC3, 0x00, 0x01, 0xC3, 0x16, 0x01]
+    );
+    assert_eq!(
+        state.lookup_section("fixed").unwrap().memory.mem,
+        vec![1, 2, 3, 0xFA, 0x00, 0xC0, 0x01, 0xC0]
+    );
+
+    // the first free block of a bank
+    let src = "(def-region rom :offset 0x4000 :length 0x10 :bank 1)
+               (def-region rom :offset 0x4000 :length 0x10 :bank 2)
+               (def-section .a :region rom :length 0x10)
+               (def-section .b :region rom)
+               (def-section .c :region rom :bank 2 :align 4)
+               (section .b) (db 1) (section .c) (db 2)";
+    let mut state = State::new(&OsFileSystem);
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    assert_eq!(state.lookup_section("a").unwrap().offset.0, 0x4000);
+    assert_eq!(state.lookup_section("b").unwrap().offset.0, 0x4000);
+    assert_eq!(state.lookup_section("c").unwrap().offset.0, 0x4004);
+    let fs = MemoryFileSystem::new();
+    let err = assemble_source("main.asm", src, &fs, &Environment::default()).unwrap_err();
+    assert_eq!(
+        err.message,
+        "flat assembly has no banks, section .b (0x4000) overlaps the sections before"
+    );
+    assert_eq!(err.kind, ErrorKind::Range);
+
+    // a jr to a known address is resolved after the placement
+    let src = "(def-region rom :offset 0x100 :length 0x10)
+               (def-section .code :region rom)
+               (section .code) (nop) (jr 0x101)";
+    let mut state = State::new(&OsFileSystem);
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    assert_eq!(
+        state.lookup_section("code").unwrap().memory.mem,
+        vec![0x00, 0x18, 0xFE]
+    );
+    // and is a patch in an object, the linker may move any section
+    let mut state = State::new(&OsFileSystem);
+    state.relocatable = true;
+    let src = "(def-section .code :offset 0x100) (section .code) (nop) (jr 0x101)";
+    let label_refs = assemble_forms(parse_from_string(src)?, &mut state)?;
+    let obj = state_to_object(&state, label_refs)?;
+    assert_eq!(
+        obj.patches[0].patch,
+        Patch::Relative8(3, SExp::Immediate(0x101))
+    );
+
+    let region = "(def-region rom :offset 0x100 :length 4)\n";
+    let cases = [
+        (
+            "(def-section .a :region rom) (section .a) (db 1 2 3 4 5)",
+            "section .a (0x5 bytes, align 1) does not fit into region rom",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .a :offset 0x100) (def-section .b :region rom :align 2)
+             (section .a) (db 1) (section .b) (db 2 3 4)",
+            "section .b (0x3 bytes, align 2) does not fit into region rom",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .a :offset 0x100) (def-section .b :offset 0x102)
+             (section .a) (db 1 2 3) (section .b) (db 4)",
+            "section .b (0x0102-0x0102) overlaps section .a (0x0100-0x0102)",
+            ErrorKind::Range,
+        ),
+        (
+            "(def-section .a :offset 0x100 :region rom)",
+            "def-section: a section has either an offset or a region",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-section .a :region ram)",
+            "def-section: no such region: ram",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-section .a :align 2)",
+            "def-section: :align and :bank need a :region",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-section .a :region rom :bank 1)",
+            "def-section: region rom has no bank 1",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-region rom :offset 0x200 :length 4)",
+            "def-region: duplicate region definition: rom",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-region hi :offset 0xFFF0 :length 0x11)",
+            "region hi (0xFFF0, length 0x11) exceeds the address space 0x0000-0xFFFF",
+            ErrorKind::Range,
+        ),
+    ];
+    for (src, err, kind) in cases {
+        let src = format!("{}{}", region, src);
+        let mut state = State::new(&OsFileSystem);
+        let got_err = assemble_in_state(parse_from_string(&src)?, &mut state).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+        assert_eq!(got_err.kind, kind, "src={:?}", src);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_align_org_and_fill() -> Result<(), Error> {
+    let sections = "(def-section .rom :offset 0x100 :length 0x20)
+                    (def-section .ram :offset 0xC001 :label-only true)
+                    (def-region rom :offset 0x200 :length 0x100)
+                    (def-section .tiles :region rom :align 16)\n";
+    let cases = [
+        (
+            "(section .rom) (db 1) (align 4) ('a db 2)",
+            vec![1, 0, 0, 0, 2],
+        ),
+        ("(section .rom) (align 4 :fill 0xFF) (db 2)", vec![2]),
+        (
+            "(section .rom) (db 1 2) (align 8 :fill 0xFF) (db 3)",
+            vec![1, 2, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 3],
+        ),
+        (
+            "(section .rom) (db 1) (org 0x104 :fill 0xAA) ('a db 2)",
+            vec![1, 0xAA, 0xAA, 0xAA, 2],
+        ),
+        ("(section .rom) (org 0x100) (db 1)", vec![1]),
+        (
+            "(section .rom) (ds 3 :fill 0xFF) (ds 1)",
+            vec![0xFF, 0xFF, 0xFF, 0],
+        ),
+        (
+            "(def-constant +pad+ 0x11) (section .rom) (ds 2 :fill +pad+)",
+            vec![0x11, 0x11],
+        ),
+    ];
+    for (src, mem) in cases {
+        let src = format!("{}{}", sections, src);
+        let mut state = State::new(&OsFileSystem);
+        assemble_in_state(parse_from_string(&src)?, &mut state)?;
+        let sec = state.lookup_section("rom").unwrap();
+        assert_eq!(sec.memory.mem[..sec.memory.mem_ptr], mem, "src={:?}", src);
+    }
+
+    // label-only sections only move the address
+    let src = format!(
+        "{}(section .ram) ('flag db) (align 0x10) (label 'buffer) (org 0xC020) ('end db)",
+        sections
+    );
+    let mut state = State::new(&OsFileSystem);
+    assemble_in_state(parse_from_string(&src)?, &mut state)?;
+    assert_eq!(state.label_addresses[&Label::from_str("buffer")].0, 0xC010);
+    assert_eq!(state.label_addresses[&Label::from_str("end")].0, 0xC020);
+    assert_eq!(state.lookup_section("ram").unwrap().extent(), 0x20);
+
+    // aligned within a floating section
+    let src = format!(
+        "{}(section .tiles) (db 1) (align 16) ('tile db 2)",
+        sections
+    );
+    let mut state = State::new(&OsFileSystem);
+    assemble_in_state(parse_from_string(&src)?, &mut state)?;
+    assert_eq!(state.label_addresses[&Label::from_str("tile")].0, 0x210);
+
+    // the object keeps the constraints for the linker
+    let src = format!(
+        "{}(section .rom) (org 0x102) (db 1) (section .tiles) (align 4) (align 8)",
+        sections
+    );
+    let mut state = State::new(&OsFileSystem);
+    state.relocatable = true;
+    let label_refs = assemble_forms(parse_from_string(&src)?, &mut state)?;
+    let obj = state_to_object(&state, label_refs)?;
+    let rom = obj.lookup_section("rom").unwrap();
+    assert_eq!((rom.data_align, rom.pinned), (1, true));
+    let tiles = obj.lookup_section("tiles").unwrap();
+    assert_eq!((tiles.data_align, tiles.pinned), (8, false));
+
+    let cases = [
+        ("(align 2)", "not in a section", ErrorKind::Syntax),
+        (
+            "(section .rom) (align)",
+            "align: alignment required",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .rom) (align 0)",
+            "align: alignment must be at least 1, got 0",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .tiles) (align 32)",
+            "align: section .tiles is placed with align 16, cannot align to 32",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .rom) (db 1) (org 0x100)",
+            "org: 0x0100 is before the current address 0x0101",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .rom) (org 0x121)",
+            "section .rom (0x0100, length 0x20) overflows: 33 bytes at 0x0100",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .tiles) (org 0x200)",
+            "org: section .tiles has no fixed offset",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .rom) (ds 1 :fill 256)",
+            "ds: fill must be a byte, got 256",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .rom) (align 2 :fill -1)",
+            "align: fill must be a byte, got -1",
+            ErrorKind::Range,
+        ),
+    ];
+    for (src, err, kind) in cases {
+        let src = format!("{}{}", sections, src);
+        let mut state = State::new(&OsFileSystem);
+        let got_err = assemble_in_state(parse_from_string(&src)?, &mut state).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+        assert_eq!(got_err.kind, kind, "src={:?}", src);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_fill() {
+    assert_eq!(parse_fill("0xFF").unwrap(), 0xFF);
+    assert_eq!(parse_fill("0").unwrap(), 0);
+    assert_eq!(
+        parse_fill("0x100").unwrap_err().message,
+        "illegal fill byte: 0x100, must be 0x00-0xFF"
+    );
+    assert_eq!(
+        parse_fill("ff").unwrap_err().message,
+        "illegal fill byte: ff, must be 0x00-0xFF"
+    );
+}
+
+#[test]
+fn test_gb_header() -> Result<(), Error> {
+    let fs = MemoryFileSystem::new();
+    let src = "(def-section .header :offset 0x100 :length 0x50)
+               (def-section .rom0 :offset 0x150)
+               (section .header) (nop) (jp 'main)
+               (gb-header :title \"PSY\" :cgb 0x80 :sgb true :cartridge-type +mbc1+
+                          :rom-size 1 :ram-size 2 :licensee \"01\")
+               (section .rom0) ('main jr 'main)";
+    let env = Environment {
+        defines: HashMap::from([("+mbc1+".to_string(), 1)]),
+        ..Environment::default()
+    };
+    let assembly = assemble_source("main.asm", src, &fs, &env)?;
+    let image = assembly.image;
+    assert_eq!(image.len(), 0x152);
+    assert_eq!(image[0x104..0x134], gb::LOGO);
+    assert_eq!(image[0x134..0x137], *b"PSY");
+    assert_eq!(
+        image[0x143..0x14D],
+        [0x80, b'0', b'1', 0x03, 0x01, 0x01, 0x02, 0x00, 0x33, 0x00]
+    );
+    let mut fixed = image.clone();
+    gb::fix_checksums(&mut fixed).map_err(Error::syntax)?;
+    assert_eq!(image, fixed);
+    assert_ne!(image[0x14D], 0);
+
+    let header = "(def-section .header :offset 0x100 :length 0x50)
+                  (def-section .rom0 :offset 0x150)\n";
+    let cases = [
+        (
+            "(section .header) (gb-header)",
+            "gb-header: the header starts at 0x0104, the current address is 0x0100",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(def-region rom :offset 0x104 :length 0x100)
+             (def-section .floating :region rom) (section .floating) (gb-header)",
+            "gb-header: the header starts at 0x0104, the current address is floating in .floating",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .header) (ds 4) (gb-header) (gb-header)",
+            "gb-header: duplicate header",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .header) (ds 4) (gb-header :title \"A TITLE TOO LONG FOR A GAME\")",
+            "gb-header: title must be at most 16 ASCII characters: \"A TITLE TOO LONG FOR A GAME\"",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .header) (ds 4) (gb-header :cartridge-type 0x100)",
+            "gb-header: cartridge-type must be a byte, got 256",
+            ErrorKind::Range,
+        ),
+        (
+            "(section .header) (ds 4) (gb-header :licensee \"001\")",
+            "gb-header: licensee must be two characters or a byte, got \"001\"",
+            ErrorKind::Syntax,
+        ),
+        (
+            "(section .header) (ds 4) (gb-header :sgb 1)",
+            "gb-header: sgb must be true or false, got 1",
+            ErrorKind::Syntax,
+        ),
+    ];
+    for (src, err, kind) in cases {
+        let src = format!("{}{}", header, src);
+        let got_err = assemble_source("main.asm", &src, &fs, &Environment::default()).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+        assert_eq!(got_err.kind, kind, "src={:?}", src);
+    }
+
+    let src = "(def-section .short :offset 0x104 :length 0x10) (section .short) (gb-header)";
+    let got_err = assemble_source("main.asm", src, &fs, &Environment::default()).unwrap_err();
+    assert_eq!(
+        got_err.message,
+        "section .short (0x0104, length 0x10) overflows: 76 bytes at 0x0104"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_define() -> Result<(), Error> {
+    let cases = [
+        ("DEBUG", ("+DEBUG+", 1)),
+        ("+cgb+", ("+cgb+", 1)),
+        ("VERSION=0x12", ("+VERSION+", 0x12)),
+        ("OFFSET=-2", ("+OFFSET+", -2)),
+        ("MODE = 0b10", ("+MODE+", 2)),
+    ];
+    for (define, (name, value)) in cases {
+        assert_eq!(
+            parse_define(define)?,
+            (name.to_string(), value),
+            "define={}",
+            define
+        );
+    }
+
+    let cases = [
+        ("", "illegal define: "),
+        ("=1", "illegal define: =1"),
+        ("A B", "illegal define: A B"),
+        (
+            "A=x",
+            "illegal define value for +A+: illegal immediate value",
+        ),
+    ];
+    for (define, err) in cases {
+        assert_eq!(
+            parse_define(define).unwrap_err().message,
+            err,
+            "define={}",
+            define
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ldh() -> Result<(), Error> {
+    let src = "(def-section .hw :offset 0xFF00 :label-only true)
+        (section .hw)
+        (db) ('hw-p1 db)
+        (section .test-section)
+        (ld %a ('hw-p1))
+        (ldh ('hw-p1) %a)
+        (ld ('later) %a)
+        (ldh ('hram) %a)
+        (def-section .hram :offset 0xFF80 :length 0x10 :label-only true)
+        (section .hram)
+        ('later db)
+        ('hram db)";
+    let (state, ()) = assemble_passes(parse_from_string(src)?, test_state, assemble_in_state)?;
+
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[0..sec.memory.mem_ptr],
+        [
+            // known high page label, ld is encoded as ldh
+            sm83::INSTR_LDH_TO_A_FROM_IMMEDIATE.op_code,
+            0x01,
+            sm83::INSTR_LDH_TO_IMMEDIATE_FROM_A.op_code,
+            0x01,
+            // label defined later, in the high page in the pass before
+            sm83::INSTR_LDH_TO_IMMEDIATE_FROM_A.op_code,
+            0x80,
+            sm83::INSTR_LDH_TO_IMMEDIATE_FROM_A.op_code,
+            0x81,
+        ]
+    );
+
+    // a single pass does not know the label defined later
+    let mut state = test_state();
+    assemble_in_state(parse_from_string(src)?, &mut state)?;
+    let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(
+        sec.memory.mem[4..7],
+        [sm83::INSTR_LD_TO_DEREF_LABEL_FROM_A.op_code, 0x80, 0xFF]
+    );
+
+    // the labels of a fixed label-only section are final in an object
+    let src = "(ld %a ('flag))
+               (def-section .hram :offset 0xFF80 :label-only true)
+               (section .hram) ('flag db)";
+    let new_state = || {
+        let mut state = test_state_with_section_offset(Address(0x00));
+        state.relocatable = true;
+        state
+    };
+    let (state, label_refs) = assemble_passes(parse_from_string(src)?, new_state, assemble_forms)?;
+    let obj = state_to_object(&state, label_refs)?;
+    assert_eq!(
+        obj.sections[0].data,
+        [sm83::INSTR_LDH_TO_A_FROM_IMMEDIATE.op_code, 0x00]
+    );
+
+    let src = "(ldh ('lbl) %a) ('lbl nop)";
+    let mut state = test_state();
+    let err = assemble_in_state(parse_from_string(src)?, &mut state)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "<string>:1:1: ldh: address 0x4002 not in high page 0xFF00-0xFFFF\n  (ldh ('lbl) %a) ('lbl nop)\n  ^"
+    );
+
+    // a label of another object is not known, the ld is patched by the linker
+    let mut state = test_state_with_section_offset(Address(0x00));
+    let label_refs = assemble_forms(parse_from_string("(ld %a ('hram))")?, &mut state)?;
+    let obj = state_to_object(&state, label_refs)?;
+    assert_eq!(
+        obj.sections[0].data,
+        [sm83::INSTR_LD_TO_A_FROM_DEREF_LABEL.op_code, 0x00, 0x00]
+    );
+    assert_eq!(
+        obj.patches[0].patch,
+        Patch::Absolute16(SExp::Symbol(Symbol::Label(Label::from_str("hram"))))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_error_location() -> Result<(), Error> {
+    let cases = [
+        (
+            "(nop)\n  (ld %a 256)",
+            "<string>:2:3: ld: immediate value 256 out of range -128..255 in (ld %a 256)\n    (ld %a 256)\n    ^",
+        ),
+        // resolved after all forms are assembled
+        (
+            "(nop)\n(jp 'undefined)",
+            "<string>:2:1: undefined label: 'undefined\n  (jp 'undefined)\n  ^",
+        ),
+        // located in the included file
+        (
+            "(nop)\n(include \"testdata/asm/include_error\")",
+            "testdata/asm/include_error.asm:3:3: ld: illegal arguments: (ld %a %sp)",
+        ),
+        (
+            "(include \"testdata/asm/missing\")",
+            "<string>:1:1: include: testdata/asm/missing.asm: No such file or directory",
+        ),
+    ];
+
+    for (src, err) in cases {
+        let mut state = test_state();
+        let got_err = assemble_in_state(parse_from_string(src)?, &mut state)
+            .unwrap_err()
+            .to_string();
+        assert!(got_err.starts_with(err), "src={:?}, err={}", src, got_err);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_error_kind() -> Result<(), Error> {
+    let cases = [
+        ("(jr 'undefined)", ErrorKind::UndefinedLabel),
+        ("(ld %a +undefined+)", ErrorKind::UndefinedConstant),
+        ("('lbl nop) ('lbl nop)", ErrorKind::DuplicateLabel),
+        ("(ld %a 256)", ErrorKind::Range),
+        ("(jr 0x4100)", ErrorKind::Range),
+        ("(ld %a %sp)", ErrorKind::Syntax),
+        ("(include \"testdata/asm/missing\")", ErrorKind::Io),
+        // the error is in the included file
+        (
+            "(include \"testdata/asm/include_error\")",
+            ErrorKind::Syntax,
+        ),
+    ];
+
+    for (src, kind) in cases {
+        let mut state = test_state();
+        let err = assemble_in_state(parse_from_string(src)?, &mut state).unwrap_err();
+        assert_eq!(err.kind, kind, "src={:?}", src);
+        assert!(err.span.is_some(), "src={:?}", src);
+    }
+
+    let err = parse_from_string("(db 0xG)").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Parse);
+
+    let mut state = test_state();
+    let src = "(nop)\n(include \"testdata/asm/include_error\")";
+    let err = assemble_in_state(parse_from_string(src)?, &mut state).unwrap_err();
+    assert_eq!(err.notes, vec!["included from <string>:2:1".to_string()]);
+    Ok(())
+}
+
+#[test]
+fn test_incbin() -> Result<(), Error> {
+    let mut fs = MemoryFileSystem::new();
+    fs.insert_bytes("gfx/font.2bpp", [1, 2, 3, 4, 5]);
+    let sections = "(def-section .rom :offset 0 :length 12) (section .rom)\n";
+    let src = format!(
+        "{}(def-constant +glyph+ 2)
+         (incbin \"gfx/font.2bpp\")
+         ('glyph incbin \"gfx/font.2bpp\" :offset +glyph+ :length 2)
+         (incbin \"gfx/font.2bpp\" :offset 4)
+         (incbin \"gfx/font.2bpp\" :offset 5)
+         ('end jp 'glyph)",
+        sections
+    );
+    let assembly = assemble_source("main.asm", &src, &fs, &Environment::default())?;
+    assert_eq!(
+        assembly.image,
+        vec![1, 2, 3, 4, 5, 3, 4, 5, sm83::INSTR_JP.op_code, 5, 0, 0]
+    );
+    assert_eq!(assembly.labels.get("end"), Some(&8));
+
+    let cases = [
+        ("(incbin)", "incbin: file path required", ErrorKind::Syntax),
+        ("(incbin gfx)", "incbin: string expected", ErrorKind::Syntax),
+        (
+            "(incbin \"gfx/font.2bpp\" :offset -1)",
+            "incbin: offset must be positive",
+            ErrorKind::Range,
+        ),
+        (
+            "(incbin \"gfx/font.2bpp\" :length -1)",
+            "incbin: length must be positive",
+            ErrorKind::Range,
+        ),
+        (
+            "(incbin \"gfx/font.2bpp\" :offset 6)",
+            "incbin: offset 6 beyond the end of gfx/font.2bpp (5 bytes)",
+            ErrorKind::Range,
+        ),
+        (
+            "(incbin \"gfx/font.2bpp\" :offset 2 :length 4)",
+            "incbin: 4 bytes from offset 2 beyond the end of gfx/font.2bpp (5 bytes)",
+            ErrorKind::Range,
+        ),
+        (
+            "(repeat 3 (incbin \"gfx/font.2bpp\"))",
+            "section .rom (0x0000, length 0xC) overflows: 5 bytes at 0x000A",
+            ErrorKind::Range,
+        ),
+    ];
+    for (form, err, kind) in cases {
+        let src = format!("{}{}", sections, form);
+        let got_err = assemble_source("main.asm", &src, &fs, &Environment::default()).unwrap_err();
+        assert_eq!(got_err.message, err, "src={:?}", src);
+        assert_eq!(got_err.kind, kind, "src={:?}", src);
+    }
+
+    let err = assemble_source(
+        "main.asm",
+        &format!("{}(incbin \"gfx/missing.2bpp\")", sections),
+        &fs,
+        &Environment::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Io);
+
+    // the file is searched relative to the including file, then in the include paths
+    fs.insert(
+        "src/main.asm",
+        "(def-section .rom :offset 0 :length 4) (include \"gfx/tiles\")",
+    );
+    fs.insert(
+        "src/gfx/tiles.asm",
+        "(section .rom) (incbin \"tiles.2bpp\") (incbin \"font.2bpp\" :length 2)",
+    );
+    fs.insert_bytes("src/gfx/tiles.2bpp", [7, 8]);
+    fs.insert_bytes("res/font.2bpp", [9, 10]);
+    let env = Environment {
+        include_paths: vec![PathBuf::from("res")],
+        ..Default::default()
+    };
+    let assembly = assemble_source(
+        "src/main.asm",
+        &fs.read_to_string("src/main.asm").unwrap(),
+        &fs,
+        &env,
+    )?;
+    assert_eq!(assembly.image, vec![7, 8, 9, 10]);
+
+    let err = assemble_source(
+        "src/main.asm",
+        &format!("{}(incbin \"tiles.2bpp\")", sections),
+        &fs,
+        &env,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Io);
+    assert_eq!(err.notes, ["searched src/tiles.2bpp, res/tiles.2bpp"]);
+    Ok(())
+}
+
+#[test]
+fn test_include_search() -> Result<(), Error> {
+    let mut fs = MemoryFileSystem::new();
+    fs.insert(
+        "src/main.asm",
+        "(include \"lib/util\") (include \"hw\") (include \"../shared/data\")",
+    );
+    fs.insert(
+        "src/lib/util.asm",
+        "(include-once \"defs\") (include-once \"defs\") (section .rom) ('util nop)",
+    );
+    fs.insert("src/lib/defs.asm", "(def-section .rom :offset 0 :length 4)");
+    fs.insert("inc/hw.asm", "('hw nop)");
+    fs.insert("shared/data.asm", "('data db 1)");
+    let env = Environment {
+        include_paths: vec![PathBuf::from("inc")],
+        ..Default::default()
+    };
+    let assembly = assemble_source(
+        "src/main.asm",
+        &fs.read_to_string("src/main.asm").unwrap(),
+        &fs,
+        &env,
+    )?;
+    assert_eq!(
+        assembly.image,
+        vec![sm83::INSTR_NOP.op_code, sm83::INSTR_NOP.op_code, 1, 0]
+    );
+
+    // the stdlib is embedded unless a directory is given
+    let src = "(include :std \"gb/dma\")";
+    let assembly = assemble_source("main.asm", src, &fs, &Environment::default())?;
+    assert_eq!(assembly.labels.get("hw-lcdc"), Some(&0xFF40));
+
+    fs.insert("mystd/gb/dma.asm", "(label 'dma)");
+    let env = Environment {
+        stdlib: Some(PathBuf::from("mystd")),
+        ..Default::default()
+    };
+    let assembly = assemble_source("main.asm", src, &fs, &env)?;
+    assert_eq!(assembly.labels.keys().collect::<Vec<_>>(), ["dma"]);
+
+    fs.insert("a.asm", "(include \"b\")");
+    fs.insert("b.asm", "(def-constant +b+ 1)\n(include \"a\")");
+    let err = assemble_source("main.asm", "(include \"a\")", &fs, &env).unwrap_err();
+    assert_eq!(
+        err.message,
+        "include: include cycle main.asm -> a.asm -> b.asm -> a.asm"
+    );
+    assert_eq!(err.kind, ErrorKind::Range);
+    assert_eq!(
+        err.span.map(|span| span.to_string()),
+        Some("b.asm:2:1".to_string())
+    );
+
+    let env = Environment {
+        include_paths: vec![PathBuf::from("inc"), PathBuf::from("/usr/psy")],
+        ..Default::default()
+    };
+    let err = assemble_source("src/main.asm", "(include \"missing\")", &fs, &env).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Io);
+    assert_eq!(
+        err.message,
+        "include: src/missing.asm: No such file or directory"
+    );
+    assert_eq!(
+        err.notes,
+        ["searched src/missing.asm, inc/missing.asm, /usr/psy/missing.asm"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_normalize_path() {
+    let cases = [
+        ("a/b.asm", "a/b.asm"),
+        ("./a/./b.asm", "a/b.asm"),
+        ("a/../b.asm", "b.asm"),
+        ("../a/../../b.asm", "../../b.asm"),
+        ("/a/../../b.asm", "/b.asm"),
+    ];
+    for (path, want) in cases {
+        assert_eq!(normalize_path(Path::new(path)), want, "path={}", path);
+    }
+}
+
+#[test]
+fn test_assemble_source() -> Result<(), Error> {
+    let mut fs = MemoryFileSystem::new();
+    fs.insert(
+        "lib/sections.asm",
+        "(def-section .wram :offset 0xC000 :length 0x10 :label-only true)\n(def-section .rom :offset 2 :length 4)",
+    );
+    let src = "(include \"lib/sections\")\n(section .wram) ('counter db)\n(section .rom) ('start nop) (jr 'start)";
+
+    let assembly = assemble_source("main.asm", src, &fs, &Environment::default())?;
+    assert_eq!(
+        assembly.image,
+        vec![
+            0,
+            0,
+            sm83::INSTR_NOP.op_code,
+            sm83::INSTR_JR.op_code,
+            0xFD,
+            0
+        ]
+    );
+    assert_eq!(
+        assembly.labels.into_iter().collect::<Vec<_>>(),
+        vec![("counter".to_string(), 0xC000), ("start".to_string(), 2)]
+    );
+    assert_eq!(
+        assembly.sections,
+        vec![
+            SectionLayout {
+                name: "rom".to_string(),
+                offset: 2,
+                length: Some(4),
+                label_only: false,
+                used: 3,
+            },
+            SectionLayout {
+                name: "wram".to_string(),
+                offset: 0xC000,
+                length: Some(0x10),
+                label_only: true,
+                used: 0,
+            },
+        ]
+    );
+
+    // growing and floating sections in the image
+    let src = "(def-region rom :offset 0 :length 0x10)
+               (def-section .code :offset 1) (def-section .data :region rom :align 4)
+               (section .data) (db 7 8) (section .code) (db 1 2)";
+    let assembly = assemble_source("main.asm", src, &fs, &Environment::default())?;
+    assert_eq!(assembly.image, vec![0, 1, 2, 0, 7, 8]);
+
+    // the gaps with the fill byte
+    let src = "(def-section .a :offset 1 :length 3) (def-section .b :offset 5)
+               (section .a) (db 1) (section .b) (db 2 0)";
+    let env = Environment {
+        fill: 0xFF,
+        ..Environment::default()
+    };
+    let assembly = assemble_source("main.asm", src, &fs, &env)?;
+    assert_eq!(assembly.image, vec![0xFF, 1, 0xFF, 0xFF, 0xFF, 2, 0]);
+
+    // included files are only read from the given file system
+    let err = assemble_source(
+        "main.asm",
+        "(include \"testdata/asm/min\")",
+        &fs,
+        &Environment::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Io);
+    assert_eq!(
+        err.span.map(|span| span.to_string()),
+        Some("main.asm:1:1".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_assemble_object() -> Result<(), Error> {
+    let mut state = test_state_with_section_offset(Address(0x10));
+    let tl = parse_from_string("(nop) (label 'loop) (jr 'loop) (jp 'extern)")?;
+    let label_refs = assemble_forms(tl, &mut state)?;
+    let obj = state_to_object(&state, label_refs)?;
+
+    let sec = obj.lookup_section(TEST_SEC_NAME).expect("test sec");
+    assert_eq!(sec.offset, Address(0x10));
+    assert_eq!(
+        sec.data,
+        vec![
+            sm83::INSTR_NOP.op_code,
+            sm83::INSTR_JR.op_code,
+            0,
+            sm83::INSTR_JP.op_code,
+            0,
+            0
+        ]
+    );
+
+    assert_eq!(
+        obj.symbols,
+        vec![ObjectSymbol {
+            label: Label::from_str("loop"),
+            section: Some(TEST_SEC_NAME.to_string()),
+            offset: 1,
+        }]
+    );
+
+    assert_eq!(obj.patches.len(), 2);
+    assert_eq!(obj.patches[0].patch_index, 2);
+    assert_eq!(
+        obj.patches[0].patch,
+        Patch::Relative8(3, SExp::Symbol(Symbol::Label(Label::from_str("loop"))))
+    );
+    assert_eq!(obj.patches[1].patch_index, 4);
+    assert_eq!(
+        obj.patches[1].patch,
+        Patch::Absolute16(SExp::Symbol(Symbol::Label(Label::from_str("extern"))))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_assemble_object_floating_sections() -> Result<(), Error> {
+    // the floating sections are written unplaced, the linker places them
+    let region = "(def-region rom :offset 0x100 :length 0x20)";
+    let a = format!(
+        "{}(def-section .header :offset 0x0) (def-section .a :region rom)
+         (section .header) (jp 'b) (section .a) ('a jr 'a)",
+        region
+    );
+    let b = format!(
+        "{}(def-section .b :region rom :align 4) (section .b) ('b call 'a)",
+        region
+    );
+    let mut objects = Vec::new();
+    for src in [a, b] {
+        let mut state = State::new(&OsFileSystem);
+        let label_refs = assemble_forms(parse_from_string(&src)?, &mut state)?;
+        objects.push(state_to_object(&state, label_refs)?);
+    }
+
+    let sec = objects[1].lookup_section("b").expect("section b");
+    assert_eq!(sec.offset, Address(0));
+    assert_eq!(
+        sec.floating,
+        Some(Floating {
+            region: "rom".to_string(),
+            align: 4,
+            bank: None,
+        })
+    );
+    assert_eq!(
+        objects[1].regions,
+        [Region {
+            name: "rom".to_string(),
+            offset: 0x100,
+            length: 0x20,
+            bank: None,
+        }]
+    );
+
+    let rom = crate::link::gb::link(&objects, 0)?;
+    assert_eq!(rom.len(), 0x107);
+    assert_eq!(rom[..3], [sm83::INSTR_JP.op_code, 0x04, 0x01]);
+    assert_eq!(
+        rom[0x100..],
+        [
+            sm83::INSTR_JR.op_code,
+            0xFE,
+            0,
+            0,
+            sm83::INSTR_CALL.op_code,
+            0x00,
+            0x01
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_resolve_label_fails() -> Result<(), Error> {
+    let test_label = Label::from_string("lbl".to_string());
+    let cases = [
+        (
+            LabelRef {
+                reference: Ref::Relative(
+                    TEST_SEC_ADDR,
+                    SExp::Symbol(Symbol::Label(test_label.clone())),
+                    check_jr_jump,
+                ),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1,
+                span: Span::default(),
+            },
+            Address(TEST_SEC_ADDR.0 - 129),
+            "jr: max -128 jumps back, was -129",
+        ),
+        (
+            LabelRef {
+                reference: Ref::Relative(
+                    TEST_SEC_ADDR,
+                    SExp::Symbol(Symbol::Label(test_label.clone())),
+                    check_jr_jump,
+                ),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1,
+                span: Span::default(),
+            },
+            Address(TEST_SEC_ADDR.0 + 130),
+            "jr: max 127 jumps forward, was 130",
+        ),
+    ];
+
+    for (label_ref, label_address, err) in cases {
+        let mut state = test_state();
+        state
+            .label_addresses
+            .insert(test_label.clone(), label_address);
+
+        let r = resolve_label(&label_ref, &mut state);
+
+        assert!(r.is_err(), "expected error '{}'", err);
+        assert_eq!(r.unwrap_err().to_string(), err);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_resolve_label_ok() -> Result<(), Error> {
+    let test_label = Label::from_string("lbl".to_string());
+
+    let cases = [
+        (
+            "jump maximum back",
+            LabelRef {
+                reference: Ref::Relative(
+                    TEST_SEC_ADDR,
+                    SExp::Symbol(Symbol::Label(test_label.clone())),
+                    check_jr_jump,
+                ),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1,
+                span: Span::default(),
+            },
+            Address(TEST_SEC_ADDR.0 - 126),
+            (-126i32) as u8,
+            0x00,
+        ),
+        (
+            "jump maximum forward",
+            LabelRef {
+                reference: Ref::Relative(
+                    TEST_SEC_ADDR,
+                    SExp::Symbol(Symbol::Label(test_label.clone())),
+                    check_jr_jump,
+                ),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1,
+                span: Span::default(),
+            },
+            Address(TEST_SEC_ADDR.0 + 127),
+            127,
+            0x00,
+        ),
+        (
+            "absolute jump",
+            LabelRef {
+                reference: Ref::from_label(test_label.clone()),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1,
+                span: Span::default(),
+            },
+            Address(0x5001),
+            0x01,
+            0x50,
+        ),
+        (
+            // check ref, Ref::Expression evaluation is tested in interpreter_test.rs
+            "arith address 1",
+            LabelRef {
+                reference: Ref::from_label(test_label.clone()),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1,
+                span: Span::default(),
+            },
+            Address(0x6754),
+            0x54,
+            0x67,
+        ),
+    ];
+    for (test, label_ref, label_address, mem_1, mem_2) in cases {
+        let patch_ix = label_ref.patch_index;
+        let label_refs = vec![label_ref];
+
+        let mut state = test_state();
+        state
+            .label_addresses
+            .insert(test_label.clone(), label_address);
+
+        resolve_labels(label_refs, &mut state)?;
+
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+        assert_eq!(
+            sec.memory.mem[patch_ix], mem_1,
+            "mem_1 was {:x}, expected {:x}, test: {}",
+            sec.memory.mem[patch_ix], mem_1, test,
+        );
+        assert_eq!(
+            sec.memory.mem[patch_ix + 1],
+            mem_2,
+            "mem_2 was {:x}, expected {:x}, test: {}",
+            sec.memory.mem[patch_ix + 1],
+            mem_2,
+            test,
+        );
+    }
+    Ok(())
+}
+
+// helper
+
+static TEST_SEC_NAME: &str = "test-section";
+static TEST_SEC_ADDR: Address = Address(0x4000);
+
+fn assert_eq_label_ref(got: Option<LabelRef>, expect: Option<LabelRef>) {
+    assert!(got.is_some() == expect.is_some());
+
+    if let Some(expect_ref) = expect {
+        let got_ref = got.expect("got is some");
+        assert_eq_ref(&got_ref.reference, &expect_ref.reference);
+        assert_eq!(got_ref.sec_name, expect_ref.sec_name, "sec_name");
+        assert_eq!(got_ref.patch_index, expect_ref.patch_index, "patch_index");
+    }
+}
+
+#[test]
+fn test_call_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(call)",
+            "call: illegal arguments: (call), valid forms: (call #nz|#z|#nc|#c a16) (call a16)",
+        ),
+        (
+            "(call #c 'foo 'bar)",
+            "call: illegal arguments: (call #c 'foo 'bar), valid forms: (call #nz|#z|#nc|#c a16) (call a16)",
+        ),
+    ];
+
+    for (exp, err) in cases {
+        let mut state = test_state();
+        let mut tl = parse_from_string(exp)?;
+
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
+
+        assert!(
+            r.is_err(),
+            "expected error '{}' on expression = {:?}",
+            err,
+            exp
+        );
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_call_ok() -> Result<(), Error> {
+    let cases = [
+        (
+            "(call 'fn)",
+            Some(LabelRef {
+                reference: Ref::from_label(Label::from_string("fn".to_string())),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_CALL.op_code,
+        ),
+        (
             "(call #c 'fn)",
             Some(LabelRef {
                 reference: Ref::from_label(Label::from_string("fn".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_CALL_IF_C.op_code,
         ),
@@ -1011,20 +2770,41 @@ fn test_call_ok() -> Result<(), String> {
                 reference: Ref::from_label(Label::from_string("fn".to_string())),
                 sec_name: TEST_SEC_NAME.to_string(),
                 patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
             }),
             sm83::INSTR_CALL_IF_NZ.op_code,
         ),
+        (
+            "(call #z 'fn)",
+            Some(LabelRef {
+                reference: Ref::from_label(Label::from_string("fn".to_string())),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_CALL_IF_Z.op_code,
+        ),
+        (
+            "(call #nc 'fn)",
+            Some(LabelRef {
+                reference: Ref::from_label(Label::from_string("fn".to_string())),
+                sec_name: TEST_SEC_NAME.to_string(),
+                patch_index: 1, // address bytes start at byte 1
+                span: Span::default(),
+            }),
+            sm83::INSTR_CALL_IF_NC.op_code,
+        ),
     ];
 
     for (exp, expect_label_ref, op_code) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let got_label_ref = call(&mut state, tl.forms.pop().unwrap())?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
 
         assert_eq_label_ref(got_label_ref, expect_label_ref);
 
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(
             sec.memory.mem[0], op_code,
             "op_code was {:x}, expected {:x}",
@@ -1045,17 +2825,23 @@ fn test_call_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_ret_ok() -> Result<(), String> {
-    let cases = [("(ret)", sm83::INSTR_RET.op_code)];
+fn test_ret_ok() -> Result<(), Error> {
+    let cases = [
+        ("(ret)", sm83::INSTR_RET.op_code),
+        ("(ret #nz)", sm83::INSTR_RET_IF_NZ.op_code),
+        ("(ret #z)", sm83::INSTR_RET_IF_Z.op_code),
+        ("(ret #nc)", sm83::INSTR_RET_IF_NC.op_code),
+        ("(ret #c)", sm83::INSTR_RET_IF_C.op_code),
+    ];
 
     for (exp, op_code) in cases {
         let mut state = test_state();
         let mut tl = parse_from_string(exp)?;
 
-        let got_label_ref = ret(&mut state, tl.forms.pop().unwrap())?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
 
         assert!(got_label_ref.is_none());
-        let sec = state.lookup_section(&TEST_SEC_NAME).expect("test sec");
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
         assert_eq!(
             sec.memory.mem[0], op_code,
             "op_code was {:x}, expected {:?}, expression={:?}",
@@ -1067,11 +2853,132 @@ fn test_ret_ok() -> Result<(), String> {
 }
 
 #[test]
-fn test_tile_to_u16() -> Result<(), String> {
+fn test_unknown_flag_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(jp #p 'lbl)",
+            "jp: unknown flag #p, valid flags: #nz #z #nc #c",
+        ),
+        (
+            "(jr #nz #z 'lbl)",
+            "jr: illegal arguments: (jr #nz #z 'lbl), valid forms: (jr e8) (jr #nz|#z|#nc|#c e8)",
+        ),
+        (
+            "(call #m 'fn)",
+            "call: unknown flag #m, valid flags: #nz #z #nc #c",
+        ),
+        (
+            "(ret #cy)",
+            "ret: unknown flag #cy, valid flags: #nz #z #nc #c",
+        ),
+        (
+            "(ret 'lbl)",
+            "ret: illegal arguments: (ret 'lbl), valid forms: (ret #nz|#z|#nc|#c) (ret)",
+        ),
+    ];
+
+    for (exp, err) in cases {
+        let mut state = test_state();
+        let mut tl = parse_from_string(exp)?;
+
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
+
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stack_and_cpu_control_ok() -> Result<(), Error> {
+    let cases = [
+        ("(push %bc)", vec![sm83::INSTR_PUSH_BC.op_code]),
+        ("(push %de)", vec![sm83::INSTR_PUSH_DE.op_code]),
+        ("(push %hl)", vec![sm83::INSTR_PUSH_HL.op_code]),
+        ("(push %af)", vec![sm83::INSTR_PUSH_AF.op_code]),
+        ("(pop %bc)", vec![sm83::INSTR_POP_BC.op_code]),
+        ("(pop %de)", vec![sm83::INSTR_POP_DE.op_code]),
+        ("(pop %hl)", vec![sm83::INSTR_POP_HL.op_code]),
+        ("(pop %af)", vec![sm83::INSTR_POP_AF.op_code]),
+        ("(rst 0x00)", vec![sm83::INSTR_RST_00.op_code]),
+        ("(rst 0x28)", vec![sm83::INSTR_RST_28.op_code]),
+        ("(rst 56)", vec![sm83::INSTR_RST_38.op_code]),
+        ("(reti)", vec![sm83::INSTR_RETI.op_code]),
+        ("(halt)", vec![sm83::INSTR_HALT.op_code]),
+        ("(stop)", vec![sm83::INSTR_STOP.op_code, 0x00]),
+        ("(di)", vec![sm83::INSTR_DI.op_code]),
+        ("(ei)", vec![sm83::INSTR_EI.op_code]),
+        ("(daa)", vec![sm83::INSTR_DAA.op_code]),
+        ("(scf)", vec![sm83::INSTR_SCF.op_code]),
+        ("(ccf)", vec![sm83::INSTR_CCF.op_code]),
+        ("(cpl)", vec![sm83::INSTR_CPL.op_code]),
+        ("(rlca)", vec![sm83::INSTR_RLCA.op_code]),
+        ("(rrca)", vec![sm83::INSTR_RRCA.op_code]),
+        ("(rla)", vec![sm83::INSTR_RLA.op_code]),
+        ("(rra)", vec![sm83::INSTR_RRA.op_code]),
+    ];
+    for (exp, bytes) in cases {
+        let mut state = test_state();
+        let mut tl = parse_from_string(exp)?;
+        let got_label_ref = instruction(&mut state, tl.forms.pop().unwrap())?;
+        assert!(got_label_ref.is_none());
+
+        let sec = state.lookup_section(TEST_SEC_NAME).expect("test sec");
+        assert_eq!(
+            sec.memory.mem[0..bytes.len()],
+            bytes,
+            "expression={:?}",
+            exp
+        );
+        assert_eq!(
+            state.current_section_address.0,
+            TEST_SEC_ADDR.0 + bytes.len() as u64
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_stack_and_cpu_control_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(push %sp)",
+            "push: illegal arguments: (push %sp), valid forms: (push %bc|%de|%hl|%af)",
+        ),
+        (
+            "(pop)",
+            "pop: illegal arguments: (pop), valid forms: (pop %bc|%de|%hl|%af)",
+        ),
+        (
+            "(rst 0x40)",
+            "rst: illegal vector 0x40, valid vectors: 0x00 0x08 0x10 0x18 0x20 0x28 0x30 0x38",
+        ),
+        (
+            "(rst)",
+            "rst: illegal arguments: (rst), valid forms: (rst 0x00|0x08|0x10|0x18|0x20|0x28|0x30|0x38)",
+        ),
+        (
+            "(di %a)",
+            "di: illegal arguments: (di %a), valid forms: (di)",
+        ),
+    ];
+
+    for (exp, err) in cases {
+        let mut state = test_state();
+        let mut tl = parse_from_string(exp)?;
+
+        let r = instruction(&mut state, tl.forms.pop().unwrap());
+
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_tile_to_u16() -> Result<(), Error> {
     let cases = [("01012323", 0x0F55)];
 
     for (tile_def, expected_val) in cases {
-        let val = tile_to_u16(&tile_def)?;
+        let val = tile_to_u16(tile_def).map_err(Error::syntax)?;
         assert_eq!(val, expected_val);
     }
 
@@ -1084,28 +2991,45 @@ fn assert_eq_ref(got_ref: &Ref, expect_ref: &Ref) {
     match got_ref {
         Ref::Expression(got_exp) => match expect_ref {
             Ref::Expression(expect_exp) => assert_eq!(got_exp, expect_exp, "Ref::Expression"),
-            _ => assert!(false, "got Ref::Expression, expected {:?}", expect_ref),
+            _ => panic!("got Ref::Expression, expected {:?}", expect_ref),
         },
-        Ref::Relative(got_address, got_label, got_check) => match expect_ref {
-            Ref::Relative(expect_address, expect_label, expect_check) => {
+        Ref::Relative(got_address, got_exp, got_check) => match expect_ref {
+            Ref::Relative(expect_address, expect_exp, expect_check) => {
                 assert_eq!(got_address, expect_address, "relative address");
-                assert_eq!(got_label, expect_label, "relative label");
+                assert_eq!(got_exp, expect_exp, "relative target");
                 assert!(
                     std::ptr::fn_addr_eq(*got_check, *expect_check),
                     "relative check fn"
                 );
             }
-            _ => assert!(false, "got Ref::Relative, expected: {:?}", expect_ref),
+            _ => panic!("got Ref::Relative, expected: {:?}", expect_ref),
+        },
+        Ref::HighPage(got_exp) => match expect_ref {
+            Ref::HighPage(expect_exp) => assert_eq!(got_exp, expect_exp, "Ref::HighPage"),
+            _ => panic!("got Ref::HighPage, expected {:?}", expect_ref),
+        },
+        Ref::Immediate8(got_exp) => match expect_ref {
+            Ref::Immediate8(expect_exp) => assert_eq!(got_exp, expect_exp, "Ref::Immediate8"),
+            _ => panic!("got Ref::Immediate8, expected {:?}", expect_ref),
+        },
+        Ref::Immediate16(got_exp) => match expect_ref {
+            Ref::Immediate16(expect_exp) => assert_eq!(got_exp, expect_exp, "Ref::Immediate16"),
+            _ => panic!("got Ref::Immediate16, expected {:?}", expect_ref),
         },
     }
 }
 
-fn test_state() -> State {
+fn must_parse_exp(str: &str) -> SExp {
+    let mut tl = parse_from_string(&format!("(x {})", str)).expect("parse");
+    tl.forms[0].exps.pop().expect("exp")
+}
+
+fn test_state() -> State<'static> {
     test_state_with_section_offset(TEST_SEC_ADDR)
 }
 
-fn test_state_with_section_offset(offset_address: Address) -> State {
-    let mut state = State::new();
+fn test_state_with_section_offset(offset_address: Address) -> State<'static> {
+    let mut state = State::new(&OsFileSystem);
     state.sections.push(Section {
         name: TEST_SEC_NAME.to_string(),
         length: Some(100),
@@ -1115,6 +3039,10 @@ fn test_state_with_section_offset(offset_address: Address) -> State {
             mem: vec![0; 100],
             mem_ptr: 0,
         },
+        reserved: 0,
+        floating: None,
+        data_align: 1,
+        pinned: false,
     });
     state.current_section_name = Some(TEST_SEC_NAME.to_string());
     state.current_section_address = offset_address;
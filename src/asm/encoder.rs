@@ -0,0 +1,595 @@
+#[cfg(test)]
+#[path = "./encoder_test.rs"]
+mod encoder_test;
+
+use crate::arch::sm83::{self, INSTRUCTIONS, PREFIX_INSTRUCTIONS};
+use crate::asm::assembler::{
+    check_high_page_address, check_immediate_range, check_jr_jump, is_const_expression_op,
+};
+use crate::asm::interpreter::{contains_label, eval, eval_const, fold_constants};
+use crate::asm::parser::{Address, Form, Label, SExp, Symbol, parse_from_string, parse_number};
+use crate::error::{Error, ErrorKind};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// The ALU operations. The %a destination is implicit for them and may
+/// be left out, (cp 144) is the same as (cp %a 144).
+const ALU_OPS: [&str; 8] = ["add", "adc", "sub", "sbc", "and", "xor", "or", "cp"];
+
+/// The kind of an instruction argument, named like the placeholders
+/// in the sm83 mnemonics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArgKind {
+    N8,
+    N16,
+    E8,
+    A8,
+    A16,
+}
+
+impl ArgKind {
+    fn from_placeholder(placeholder: &str) -> Option<ArgKind> {
+        match placeholder {
+            "n8" => Some(ArgKind::N8),
+            "n16" => Some(ArgKind::N16),
+            "e8" => Some(ArgKind::E8),
+            "a8" => Some(ArgKind::A8),
+            "a16" => Some(ArgKind::A16),
+            _ => None,
+        }
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            ArgKind::N8 => "n8",
+            ArgKind::N16 => "n16",
+            ArgKind::E8 => "e8",
+            ArgKind::A8 => "a8",
+            ArgKind::A16 => "a16",
+        }
+    }
+}
+
+/// An operand in the mnemonic of an instruction.
+#[derive(Debug, PartialEq)]
+enum Pattern {
+    Reg(String),
+    DerefReg(String, Option<String>),
+    Flag(String),
+    Literal(i64),
+    Arg(ArgKind),
+    DerefArg(ArgKind),
+}
+
+#[derive(Debug)]
+struct InstrPattern {
+    op: String,
+    operands: Vec<Pattern>,
+    mnemonic: &'static str,
+    op_code: u8,
+    len: usize,
+    prefixed: bool,
+}
+
+/// The operand patterns of all instructions, derived from the
+/// mnemonics of the base and the prefix instruction tables.
+static PATTERNS: LazyLock<Vec<InstrPattern>> = LazyLock::new(|| {
+    let mut patterns = Vec::new();
+    for instr in INSTRUCTIONS {
+        if std::ptr::eq(instr, &sm83::INSTR_INVALID) || std::ptr::eq(instr, &sm83::INSTR_PREFIX) {
+            continue;
+        }
+        patterns.push(instr_pattern(
+            instr.mnemonic,
+            instr.op_code,
+            instr.len(),
+            false,
+        ));
+    }
+    for instr in PREFIX_INSTRUCTIONS {
+        patterns.push(instr_pattern(
+            instr.mnemonic,
+            instr.op_code,
+            instr.len(),
+            true,
+        ));
+    }
+    patterns
+});
+
+fn instr_pattern(mnemonic: &'static str, op_code: u8, len: usize, prefixed: bool) -> InstrPattern {
+    let mut tl = parse_from_string(&format!("({})", mnemonic)).expect("mnemonic parses");
+    let form = tl.forms.pop().expect("mnemonic form");
+    let operands = form
+        .exps
+        .iter()
+        .map(|exp| match exp {
+            SExp::Symbol(Symbol::Reg(reg)) => Pattern::Reg(reg.clone()),
+            SExp::Symbol(Symbol::Flag(flag)) => Pattern::Flag(flag.clone()),
+            SExp::Symbol(Symbol::Sym(placeholder)) => {
+                Pattern::Arg(ArgKind::from_placeholder(placeholder).expect("placeholder"))
+            }
+            SExp::Immediate(v) => Pattern::Literal(*v),
+            SExp::Form(deref) => match (&deref.op, deref.exps.first()) {
+                (Symbol::Reg(reg), None) => Pattern::DerefReg(reg.clone(), None),
+                (Symbol::Reg(reg), Some(SExp::Symbol(Symbol::Sym(deref_mod)))) => {
+                    Pattern::DerefReg(reg.clone(), Some(deref_mod.clone()))
+                }
+                (Symbol::Sym(placeholder), None) => {
+                    Pattern::DerefArg(ArgKind::from_placeholder(placeholder).expect("placeholder"))
+                }
+                _ => panic!("illegal deref in mnemonic: {}", mnemonic),
+            },
+            _ => panic!("illegal operand in mnemonic: {}", mnemonic),
+        })
+        .collect();
+    InstrPattern {
+        op: form.op.to_string(),
+        operands,
+        mnemonic,
+        op_code,
+        len,
+        prefixed,
+    }
+}
+
+/// A value argument of an instruction.
+#[derive(Debug)]
+enum Value {
+    Known(i64),
+    Label(Label),
+    Expression(SExp),
+}
+
+/// An operand of the instruction form to be encoded.
+#[derive(Debug)]
+enum Operand<'a> {
+    Reg(&'a str),
+    DerefReg(&'a str, Option<&'a str>),
+    Flag(&'a str),
+    Value(Value),
+    Deref(Value),
+}
+
+/// A reference in the encoded instruction that can only be written
+/// once the label addresses are known.
+#[derive(Debug, PartialEq)]
+pub(crate) enum EncodedRef {
+    /// a 16-bit address expression
+    Absolute16(SExp),
+    /// a signed 8-bit distance to the target address expression,
+    /// relative to the address after the instruction
+    Relative8(SExp),
+    /// the low byte of an address expression in the high page
+    High8(SExp),
+    /// an 8-bit value expression, e.g. (lo 'lbl)
+    Immediate8(SExp),
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Encoded {
+    pub bytes: Vec<u8>,
+    /// the reference and its index into bytes
+    pub reference: Option<(usize, EncodedRef)>,
+}
+
+/// Whether the op is the name of a sm83 instruction.
+pub(crate) fn is_instruction(op: &str) -> bool {
+    PATTERNS.iter().any(|pattern| pattern.op == op)
+}
+
+/// Encodes the instruction form. The address is the address of the
+/// first byte of the instruction, None if the section is not placed
+/// yet (floating sections and objects). The distance of a jr to a known
+/// target is a reference then as well.
+///
+/// A ld from or to an address in the high page (0xFF00-0xFFFF) is
+/// encoded as the shorter ldh, if the address is known from the label
+/// addresses, otherwise the absolute ld is used, see high_page_variant.
+/// The assembler passes the final label addresses of its pass before.
+pub(crate) fn encode(
+    form: &Form,
+    address: Option<Address>,
+    const_values: &HashMap<String, i64>,
+    label_addresses: &HashMap<Label, Address>,
+) -> Result<Encoded, Error> {
+    let op = form.op.to_string();
+    let operands = form
+        .exps
+        .iter()
+        .map(|exp| operand(exp, const_values))
+        .collect::<Result<Vec<Operand>, Error>>()
+        .map_err(|e| e.prefixed(&op))?;
+
+    let candidates: Vec<&InstrPattern> = PATTERNS.iter().filter(|p| p.op == op).collect();
+    let (pattern, operand_patterns) = candidates
+        .iter()
+        .find_map(|pattern| {
+            if matches_all(&pattern.operands, &operands) {
+                return Some((pattern, &pattern.operands[..]));
+            }
+            if ALU_OPS.contains(&op.as_str())
+                && pattern.operands.first() == Some(&Pattern::Reg(sm83::REG_A.to_string()))
+                && matches_all(&pattern.operands[1..], &operands)
+            {
+                return Some((pattern, &pattern.operands[1..]));
+            }
+            None
+        })
+        .ok_or_else(|| match operands.as_slice() {
+            [Operand::Value(Value::Known(v))] if op == "rst" => {
+                format!(
+                    "rst: illegal vector 0x{:02X}, valid vectors: {}",
+                    v,
+                    rst_vectors(&candidates)
+                )
+            }
+            _ => format!(
+                "{}: illegal arguments: {}, valid forms: {}",
+                op,
+                form,
+                valid_forms(&candidates)
+            ),
+        })
+        .map_err(Error::syntax)?;
+
+    let (pattern, operand_patterns) =
+        match high_page_variant(pattern, &operands, const_values, label_addresses) {
+            Some(ldh_pattern) => (ldh_pattern, &ldh_pattern.operands[..]),
+            None => (*pattern, operand_patterns),
+        };
+
+    let mut bytes = Vec::with_capacity(pattern.len);
+    if pattern.prefixed {
+        bytes.push(sm83::INSTR_PREFIX.op_code);
+    }
+    bytes.push(pattern.op_code);
+
+    let mut reference = None;
+    for (operand_pattern, operand) in operand_patterns.iter().zip(operands) {
+        let (kind, value) = match (operand_pattern, operand) {
+            (Pattern::Arg(kind), Operand::Value(value)) => (kind, value),
+            (Pattern::DerefArg(kind), Operand::Deref(value)) => (kind, value),
+            _ => continue,
+        };
+        encode_arg(
+            form,
+            *kind,
+            value,
+            address,
+            pattern.len,
+            &mut bytes,
+            &mut reference,
+        )?;
+    }
+    // instructions with an unused argument byte (stop)
+    bytes.resize(pattern.len, 0);
+
+    Ok(Encoded { bytes, reference })
+}
+
+fn encode_arg(
+    form: &Form,
+    kind: ArgKind,
+    value: Value,
+    address: Option<Address>,
+    len: usize,
+    bytes: &mut Vec<u8>,
+    reference: &mut Option<(usize, EncodedRef)>,
+) -> Result<(), Error> {
+    let op = form.op.to_string();
+    match (kind, value) {
+        (ArgKind::N8, Value::Known(v)) => {
+            check_immediate_range(v, 8, form)?;
+            bytes.push(v as u8);
+        }
+        (ArgKind::N8, Value::Expression(exp)) => {
+            *reference = Some((bytes.len(), EncodedRef::Immediate8(exp)));
+            bytes.push(0);
+        }
+        (ArgKind::N16, Value::Known(v)) => {
+            check_immediate_range(v, 16, form)?;
+            bytes.extend_from_slice(&(v as u16).to_le_bytes());
+        }
+        (ArgKind::A16, Value::Known(v)) => {
+            if !(0..=0xFFFF).contains(&v) {
+                return Err(Error::new(
+                    ErrorKind::Range,
+                    format!(
+                        "{}: address {} out of range 0x0000-0xFFFF in {}",
+                        op, v, form
+                    ),
+                ));
+            }
+            bytes.extend_from_slice(&(v as u16).to_le_bytes());
+        }
+        (ArgKind::N16 | ArgKind::A16, Value::Label(lbl)) => {
+            *reference = Some((
+                bytes.len(),
+                EncodedRef::Absolute16(SExp::Symbol(Symbol::Label(lbl))),
+            ));
+            bytes.extend_from_slice(&[0, 0]);
+        }
+        (ArgKind::N16 | ArgKind::A16, Value::Expression(exp)) => {
+            *reference = Some((bytes.len(), EncodedRef::Absolute16(exp)));
+            bytes.extend_from_slice(&[0, 0]);
+        }
+        // jr jumps to an address, all other e8 arguments are offsets
+        (ArgKind::E8, Value::Known(v)) if op == "jr" => {
+            if !(0..=0xFFFF).contains(&v) {
+                return Err(Error::new(
+                    ErrorKind::Range,
+                    format!(
+                        "{}: address {} out of range 0x0000-0xFFFF in {}",
+                        op, v, form
+                    ),
+                ));
+            }
+            match address {
+                Some(address) => {
+                    let dist = v - (address.0 + len as u64) as i64;
+                    check_jr_jump(dist as i32)?;
+                    bytes.push(dist as u8);
+                }
+                None => {
+                    *reference = Some((bytes.len(), EncodedRef::Relative8(SExp::Immediate(v))));
+                    bytes.push(0);
+                }
+            }
+        }
+        (ArgKind::E8, Value::Label(lbl)) if op == "jr" => {
+            *reference = Some((
+                bytes.len(),
+                EncodedRef::Relative8(SExp::Symbol(Symbol::Label(lbl))),
+            ));
+            bytes.push(0);
+        }
+        (ArgKind::E8, Value::Known(v)) => {
+            if !(i8::MIN as i64..=i8::MAX as i64).contains(&v) {
+                return Err(Error::new(
+                    ErrorKind::Range,
+                    format!("{}: offset {} out of range -128..127", op, v),
+                ));
+            }
+            bytes.push(v as u8);
+        }
+        (ArgKind::A8, Value::Known(v)) => {
+            check_high_page_address(v)?;
+            bytes.push(v as u8);
+        }
+        (ArgKind::A8, Value::Label(lbl)) => {
+            *reference = Some((
+                bytes.len(),
+                EncodedRef::High8(SExp::Symbol(Symbol::Label(lbl))),
+            ));
+            bytes.push(0);
+        }
+        (ArgKind::A8, Value::Expression(exp)) => {
+            *reference = Some((bytes.len(), EncodedRef::High8(exp)));
+            bytes.push(0);
+        }
+        (kind, value) => {
+            return Err(Error::syntax(format!(
+                "{}: {} not allowed as {} argument",
+                op,
+                value_text(&value),
+                kind.placeholder()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The text of all valid forms of an instruction. Forms that only differ
+/// in one operand are merged, e.g. (inc %b|%c|%d).
+fn valid_forms(candidates: &[&InstrPattern]) -> String {
+    let mut forms: Vec<Vec<Vec<&str>>> = candidates
+        .iter()
+        .map(|pattern| {
+            mnemonic_parts(pattern.mnemonic)
+                .into_iter()
+                .map(|part| vec![part])
+                .collect()
+        })
+        .collect();
+
+    'merge: loop {
+        for i in 0..forms.len() {
+            for j in (i + 1)..forms.len() {
+                if forms[i].len() != forms[j].len() {
+                    continue;
+                }
+                let diffs: Vec<usize> = (0..forms[i].len())
+                    .filter(|k| forms[i][*k] != forms[j][*k])
+                    .collect();
+                if let [k] = diffs[..] {
+                    let merge = forms.remove(j);
+                    for alt in &merge[k] {
+                        if !forms[i][k].contains(alt) {
+                            forms[i][k].push(alt);
+                        }
+                    }
+                    continue 'merge;
+                }
+            }
+        }
+        break;
+    }
+
+    let texts: Vec<String> = forms
+        .iter()
+        .map(|form| {
+            let parts: Vec<String> = form.iter().map(|alts| alts.join("|")).collect();
+            format!("({})", parts.join(" "))
+        })
+        .collect();
+    texts.join(" ")
+}
+
+/// The ldh pattern for a ld from or to a deref address that is
+/// known to be in the high page. A label of another object is unknown,
+/// the ld stays the 3 byte absolute form (FA lo hi) for it, write ldh
+/// to get the short form.
+fn high_page_variant(
+    pattern: &InstrPattern,
+    operands: &[Operand],
+    const_values: &HashMap<String, i64>,
+    label_addresses: &HashMap<Label, Address>,
+) -> Option<&'static InstrPattern> {
+    if pattern.op != "ld" {
+        return None;
+    }
+    let deref_ix = pattern
+        .operands
+        .iter()
+        .position(|p| *p == Pattern::DerefArg(ArgKind::A16))?;
+    let known_address = match &operands[deref_ix] {
+        Operand::Deref(Value::Known(v)) => Some(*v),
+        Operand::Deref(Value::Label(lbl)) => label_addresses.get(lbl).map(|a| a.0 as i64),
+        Operand::Deref(Value::Expression(exp)) => eval(exp, const_values, label_addresses).ok(),
+        _ => None,
+    }?;
+    if check_high_page_address(known_address).is_err() {
+        return None;
+    }
+
+    PATTERNS.iter().find(|ldh| {
+        ldh.op == "ldh"
+            && ldh.operands.len() == pattern.operands.len()
+            && ldh
+                .operands
+                .iter()
+                .zip(&pattern.operands)
+                .all(|(p_ldh, p_ld)| {
+                    p_ldh == p_ld
+                        || (*p_ldh == Pattern::DerefArg(ArgKind::A8)
+                            && *p_ld == Pattern::DerefArg(ArgKind::A16))
+                })
+    })
+}
+
+fn rst_vectors(candidates: &[&InstrPattern]) -> String {
+    let vectors: Vec<String> = candidates
+        .iter()
+        .filter_map(|pattern| match pattern.operands.as_slice() {
+            [Pattern::Literal(v)] => Some(format!("0x{:02X}", v)),
+            _ => None,
+        })
+        .collect();
+    vectors.join(" ")
+}
+
+/// Splits the mnemonic into the op and the operand texts,
+/// "ld (%hl +) %a" into ["ld", "(%hl +)", "%a"].
+fn mnemonic_parts(mnemonic: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, ch) in mnemonic.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ' ' if depth == 0 => {
+                parts.push(&mnemonic[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&mnemonic[start..]);
+    parts
+}
+
+fn matches_all(patterns: &[Pattern], operands: &[Operand]) -> bool {
+    patterns.len() == operands.len()
+        && patterns
+            .iter()
+            .zip(operands)
+            .all(|(pattern, operand)| matches(pattern, operand))
+}
+
+fn matches(pattern: &Pattern, operand: &Operand) -> bool {
+    match (pattern, operand) {
+        (Pattern::Reg(p_reg), Operand::Reg(reg)) => p_reg == reg,
+        (Pattern::DerefReg(p_reg, p_mod), Operand::DerefReg(reg, deref_mod)) => {
+            p_reg == reg && p_mod.as_deref() == *deref_mod
+        }
+        (Pattern::Flag(p_flag), Operand::Flag(flag)) => p_flag == flag,
+        (Pattern::Literal(p_v), Operand::Value(Value::Known(v))) => p_v == v,
+        (Pattern::Arg(_), Operand::Value(_)) => true,
+        (Pattern::DerefArg(_), Operand::Deref(_)) => true,
+        _ => false,
+    }
+}
+
+fn operand<'a>(exp: &'a SExp, const_values: &HashMap<String, i64>) -> Result<Operand<'a>, Error> {
+    match exp {
+        SExp::Symbol(Symbol::Reg(reg)) => Ok(Operand::Reg(reg)),
+        SExp::Symbol(Symbol::Flag(flag)) => {
+            if !sm83::FLAGS.contains(&flag.as_str()) {
+                let valid: Vec<String> = sm83::FLAGS.iter().map(|f| format!("#{}", f)).collect();
+                return Err(Error::syntax(format!(
+                    "unknown flag #{}, valid flags: {}",
+                    flag,
+                    valid.join(" ")
+                )));
+            }
+            Ok(Operand::Flag(flag))
+        }
+        SExp::Form(form) => match &form.op {
+            Symbol::Reg(reg) => match form.exps.as_slice() {
+                [] => Ok(Operand::DerefReg(reg, None)),
+                [SExp::Symbol(Symbol::Sym(deref_mod))] => {
+                    Ok(Operand::DerefReg(reg, Some(deref_mod)))
+                }
+                _ => Err(Error::syntax(format!("illegal deref: {}", form))),
+            },
+            Symbol::Sym(sym) if sym.is_empty() => match (&form.label, form.exps.as_slice()) {
+                // ('lbl)
+                (Some(lbl), []) => Ok(Operand::Deref(Value::Label(lbl.clone()))),
+                // ((+ 'lbl 1))
+                (None, [exp]) => Ok(Operand::Deref(value(exp, const_values)?)),
+                _ => Err(Error::syntax(format!("illegal deref: {}", form))),
+            },
+            // (0xFF40) or (+constant+)
+            Symbol::Sym(sym) if form.exps.is_empty() && form.label.is_none() => {
+                let v = match const_values.get(sym) {
+                    Some(v) => *v,
+                    None => parse_number(sym).map_err(Error::syntax)?,
+                };
+                Ok(Operand::Deref(Value::Known(v)))
+            }
+            _ => Ok(Operand::Value(value(exp, const_values)?)),
+        },
+        _ => Ok(Operand::Value(value(exp, const_values)?)),
+    }
+}
+
+fn value(exp: &SExp, const_values: &HashMap<String, i64>) -> Result<Value, Error> {
+    match exp {
+        SExp::Immediate(v) => Ok(Value::Known(*v)),
+        SExp::Symbol(Symbol::Label(lbl)) => Ok(Value::Label(lbl.clone())),
+        SExp::Symbol(Symbol::Sym(_)) => Ok(Value::Known(eval_const(exp, const_values)?)),
+        // deferred until the labels are known, the constants are
+        // replaced now
+        SExp::Form(Form {
+            op: Symbol::Sym(sym),
+            ..
+        }) if is_const_expression_op(sym) && contains_label(exp) => {
+            Ok(Value::Expression(fold_constants(exp, const_values)?))
+        }
+        SExp::Form(Form {
+            op: Symbol::Sym(sym),
+            ..
+        }) if is_const_expression_op(sym) => Ok(Value::Known(eval_const(exp, const_values)?)),
+        illegal => Err(Error::syntax(format!("illegal argument: {}", illegal))),
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Known(v) => v.to_string(),
+        Value::Label(lbl) => format!("'{}", lbl.name()),
+        Value::Expression(exp) => exp.to_string(),
+    }
+}
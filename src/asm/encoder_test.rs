@@ -0,0 +1,264 @@
+use crate::arch::sm83::{self, INSTRUCTIONS, PREFIX_INSTRUCTIONS};
+use crate::asm::encoder::{Encoded, EncodedRef, encode};
+use crate::asm::parser::{Address, Label, SExp, Symbol, parse_from_string};
+use crate::error::Error;
+use std::collections::HashMap;
+
+const TEST_ADDR: Address = Address(0x100);
+
+/// Replaces the argument placeholders in the mnemonic with sample
+/// values and returns the text with the expected argument bytes.
+fn sample_text(mnemonic: &str) -> (String, Vec<u8>) {
+    if mnemonic.contains("n16") || mnemonic.contains("a16") {
+        let text = mnemonic.replace("n16", "0x1234").replace("a16", "0x1234");
+        (text, vec![0x34, 0x12])
+    } else if mnemonic.contains("n8") {
+        (mnemonic.replace("n8", "0x12"), vec![0x12])
+    } else if mnemonic.contains("a8") {
+        (mnemonic.replace("a8", "0xFF12"), vec![0x12])
+    } else if mnemonic.starts_with("jr") {
+        // jump target, 3 bytes after the instruction end
+        (mnemonic.replace("e8", "0x105"), vec![3])
+    } else if mnemonic.contains("e8") {
+        (mnemonic.replace("e8", "5"), vec![5])
+    } else {
+        (mnemonic.to_string(), vec![])
+    }
+}
+
+/// Encodes the form with the constant +c+ = 0xFF40 and the
+/// labels 'hw-ly = 0xFF44 and 'ram = 0xC000 defined.
+fn encode_str(str: &str) -> Result<Encoded, Error> {
+    let mut const_values = HashMap::new();
+    const_values.insert("+c+".to_string(), 0xFF40);
+    let mut label_addresses = HashMap::new();
+    label_addresses.insert(Label::from_str("hw-ly"), Address(0xFF44));
+    label_addresses.insert(Label::from_str("ram"), Address(0xC000));
+    let mut tl = parse_from_string(str)?;
+    encode(
+        &tl.forms.pop().expect("form"),
+        Some(TEST_ADDR),
+        &const_values,
+        &label_addresses,
+    )
+}
+
+fn must_parse_exp(str: &str) -> SExp {
+    let mut tl = parse_from_string(&format!("(x {})", str)).expect("parse");
+    tl.forms[0].exps.pop().expect("exp")
+}
+
+#[test]
+fn test_encode_all_instructions() -> Result<(), Error> {
+    for instr in INSTRUCTIONS {
+        if std::ptr::eq(instr, &sm83::INSTR_INVALID) || std::ptr::eq(instr, &sm83::INSTR_PREFIX) {
+            continue;
+        }
+        let (text, args) = sample_text(instr.mnemonic);
+        let mut expected = vec![instr.op_code];
+        expected.extend(args);
+        expected.resize(instr.len(), 0);
+
+        let encoded = encode_str(&format!("({})", text))
+            .map_err(|e| Error::syntax(format!("{}: {}", text, e)))?;
+        assert_eq!(encoded.bytes, expected, "instr={}", text);
+        assert!(encoded.reference.is_none());
+    }
+
+    for instr in PREFIX_INSTRUCTIONS {
+        let encoded = encode_str(&format!("({})", instr.mnemonic))?;
+        assert_eq!(
+            encoded.bytes,
+            vec![sm83::INSTR_PREFIX.op_code, instr.op_code],
+            "instr={}",
+            instr.mnemonic
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_encode_ok() -> Result<(), Error> {
+    let cases = [
+        // implicit %a for the ALU ops
+        ("(cp 144)", vec![0xFE, 144]),
+        ("(or %c)", vec![0xB1]),
+        ("(xor %a)", vec![0xAF]),
+        ("(add %a %b)", vec![0x80]),
+        // constants
+        ("(ld %a (| 1 0x10))", vec![0x3E, 0x11]),
+        ("(ld %a (hi +c+))", vec![0x3E, 0xFF]),
+        ("(ld %a (lo (+ +c+ 4)))", vec![0x3E, 0x44]),
+        ("(ld %a (& (~ 0x0F) 0xFF))", vec![0x3E, 0xF0]),
+        ("(ld %hl (* 0x10 (- 20 4)))", vec![0x21, 0x00, 0x01]),
+        ("(ld %a (- 1))", vec![0x3E, 0xFF]),
+        ("(cp (>= +c+ 0xFF00))", vec![0xFE, 0x01]),
+        ("(ld %a ((+ +c+ 4)))", vec![0xF0, 0x44]),
+        // signed and unsigned immediates
+        ("(ld %a -1)", vec![0x3E, 0xFF]),
+        ("(ld %a 255)", vec![0x3E, 0xFF]),
+        ("(ld %de -0x8000)", vec![0x11, 0x00, 0x80]),
+        ("(ld %hl +c+)", vec![0x21, 0x40, 0xFF]),
+        ("(ld %a (+c+))", vec![0xF0, 0x40]),
+        // deref of a known address
+        ("(ld (0xC000) %a)", vec![0xEA, 0x00, 0xC0]),
+        ("(ld (0xFF80) %a)", vec![0xE0, 0x80]),
+        ("(ld %a (0xFFFF))", vec![0xF0, 0xFF]),
+        ("(ld %a (0xFEFF))", vec![0xFA, 0xFF, 0xFE]),
+        ("(ldh %a (0xFF44))", vec![0xF0, 0x44]),
+        ("(ldh (%c) %a)", vec![0xE2]),
+        ("(ldh %a (%c))", vec![0xF2]),
+        // jr to a known address, jumps back to itself
+        ("(jr 0x100)", vec![0x18, 0xFE]),
+        ("(rst 0x38)", vec![0xFF]),
+        ("(stop)", vec![0x10, 0x00]),
+    ];
+    for (str, bytes) in cases {
+        let encoded = encode_str(str)?;
+        assert_eq!(encoded.bytes, bytes, "exp={}", str);
+        assert!(encoded.reference.is_none(), "exp={}", str);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_encode_reference() -> Result<(), Error> {
+    let label_exp = |name: &str| SExp::Symbol(Symbol::Label(Label::from_str(name)));
+    let cases = [
+        ("(jp 'lbl)", (1, EncodedRef::Absolute16(label_exp("lbl")))),
+        (
+            "(call #z 'fn)",
+            (1, EncodedRef::Absolute16(label_exp("fn"))),
+        ),
+        (
+            "(ld %a ('lbl))",
+            (1, EncodedRef::Absolute16(label_exp("lbl"))),
+        ),
+        (
+            "(ld ('lbl) %sp)",
+            (1, EncodedRef::Absolute16(label_exp("lbl"))),
+        ),
+        (
+            "(jr #nc 'lbl)",
+            (1, EncodedRef::Relative8(label_exp("lbl"))),
+        ),
+        ("(ldh ('lbl) %a)", (1, EncodedRef::High8(label_exp("lbl")))),
+        ("(ldh %a ('ram))", (1, EncodedRef::High8(label_exp("ram")))),
+        (
+            "(ld %a (hi 'lbl))",
+            (1, EncodedRef::Immediate8(must_parse_exp("(hi 'lbl)"))),
+        ),
+        // the constant is replaced
+        (
+            "(ld %hl (+ 'lbl +c+))",
+            (1, EncodedRef::Absolute16(must_parse_exp("(+ 'lbl 65344)"))),
+        ),
+        (
+            "(jp (+ 'lbl (* 2 3)))",
+            (1, EncodedRef::Absolute16(must_parse_exp("(+ 'lbl 6)"))),
+        ),
+    ];
+    for (str, reference) in cases {
+        let encoded = encode_str(str)?;
+        assert_eq!(encoded.reference, Some(reference), "exp={}", str);
+    }
+
+    // a known jr target is a reference if the address is not final yet
+    let tl = parse_from_string("(jr 0x105)")?;
+    let encoded = encode(&tl.forms[0], None, &HashMap::new(), &HashMap::new())?;
+    assert_eq!(encoded.bytes, vec![0x18, 0]);
+    assert_eq!(
+        encoded.reference,
+        Some((1, EncodedRef::Relative8(SExp::Immediate(0x105))))
+    );
+
+    // ld is encoded as ldh if the label is known to be in the high page
+    let cases = [
+        (
+            "(ld %a ('hw-ly))",
+            vec![0xF0, 0],
+            EncodedRef::High8(label_exp("hw-ly")),
+        ),
+        (
+            "(ld ('hw-ly) %a)",
+            vec![0xE0, 0],
+            EncodedRef::High8(label_exp("hw-ly")),
+        ),
+        (
+            "(ld %a ('ram))",
+            vec![0xFA, 0, 0],
+            EncodedRef::Absolute16(label_exp("ram")),
+        ),
+        // not yet defined
+        (
+            "(ld ('lbl) %a)",
+            vec![0xEA, 0, 0],
+            EncodedRef::Absolute16(label_exp("lbl")),
+        ),
+    ];
+    for (str, bytes, reference) in cases {
+        let encoded = encode_str(str)?;
+        assert_eq!(encoded.bytes, bytes, "exp={}", str);
+        assert_eq!(encoded.reference, Some((1, reference)), "exp={}", str);
+    }
+
+    let encoded = encode_str("(ld %a ((+ 'hw-ly)))")?;
+    let mut tl = parse_from_string("(+ 'hw-ly)")?;
+    let exp = SExp::Form(tl.forms.pop().expect("form"));
+    assert_eq!(encoded.bytes, vec![0xF0, 0]);
+    assert_eq!(encoded.reference, Some((1, EncodedRef::High8(exp))));
+
+    let encoded = encode_str("(ld %a ((+ 'lbl 1)))")?;
+    let mut tl = parse_from_string("(+ 'lbl 1)")?;
+    let exp = SExp::Form(tl.forms.pop().expect("form"));
+    assert_eq!(encoded.bytes, vec![0xFA, 0, 0]);
+    assert_eq!(encoded.reference, Some((1, EncodedRef::Absolute16(exp))));
+    Ok(())
+}
+
+#[test]
+fn test_encode_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(nop %a)",
+            "nop: illegal arguments: (nop %a), valid forms: (nop)",
+        ),
+        (
+            "(push %sp)",
+            "push: illegal arguments: (push %sp), valid forms: (push %bc|%de|%hl|%af)",
+        ),
+        (
+            "(rst 0x39)",
+            "rst: illegal vector 0x39, valid vectors: 0x00 0x08 0x10 0x18 0x20 0x28 0x30 0x38",
+        ),
+        ("(add %sp 128)", "add: offset 128 out of range -128..127"),
+        (
+            "(ldh %a (0xFE44))",
+            "ldh: address 0xFE44 not in high page 0xFF00-0xFFFF",
+        ),
+        ("(jr 0x200)", "jr: max 127 jumps forward, was 254"),
+        (
+            "(jr -5)",
+            "jr: address -5 out of range 0x0000-0xFFFF in (jr -5)",
+        ),
+        ("(ld %a 'lbl)", "ld: 'lbl not allowed as n8 argument"),
+        (
+            "(ld %a (%hl) %b)",
+            "ld: illegal arguments: (ld %a (%hl) %b), valid forms: ",
+        ),
+        ("(ld %a (0x12G))", "ld: illegal number: 0x12G"),
+        (
+            "(ld %a (| 0x100 1))",
+            "ld: immediate value 257 out of range -128..255 in (ld %a (| 256 1))",
+        ),
+        (
+            "(ld (0x10000) %a)",
+            "ld: address 65536 out of range 0x0000-0xFFFF in (ld (0x10000) %a)",
+        ),
+    ];
+    for (str, err) in cases {
+        let got_err = encode_str(str).unwrap_err().to_string();
+        assert!(got_err.starts_with(err), "exp={}, err={}", str, got_err);
+    }
+    Ok(())
+}
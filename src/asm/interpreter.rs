@@ -4,132 +4,466 @@ mod interpreter_test;
 
 use std::collections::HashMap;
 
-use crate::asm::parser::{Address, Form, Label, SExp, Symbol};
-
-/// eval_aar, aar = address_arithmetic
-/// only able to to evaluate address arithmetic computation
-pub fn eval_aar(sexp: &SExp, label_addresses: &HashMap<Label, Address>) -> Result<Address, String> {
-    match sexp {
-        SExp::Symbol(Symbol::Label(lbl)) => {
-            let may_address = label_addresses.get(lbl);
+use crate::asm::parser::{Address, Form, Label, SExp, Symbol, parse_number};
+use crate::error::{Error, ErrorKind};
+
+pub const CONST_OP_PLUS: &str = "+";
+pub const CONST_OP_MINUS: &str = "-";
+pub const CONST_OP_MULTIPLY: &str = "*";
+pub const CONST_OP_DIVIDE: &str = "/";
+pub const CONST_OP_MODULO: &str = "mod";
+pub const CONST_OP_BITWISE_AND: &str = "&";
+pub const CONST_OP_BITWISE_OR: &str = "|";
+pub const CONST_OP_BITWISE_XOR: &str = "^";
+pub const CONST_OP_BITWISE_NOT: &str = "~";
+pub const CONST_OP_SHIFT_LEFT: &str = "<<";
+pub const CONST_OP_SHIFT_RIGHT: &str = ">>";
+pub const CONST_OP_NOT: &str = "not";
+pub const CONST_OP_EQUAL: &str = "=";
+pub const CONST_OP_NOT_EQUAL: &str = "!=";
+pub const CONST_OP_LESS: &str = "<";
+pub const CONST_OP_LESS_EQUAL: &str = "<=";
+pub const CONST_OP_GREATER: &str = ">";
+pub const CONST_OP_GREATER_EQUAL: &str = ">=";
+pub const CONST_OP_HIGH_BYTE: &str = "hi";
+pub const CONST_OP_LOW_BYTE: &str = "lo";
+
+pub const CONST_OPS: [&str; 20] = [
+    CONST_OP_PLUS,
+    CONST_OP_MINUS,
+    CONST_OP_MULTIPLY,
+    CONST_OP_DIVIDE,
+    CONST_OP_MODULO,
+    CONST_OP_BITWISE_AND,
+    CONST_OP_BITWISE_OR,
+    CONST_OP_BITWISE_XOR,
+    CONST_OP_BITWISE_NOT,
+    CONST_OP_SHIFT_LEFT,
+    CONST_OP_SHIFT_RIGHT,
+    CONST_OP_NOT,
+    CONST_OP_EQUAL,
+    CONST_OP_NOT_EQUAL,
+    CONST_OP_LESS,
+    CONST_OP_LESS_EQUAL,
+    CONST_OP_GREATER,
+    CONST_OP_GREATER_EQUAL,
+    CONST_OP_HIGH_BYTE,
+    CONST_OP_LOW_BYTE,
+];
+
+/// eval
+/// evaluates the expression over immediates, constants and labels. A label
+/// evaluates to its address, an undefined label is an error.
+///
+/// The operators are
+/// - n-ary: + - * / & | ^ (a single - negates, (+) is 0), / and mod are euclidean: mod is
+///   never negative and a = (/ a b) * b + (mod a b), (/ -7 2) is -4 and (/ -7 -2) is 4
+/// - binary: mod << >> = != < <= > >=, comparisons are 1 if true and 0 if false
+/// - unary: ~ (bitwise not), not (1 for 0, otherwise 0), hi and lo (high and low
+///   byte of the 16 bit value)
+pub fn eval(
+    exp: &SExp,
+    const_values: &HashMap<String, i64>,
+    label_addresses: &HashMap<Label, Address>,
+) -> Result<i64, Error> {
+    eval_in(exp, const_values, Some(label_addresses))
+}
+
+/// eval_const
+/// const expression evaluation. Every variable in the expression tree must evaluate to a constant value.
+/// If not, an error is returned. Labels are not constant.
+pub fn eval_const(exp: &SExp, const_values: &HashMap<String, i64>) -> Result<i64, Error> {
+    eval_in(exp, const_values, None)
+}
+
+fn eval_in(
+    exp: &SExp,
+    const_values: &HashMap<String, i64>,
+    label_addresses: Option<&HashMap<Label, Address>>,
+) -> Result<i64, Error> {
+    match exp {
+        SExp::Immediate(val) => Ok(*val),
+        SExp::Symbol(Symbol::Sym(name)) => {
+            let maybe_val = const_values.get(name);
+            if let Some(val) = maybe_val {
+                Ok(*val)
+            } else {
+                Err(Error::new(
+                    ErrorKind::UndefinedConstant,
+                    format!("no constant value for symbol: {}", name),
+                ))
+            }
+        }
+        SExp::Symbol(Symbol::Label(lbl)) if label_addresses.is_some() => {
+            let may_address = label_addresses.and_then(|addresses| addresses.get(lbl));
             if let Some(address) = may_address {
-                Ok(*address)
+                Ok(address.0 as i64)
             } else {
-                Err(format!("undefined label: {:?}", lbl))
+                Err(Error::new(
+                    ErrorKind::UndefinedLabel,
+                    format!("undefined label: '{}", lbl.name()),
+                ))
             }
         }
         SExp::Form(form) => {
-            let op_sym = match &form.op {
-                Symbol::Sym(sym) => sym,
-                invalid => {
-                    return Err(format!(
-                        "illegal arithmetic address operator: {:?}",
-                        invalid
-                    ));
+            let op_name = match &form.op {
+                Symbol::Sym(op_name) if CONST_OPS.contains(&op_name.as_str()) => op_name,
+                Symbol::Sym(op_name) => {
+                    return Err(Error::syntax(format!("illegal constant op: {:?}", op_name)));
+                }
+                illegal => {
+                    return Err(Error::syntax(format!(
+                        "illegal constant form: {:?}",
+                        illegal
+                    )));
                 }
             };
+            let args = form
+                .exps
+                .iter()
+                .map(|exp| eval_in(exp, const_values, label_addresses))
+                .collect::<Result<Vec<i64>, Error>>()?;
+            apply_const_op(op_name, &args)
+        }
+        illegal => Err(Error::syntax(format!(
+            "not a constant expression: {:?}",
+            illegal
+        ))),
+    }
+}
 
-            match op_sym.as_str() {
-                "-" => eval_aar_minus(form, label_addresses),
-                "+" => eval_aar_plus(form, label_addresses),
-                invalid => {
-                    return Err(format!(
-                        "illegal arithmetic address operator: {:?}",
-                        invalid
-                    ));
-                }
-            }
+/// Replaces the constants in the expression by their values and evaluates
+/// all sub expressions without a label. The result only refers to labels,
+/// it can be evaluated without the constants (e.g. by the linker).
+pub fn fold_constants(exp: &SExp, const_values: &HashMap<String, i64>) -> Result<SExp, Error> {
+    if !contains_label(exp) {
+        return Ok(SExp::Immediate(eval_const(exp, const_values)?));
+    }
+    match exp {
+        SExp::Form(form) => {
+            let exps = form
+                .exps
+                .iter()
+                .map(|exp| fold_constants(exp, const_values))
+                .collect::<Result<Vec<SExp>, Error>>()?;
+            Ok(SExp::Form(Form {
+                exps,
+                ..form.clone()
+            }))
         }
-        invalid => Err(format!(
-            "illegal address arithmetic expression: {:?}",
-            invalid
-        )),
+        exp => Ok(exp.clone()),
     }
 }
 
-fn eval_aar_minus(
-    form: &Form,
-    label_addresses: &HashMap<Label, Address>,
-) -> Result<Address, String> {
-    if form.exps.len() <= 1 {
-        return Err(format!(
-            "-: invalid number or arguments {}",
-            form.exps.len()
-        ));
+/// Whether the expression refers to a label, its value is then only known
+/// after all labels are defined.
+pub fn contains_label(exp: &SExp) -> bool {
+    match exp {
+        SExp::Symbol(Symbol::Label(_)) => true,
+        SExp::Form(form) => form.label.is_some() || form.exps.iter().any(contains_label),
+        _ => false,
     }
+}
 
-    let mut address = eval_aar(&form.exps[0], label_addresses)?;
-    for i in 1..(form.exps.len()) {
-        let exp = &form.exps[i];
-        let exp_address = eval_aar(exp, label_addresses)?;
-        if address.0 < exp_address.0 {
-            return Err("-: negative address".to_string());
+fn apply_const_op(op: &str, args: &[i64]) -> Result<i64, Error> {
+    match op {
+        CONST_OP_PLUS => args
+            .iter()
+            .try_fold(0i64, |sum, arg| sum.checked_add(*arg))
+            .ok_or_else(|| overflow(op)),
+        CONST_OP_MINUS if args.len() == 1 => args[0].checked_neg().ok_or_else(|| overflow(op)),
+        CONST_OP_MINUS => fold_args(op, args, 1, i64::checked_sub),
+        CONST_OP_MULTIPLY => fold_args(op, args, 1, i64::checked_mul),
+        CONST_OP_DIVIDE => {
+            expect_no_zero_divisor(op, args)?;
+            fold_args(op, args, 2, i64::checked_div_euclid)
         }
-        address = Address(address.0 - exp_address.0);
+        CONST_OP_MODULO => {
+            expect_arity(op, args, 2)?;
+            expect_no_zero_divisor(op, args)?;
+            args[0]
+                .checked_rem_euclid(args[1])
+                .ok_or_else(|| overflow(op))
+        }
+        CONST_OP_BITWISE_AND => fold_args(op, args, 1, |a, b| Some(a & b)),
+        CONST_OP_BITWISE_OR => fold_args(op, args, 1, |a, b| Some(a | b)),
+        CONST_OP_BITWISE_XOR => fold_args(op, args, 1, |a, b| Some(a ^ b)),
+        CONST_OP_BITWISE_NOT => {
+            expect_arity(op, args, 1)?;
+            Ok(!args[0])
+        }
+        CONST_OP_SHIFT_LEFT | CONST_OP_SHIFT_RIGHT => {
+            expect_arity(op, args, 2)?;
+            if !(0..64).contains(&args[1]) {
+                return Err(Error::new(
+                    ErrorKind::Range,
+                    format!("{}: shift {} out of range 0..63", op, args[1]),
+                ));
+            }
+            if op == CONST_OP_SHIFT_LEFT {
+                Ok(args[0] << args[1])
+            } else {
+                Ok(args[0] >> args[1])
+            }
+        }
+        CONST_OP_NOT => {
+            expect_arity(op, args, 1)?;
+            Ok((args[0] == 0) as i64)
+        }
+        CONST_OP_EQUAL => compare(op, args, |a, b| a == b),
+        CONST_OP_NOT_EQUAL => compare(op, args, |a, b| a != b),
+        CONST_OP_LESS => compare(op, args, |a, b| a < b),
+        CONST_OP_LESS_EQUAL => compare(op, args, |a, b| a <= b),
+        CONST_OP_GREATER => compare(op, args, |a, b| a > b),
+        CONST_OP_GREATER_EQUAL => compare(op, args, |a, b| a >= b),
+        CONST_OP_HIGH_BYTE => {
+            expect_arity(op, args, 1)?;
+            Ok((args[0] >> 8) & 0xFF)
+        }
+        CONST_OP_LOW_BYTE => {
+            expect_arity(op, args, 1)?;
+            Ok(args[0] & 0xFF)
+        }
+        _ => Err(Error::syntax(format!("illegal constant op: {:?}", op))),
     }
-    Ok(address)
 }
 
-fn eval_aar_plus(
-    form: &Form,
-    label_addresses: &HashMap<Label, Address>,
-) -> Result<Address, String> {
-    let mut address = Address(0);
-    for exp in &form.exps {
-        let exp_address = eval_aar(exp, label_addresses)?;
-        address = Address(address.0 + exp_address.0);
+fn fold_args(
+    op: &str,
+    args: &[i64],
+    min_args: usize,
+    f: fn(i64, i64) -> Option<i64>,
+) -> Result<i64, Error> {
+    if args.len() < min_args {
+        return Err(Error::syntax(format!(
+            "{}: needs at least {} {}",
+            op,
+            min_args,
+            parameters(min_args)
+        )));
+    }
+    let mut v = args[0];
+    for arg in &args[1..] {
+        v = f(v, *arg).ok_or_else(|| overflow(op))?;
     }
-    Ok(address)
+    Ok(v)
 }
 
-pub const CONST_OP_SHIFT_LEFT: &str = "<<";
-pub const CONST_OP_BITWISE_OR: &str = "|";
+fn compare(op: &str, args: &[i64], f: fn(i64, i64) -> bool) -> Result<i64, Error> {
+    expect_arity(op, args, 2)?;
+    Ok(f(args[0], args[1]) as i64)
+}
 
-/// eval_const
-/// const expression evaluation. Every variable in the expression tree must evaluate to a constant value.
-/// If not, an error is returned.
-pub fn eval_const(exp: &SExp, const_values: &HashMap<String, i64>) -> Result<i64, String> {
-    match exp {
-        SExp::Immediate(val) => Ok(*val),
-        SExp::Symbol(Symbol::Sym(name)) => {
-            let maybe_val = const_values.get(name);
-            if let Some(val) = maybe_val {
-                Ok(*val)
+fn expect_arity(op: &str, args: &[i64], n: usize) -> Result<(), Error> {
+    if args.len() != n {
+        return Err(Error::syntax(format!(
+            "{}: needs exactly {} {}",
+            op,
+            n,
+            parameters(n)
+        )));
+    }
+    Ok(())
+}
+
+fn expect_no_zero_divisor(op: &str, args: &[i64]) -> Result<(), Error> {
+    if args.len() > 1 && args[1..].contains(&0) {
+        return Err(Error::new(
+            ErrorKind::Range,
+            format!("{}: division by zero", op),
+        ));
+    }
+    Ok(())
+}
+
+fn parameters(n: usize) -> &'static str {
+    if n == 1 { "parameter" } else { "parameters" }
+}
+
+fn overflow(op: &str) -> Error {
+    Error::new(ErrorKind::Range, format!("{}: overflow", op))
+}
+
+pub const CONDITIONAL_OPS: [&str; 6] = ["if", "when", "unless", "ifdef", "ifndef", "cond"];
+
+const COND_ELSE: &str = "else";
+
+/// Evaluates the condition of the conditional form and returns the forms
+/// to assemble. The conditions are constant expressions, a value other
+/// than 0 is true.
+///
+/// (if test then-form [else-form])
+/// (when test form...), (unless test form...)
+/// (ifdef +constant+ form...), (ifndef +constant+ form...)
+/// (cond (test form...)... [(else form...)])
+pub fn conditional_forms(
+    form: &Form,
+    const_values: &HashMap<String, i64>,
+) -> Result<Vec<Form>, Error> {
+    let op = form.op.to_string();
+    let (test, body) = match form.exps.split_first() {
+        Some((test, body)) => (test, body),
+        None if op == "cond" => return Ok(Vec::new()),
+        None => return Err(Error::syntax(format!("{}: condition required", op))),
+    };
+    let selected = match op.as_str() {
+        "if" => {
+            if body.is_empty() || body.len() > 2 {
+                return Err(Error::syntax("if: needs a then and an optional else form"));
+            }
+            if eval_const(test, const_values)? != 0 {
+                &body[0..1]
             } else {
-                Err(format!("no constant value for symbol: {}", name))
+                &body[1..]
             }
         }
-        SExp::Form(form) => {
-            let op_name = match &form.op {
-                Symbol::Sym(op_name) => op_name,
-                illegal => return Err(format!("illegal constant form: {:?}", illegal)),
+        "when" | "unless" => {
+            let holds = eval_const(test, const_values)? != 0;
+            if holds == (op == "when") { body } else { &[] }
+        }
+        "ifdef" | "ifndef" => {
+            let name = match test {
+                SExp::Symbol(Symbol::Sym(name)) => name,
+                exp => {
+                    return Err(Error::syntax(format!(
+                        "{}: constant name expected, got {}",
+                        op, exp
+                    )));
+                }
             };
-            match op_name.as_str() {
-                CONST_OP_SHIFT_LEFT => eval_const_left_shift(form, const_values),
-                CONST_OP_BITWISE_OR => eval_const_bitwise_or(form, const_values),
-                _ => Err(format!("illegal constant op: {:?}", op_name)),
+            let defined = const_values.contains_key(name);
+            if defined == (op == "ifdef") {
+                body
+            } else {
+                &[]
             }
         }
-        illegal => Err(format!("not a constant expression: {:?}", illegal)),
-    }
+        "cond" => return cond_forms(&form.exps, const_values),
+        _ => return Err(Error::syntax(format!("illegal conditional: {}", op))),
+    };
+    expect_forms(&op, selected)
 }
 
-fn eval_const_left_shift(form: &Form, const_values: &HashMap<String, i64>) -> Result<i64, String> {
-    if form.exps.len() != 2 {
-        return Err(format!("<<: needs exactly 2 parameters"));
+fn cond_forms(clauses: &[SExp], const_values: &HashMap<String, i64>) -> Result<Vec<Form>, Error> {
+    for clause in clauses {
+        let clause = match clause {
+            SExp::Form(clause) if clause.label.is_none() => clause,
+            exp => return Err(Error::syntax(format!("cond: clause expected, got {}", exp))),
+        };
+        // a symbol or number test is parsed as operator, e.g. (+debug+ (nop))
+        let (test, body) = match &clause.op {
+            Symbol::Sym(name) if name.is_empty() => match clause.exps.split_first() {
+                Some((test, body)) => (test.clone(), body),
+                None => return Err(Error::syntax("cond: empty clause")),
+            },
+            Symbol::Sym(name) if parse_number(name).is_ok() => (
+                SExp::Immediate(parse_number(name).map_err(Error::syntax)?),
+                &clause.exps[..],
+            ),
+            op => (SExp::Symbol(op.clone()), &clause.exps[..]),
+        };
+        let holds = match &test {
+            SExp::Symbol(Symbol::Sym(name)) if name == COND_ELSE => true,
+            test => eval_const(test, const_values)? != 0,
+        };
+        if holds {
+            return expect_forms("cond", body);
+        }
     }
+    Ok(Vec::new())
+}
 
-    let v = eval_const(&form.exps[0], const_values)?;
-    let shift = eval_const(&form.exps[1], const_values)?;
-    Ok(v << shift)
+fn expect_forms(op: &str, exps: &[SExp]) -> Result<Vec<Form>, Error> {
+    exps.iter()
+        .map(|exp| match exp {
+            SExp::Form(form) => Ok(form.clone()),
+            exp => Err(Error::syntax(format!("{}: form expected, got {}", op, exp))),
+        })
+        .collect()
 }
 
-fn eval_const_bitwise_or(form: &Form, const_values: &HashMap<String, i64>) -> Result<i64, String> {
-    if form.exps.len() != 2 {
-        return Err(format!("|: needs exactly 2 parameters"));
-    }
+pub const LOOP_OPS: [&str; 3] = ["repeat", "dotimes", "for"];
+
+/// Upper bound of the iterations of one loop, more than the address space
+/// of the machine.
+const MAX_LOOP_ITERATIONS: i64 = 0x10000;
 
-    let v1 = eval_const(&form.exps[0], const_values)?;
-    let v2 = eval_const(&form.exps[1], const_values)?;
-    Ok(v1 | v2)
+/// The expansion of a loop form: the body is assembled once for every
+/// value, with the value bound to the variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loop {
+    pub var: Option<String>,
+    pub values: Vec<i64>,
+    pub body: Vec<Form>,
+}
+
+/// Evaluates the bounds of the loop form, the bounds are constant
+/// expressions.
+///
+/// (repeat count form...)
+/// (dotimes (+var+ count) form...), +var+ is 0 to count-1
+/// (for (+var+ start end [step]) form...), +var+ is start up to but
+/// excluding end, the step may be negative
+pub fn loop_values(form: &Form, const_values: &HashMap<String, i64>) -> Result<Loop, Error> {
+    let op = form.op.to_string();
+    let (head, body) = match form.exps.split_first() {
+        Some((head, body)) => (head, expect_forms(&op, body)?),
+        None => return Err(Error::syntax(format!("{}: loop bounds required", op))),
+    };
+    let (var, start, end, step) = match op.as_str() {
+        "repeat" => (None, 0, eval_const(head, const_values)?, 1),
+        "dotimes" | "for" => {
+            let bounds = match head {
+                SExp::Form(bounds) if bounds.label.is_none() => bounds,
+                exp => {
+                    return Err(Error::syntax(format!(
+                        "{}: variable and bounds expected, got {}",
+                        op, exp
+                    )));
+                }
+            };
+            let var = match &bounds.op {
+                Symbol::Sym(name)
+                    if name.len() > 1 && name.starts_with('+') && name.ends_with('+') =>
+                {
+                    name.clone()
+                }
+                sym => {
+                    return Err(Error::syntax(format!(
+                        "{}: invalid variable name: {}, must be surrounded by +",
+                        op, sym
+                    )));
+                }
+            };
+            let values = bounds
+                .exps
+                .iter()
+                .map(|exp| eval_const(exp, const_values))
+                .collect::<Result<Vec<i64>, Error>>()?;
+            match (op.as_str(), values.as_slice()) {
+                ("dotimes", [count]) => (Some(var), 0, *count, 1),
+                ("for", [start, end]) => (Some(var), *start, *end, 1),
+                ("for", [start, end, step]) => (Some(var), *start, *end, *step),
+                ("dotimes", _) => return Err(Error::syntax("dotimes: needs (+var+ count)")),
+                _ => return Err(Error::syntax("for: needs (+var+ start end [step])")),
+            }
+        }
+        _ => return Err(Error::syntax(format!("illegal loop: {}", op))),
+    };
+
+    if step == 0 {
+        return Err(Error::syntax(format!("{}: step must not be 0", op)));
+    }
+    // rounded up, a negative count is no iteration
+    let count = ((end as i128 - start as i128 + step as i128 - step.signum() as i128)
+        / step as i128)
+        .max(0);
+    if count > MAX_LOOP_ITERATIONS as i128 {
+        return Err(Error::syntax(format!(
+            "{}: {} iterations, more than {}",
+            op, count, MAX_LOOP_ITERATIONS
+        )));
+    }
+    let values = (0..count as i64).map(|i| start + i * step).collect();
+    Ok(Loop { var, values, body })
 }
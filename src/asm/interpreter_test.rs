@@ -1,44 +1,46 @@
-use crate::asm::interpreter::{CONST_OP_BITWISE_OR, eval_aar, eval_const};
-use crate::asm::parser::{Address, Form, Label, SExp, Symbol, parse_from_string};
+use crate::asm::interpreter::{
+    CONST_OP_BITWISE_OR, conditional_forms, contains_label, eval, eval_const, fold_constants,
+    loop_values,
+};
+use crate::asm::parser::{Address, Form, Label, SExp, Span, Symbol, parse_from_string};
+use crate::error::Error;
+use std::collections::HashMap;
 
 #[test]
-fn test_eval_aar_fails() -> Result<(), String> {
+fn test_eval_fails() -> Result<(), Error> {
     let cases = [
         (
             "(-)",
             &[].iter().cloned().collect(),
-            "-: invalid number or arguments 0",
+            "-: needs at least 1 parameter",
         ),
         (
             "(- 'a)",
             &[].iter().cloned().collect(),
-            "-: invalid number or arguments 1",
+            "undefined label: 'a",
         ),
         (
             "(- 'a 'b)",
             &[].iter().cloned().collect(),
-            "undefined label: Label(\"a\")",
+            "undefined label: 'a",
         ),
         (
-            "(- 'a 'b)", // negative address not allowed
-            &[
-                (Label::from_str("a"), Address(0x00)),
-                (Label::from_str("b"), Address(0x01)),
-            ]
-            .iter()
-            .cloned()
-            .collect(),
-            "-: negative address",
+            "(+ 'a +undefined+)",
+            &[(Label::from_str("a"), Address(0x00))]
+                .iter()
+                .cloned()
+                .collect(),
+            "no constant value for symbol: +undefined+",
         ),
         (
             "(x 'a)",
             &[].iter().cloned().collect(),
-            "illegal arithmetic address operator: \"x\"",
+            "illegal constant op: \"x\"",
         ),
     ];
     for (exp, label_addresses, err) in cases {
         let sexp = SExp::Form(must_parse_form(exp));
-        let r = eval_aar(&sexp, label_addresses);
+        let r = eval(&sexp, &HashMap::new(), label_addresses);
 
         assert!(
             r.is_err(),
@@ -46,14 +48,14 @@ fn test_eval_aar_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
 
     Ok(())
 }
 
 #[test]
-fn test_eval_aar_ok() -> Result<(), String> {
+fn test_eval_ok() -> Result<(), Error> {
     let cases = [
         (
             "label only expression",
@@ -161,14 +163,70 @@ fn test_eval_aar_ok() -> Result<(), String> {
         ),
     ];
     for (test, sexp, label_addresses, want_result_address) in cases {
-        let got_result_address = eval_aar(&sexp, label_addresses)?;
-        assert_eq!(got_result_address, want_result_address, "test: {}", test);
+        let got_result_address = eval(&sexp, &HashMap::new(), label_addresses)?;
+        assert_eq!(
+            got_result_address, want_result_address.0 as i64,
+            "test: {}",
+            test
+        );
     }
     Ok(())
 }
 
 #[test]
-fn test_eval_const_fails() -> Result<(), String> {
+fn test_eval_mixed() -> Result<(), Error> {
+    let const_values = [("+tile-size+".to_string(), 16)].iter().cloned().collect();
+    let label_addresses = [
+        (Label::from_str("tiles"), Address(0x8000)),
+        (Label::from_str("buffer"), Address(0xC1F0)),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    let cases = [
+        ("(+ 'tiles (* +tile-size+ 3))", 0x8030),
+        ("(hi 'buffer)", 0xC1),
+        ("(lo (+ 'buffer +tile-size+))", 0x00),
+        // negative results are only rejected where they are written
+        ("(- 'tiles 'buffer)", 0x8000 - 0xC1F0),
+        ("(/ (- 'buffer 'tiles) +tile-size+)", 0x41F),
+        ("(< 'tiles 'buffer)", 1),
+    ];
+    for (exp, want) in cases {
+        let sexp = SExp::Form(must_parse_form(exp));
+        assert_eq!(
+            eval(&sexp, &const_values, &label_addresses)?,
+            want,
+            "exp={}",
+            exp
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fold_constants() -> Result<(), Error> {
+    let const_values = [("+k+".to_string(), 3)].iter().cloned().collect();
+    let cases = [
+        ("(x (+ +k+ 1))", "4"),
+        ("(x 'lbl)", "'lbl"),
+        ("(x (+ 'lbl (* +k+ 2)))", "(+ 'lbl 6)"),
+        ("(x (hi (+ 'lbl +k+)))", "(hi (+ 'lbl 3))"),
+    ];
+    for (exp, want) in cases {
+        let form = must_parse_form(exp);
+        let folded = fold_constants(&form.exps[0], &const_values)?;
+        assert_eq!(folded.to_string(), want, "exp={}", exp);
+    }
+
+    let form = must_parse_form("(x (+ 'lbl +undefined+))");
+    let err = fold_constants(&form.exps[0], &const_values).unwrap_err();
+    assert_eq!(err.message, "no constant value for symbol: +undefined+");
+    Ok(())
+}
+
+#[test]
+fn test_eval_const_fails() -> Result<(), Error> {
     let cases = [
         (
             "(def-constant +c+ b)",
@@ -193,7 +251,47 @@ fn test_eval_const_fails() -> Result<(), String> {
         (
             "(def-constant +c+ (|))",
             &[].iter().cloned().collect(),
-            "|: needs exactly 2 parameters",
+            "|: needs at least 1 parameter",
+        ),
+        (
+            "(def-constant +c+ (/ 1))",
+            &[].iter().cloned().collect(),
+            "/: needs at least 2 parameters",
+        ),
+        (
+            "(def-constant +c+ (hi 1 2))",
+            &[].iter().cloned().collect(),
+            "hi: needs exactly 1 parameter",
+        ),
+        (
+            "(def-constant +c+ (< 1))",
+            &[].iter().cloned().collect(),
+            "<: needs exactly 2 parameters",
+        ),
+        (
+            "(def-constant +c+ (/ 10 2 0))",
+            &[].iter().cloned().collect(),
+            "/: division by zero",
+        ),
+        (
+            "(def-constant +c+ (mod 10 0))",
+            &[].iter().cloned().collect(),
+            "mod: division by zero",
+        ),
+        (
+            "(def-constant +c+ (<< 1 64))",
+            &[].iter().cloned().collect(),
+            "<<: shift 64 out of range 0..63",
+        ),
+        (
+            "(def-constant +c+ (* 0x100000000 0x100000000))",
+            &[].iter().cloned().collect(),
+            "*: overflow",
+        ),
+        (
+            "(def-constant +c+ (+ 'lbl 1))",
+            &[].iter().cloned().collect(),
+            "not a constant expression: Symbol(Label(Label(\"lbl\")))",
         ),
         (
             "(def-constant +c+ (xxx))",
@@ -211,14 +309,14 @@ fn test_eval_const_fails() -> Result<(), String> {
             err,
             exp
         );
-        assert_eq!(r.unwrap_err(), err, "exp={:?}", exp);
+        assert_eq!(r.unwrap_err().to_string(), err, "exp={:?}", exp);
     }
 
     Ok(())
 }
 
 #[test]
-fn test_eval_const_ok() -> Result<(), String> {
+fn test_eval_const_ok() -> Result<(), Error> {
     let cases = [
         (
             "immediate value const expression",
@@ -238,6 +336,7 @@ fn test_eval_const_ok() -> Result<(), String> {
                 label: None,
                 op: Symbol::Sym("<<".to_string()),
                 exps: vec![SExp::Immediate(1), SExp::Immediate(8)],
+                span: Span::default(),
             }),
             &[].iter().cloned().collect(),
             1 << 8,
@@ -251,6 +350,7 @@ fn test_eval_const_ok() -> Result<(), String> {
                     SExp::Symbol(Symbol::Sym("+k+".to_string())),
                     SExp::Immediate(8),
                 ],
+                span: Span::default(),
             }),
             &[("+k+".to_string(), 42)].iter().cloned().collect(),
             42 << 8,
@@ -268,9 +368,11 @@ fn test_eval_const_ok() -> Result<(), String> {
                             SExp::Symbol(Symbol::Sym("+k+".to_string())),
                             SExp::Immediate(8),
                         ],
+                        span: Span::default(),
                     }),
                     SExp::Immediate(8),
                 ],
+                span: Span::default(),
             }),
             &[("+k+".to_string(), 1)].iter().cloned().collect(),
             (1 << 8) << 8,
@@ -281,6 +383,7 @@ fn test_eval_const_ok() -> Result<(), String> {
                 label: None,
                 op: Symbol::Sym(CONST_OP_BITWISE_OR.to_string()),
                 exps: vec![SExp::Immediate(0x01), SExp::Immediate(0x10)],
+                span: Span::default(),
             }),
             &[].iter().cloned().collect(),
             0x11,
@@ -293,6 +396,68 @@ fn test_eval_const_ok() -> Result<(), String> {
     Ok(())
 }
 
+#[test]
+fn test_eval_const_ops() -> Result<(), Error> {
+    let const_values = [("+k+".to_string(), 3)].iter().cloned().collect();
+    let cases = [
+        ("(+ 1 2 +k+)", 6),
+        ("(+ 5)", 5),
+        ("(- 10 2 +k+)", 5),
+        ("(- +k+)", -3),
+        ("(- -3)", 3),
+        ("(* 2 3 4)", 24),
+        ("(/ 100 5 2)", 10),
+        ("(/ -7 2)", -4),
+        ("(mod 7 3)", 1),
+        ("(mod -7 3)", 2),
+        ("(/ 7 -2)", -3),
+        ("(/ -7 -2)", 4),
+        ("(mod 7 -2)", 1),
+        ("(mod -7 -2)", 1),
+        ("(& 0xFF 0x0F 0x03)", 0x03),
+        ("(| 0x01 0x10 0x80)", 0x91),
+        ("(^ 0xFF 0x0F)", 0xF0),
+        ("(~ 0)", -1),
+        ("(& (~ 0x0F) 0xFF)", 0xF0),
+        ("(<< 1 +k+)", 8),
+        ("(>> 0x80 4)", 0x08),
+        ("(>> -16 2)", -4),
+        ("(not 0)", 1),
+        ("(not 5)", 0),
+        ("(= +k+ 3)", 1),
+        ("(!= +k+ 3)", 0),
+        ("(< 1 2)", 1),
+        ("(<= 2 2)", 1),
+        ("(> 1 2)", 0),
+        ("(>= 1 2)", 0),
+        ("(hi 0xC0DE)", 0xC0),
+        ("(lo 0xC0DE)", 0xDE),
+        ("(hi (+ 0xC0FF 1))", 0xC1),
+        ("(lo -1)", 0xFF),
+    ];
+    for (exp, want) in cases {
+        let form = must_parse_form(&format!("(def-constant +c+ {})", exp));
+        let got = eval_const(&form.exps[1], &const_values)?;
+        assert_eq!(got, want, "exp={}", exp);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_contains_label() {
+    let cases = [
+        ("(+ 1 2)", false),
+        ("(+ +k+ 2)", false),
+        ("(+ 'lbl 2)", true),
+        ("(hi (+ 1 (- 'lbl 2)))", true),
+        ("(hi ('lbl))", true),
+    ];
+    for (exp, want) in cases {
+        let form = must_parse_form(&format!("(x {})", exp));
+        assert_eq!(contains_label(&form.exps[0]), want, "exp={}", exp);
+    }
+}
+
 fn must_parse_form(str: &str) -> Form {
     parse_from_string(str)
         .expect("parse form")
@@ -300,3 +465,121 @@ fn must_parse_form(str: &str) -> Form {
         .pop()
         .expect("test form")
 }
+
+#[test]
+fn test_conditional_forms() -> Result<(), Error> {
+    let const_values: HashMap<String, i64> = [("+cgb+".to_string(), 1), ("+debug+".to_string(), 0)]
+        .into_iter()
+        .collect();
+    let cases = [
+        ("(if +cgb+ (nop) (halt))", "(nop)"),
+        ("(if (= +cgb+ 0) (nop) (halt))", "(halt)"),
+        ("(if +debug+ (nop))", ""),
+        ("(when +cgb+ (nop) (halt))", "(nop)(halt)"),
+        ("(when +debug+ (nop))", ""),
+        ("(unless +debug+ (nop))", "(nop)"),
+        ("(ifdef +debug+ (nop))", "(nop)"),
+        ("(ifdef +release+ (nop))", ""),
+        ("(ifndef +release+ (nop) (halt))", "(nop)(halt)"),
+        (
+            "(cond (+debug+ (nop)) ((> +cgb+ 0) (halt)) (else (stop)))",
+            "(halt)",
+        ),
+        ("(cond (+debug+ (nop)) (else (stop)))", "(stop)"),
+        ("(cond (0 (nop)) (1 (halt)))", "(halt)"),
+        ("(cond (+debug+ (nop)))", ""),
+        ("(cond)", ""),
+    ];
+    for (src, want) in cases {
+        let forms = conditional_forms(&must_parse_form(src), &const_values)?;
+        let got: String = forms.iter().map(|form| form.to_string()).collect();
+        assert_eq!(got, want, "src={}", src);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_conditional_forms_fails() {
+    let const_values = HashMap::new();
+    let cases = [
+        ("(if)", "if: condition required"),
+        ("(if 1)", "if: needs a then and an optional else form"),
+        (
+            "(if 1 (nop) (nop) (nop))",
+            "if: needs a then and an optional else form",
+        ),
+        ("(when +x+ (nop))", "no constant value for symbol: +x+"),
+        ("(when 1 2)", "when: form expected, got 2"),
+        ("(ifdef 'x (nop))", "ifdef: constant name expected, got 'x"),
+        ("(cond 1)", "cond: clause expected, got 1"),
+        ("(cond ())", "cond: empty clause"),
+    ];
+    for (src, err) in cases {
+        assert_eq!(
+            conditional_forms(&must_parse_form(src), &const_values)
+                .unwrap_err()
+                .to_string(),
+            err,
+            "src={}",
+            src
+        );
+    }
+}
+
+#[test]
+fn test_loop_values() -> Result<(), Error> {
+    let const_values: HashMap<String, i64> = [("+n+".to_string(), 3)].into_iter().collect();
+    let cases = [
+        ("(repeat 2 (nop))", None, vec![0, 1]),
+        ("(repeat 0 (nop))", None, vec![]),
+        ("(repeat -1 (nop))", None, vec![]),
+        ("(dotimes (+i+ +n+) (nop))", Some("+i+"), vec![0, 1, 2]),
+        ("(for (+i+ 1 (+ +n+ 1)) (nop))", Some("+i+"), vec![1, 2, 3]),
+        ("(for (+i+ 0 10 4) (nop))", Some("+i+"), vec![0, 4, 8]),
+        ("(for (+i+ 3 0 -1) (nop))", Some("+i+"), vec![3, 2, 1]),
+        ("(for (+i+ 3 3) (nop))", Some("+i+"), vec![]),
+        ("(for (+i+ 3 0) (nop))", Some("+i+"), vec![]),
+    ];
+    for (src, var, values) in cases {
+        let lp = loop_values(&must_parse_form(src), &const_values)?;
+        assert_eq!(lp.var.as_deref(), var, "src={}", src);
+        assert_eq!(lp.values, values, "src={}", src);
+        assert_eq!(lp.body.len(), 1, "src={}", src);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_loop_values_fails() {
+    let const_values = HashMap::new();
+    let cases = [
+        ("(repeat)", "repeat: loop bounds required"),
+        ("(repeat +x+ (nop))", "no constant value for symbol: +x+"),
+        ("(repeat 2 1)", "repeat: form expected, got 1"),
+        (
+            "(dotimes +i+ (nop))",
+            "dotimes: variable and bounds expected, got +i+",
+        ),
+        (
+            "(dotimes (i 2) (nop))",
+            "dotimes: invalid variable name: i, must be surrounded by +",
+        ),
+        ("(dotimes (+i+) (nop))", "dotimes: needs (+var+ count)"),
+        ("(for (+i+ 1) (nop))", "for: needs (+var+ start end [step])"),
+        ("(for (+i+ 0 2 0) (nop))", "for: step must not be 0"),
+        (
+            "(repeat 0x10001 (nop))",
+            "repeat: 65537 iterations, more than 65536",
+        ),
+    ];
+    for (src, err) in cases {
+        assert_eq!(
+            loop_values(&must_parse_form(src), &const_values)
+                .unwrap_err()
+                .to_string(),
+            err,
+            "src={}",
+            src
+        );
+    }
+}
@@ -0,0 +1,223 @@
+#[cfg(test)]
+#[path = "./macros_test.rs"]
+mod macros_test;
+
+use crate::asm::parser::{Form, SExp, Symbol};
+use std::collections::HashMap;
+
+const REST_PARAM: &str = "&rest";
+
+/// A macro defined with
+///
+/// (defmacro name (param... [&rest rest-param]) `(template)...)
+///
+/// A call of the macro expands to its templates, with ,param replaced by
+/// the argument and ,@rest-param by all the remaining arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Macro {
+    pub name: String,
+    params: Vec<String>,
+    rest_param: Option<String>,
+    templates: Vec<Form>,
+}
+
+enum Binding<'a> {
+    One(&'a SExp),
+    Rest(&'a [SExp]),
+}
+
+/// Parses the (defmacro ...) form.
+pub fn define_macro(form: &Form) -> Result<Macro, String> {
+    if form.exps.len() < 2 {
+        return Err("defmacro: name and parameter list required".to_string());
+    }
+    let name = match &form.exps[0] {
+        SExp::Symbol(Symbol::Sym(name)) => name.clone(),
+        exp => return Err(format!("defmacro: illegal macro name: {}", exp)),
+    };
+    let param_list = match &form.exps[1] {
+        SExp::Form(params) => params,
+        exp => {
+            return Err(format!(
+                "defmacro {}: parameter list expected, got {}",
+                name, exp
+            ));
+        }
+    };
+
+    let mut params = Vec::new();
+    let mut rest_param = None;
+    let mut items = form_items(param_list).into_iter();
+    while let Some(item) = items.next() {
+        let param = expect_param_name(&name, &item)?;
+        if param == REST_PARAM {
+            let rest = items
+                .next()
+                .ok_or_else(|| format!("defmacro {}: &rest needs a parameter", name))?;
+            rest_param = Some(expect_param_name(&name, &rest)?);
+            if let Some(more) = items.next() {
+                return Err(format!(
+                    "defmacro {}: no parameter allowed after the &rest parameter, got {}",
+                    name, more
+                ));
+            }
+        } else if params.contains(&param) {
+            return Err(format!("defmacro {}: duplicate parameter {}", name, param));
+        } else {
+            params.push(param);
+        }
+    }
+
+    let mut templates = Vec::new();
+    for exp in &form.exps[2..] {
+        match exp {
+            SExp::Quasiquote(template) => templates.push(template.clone()),
+            exp => {
+                return Err(format!(
+                    "defmacro {}: body must be quasiquoted forms, got {}",
+                    name, exp
+                ));
+            }
+        }
+    }
+
+    Ok(Macro {
+        name,
+        params,
+        rest_param,
+        templates,
+    })
+}
+
+fn expect_param_name(macro_name: &str, exp: &SExp) -> Result<String, String> {
+    match exp {
+        SExp::Symbol(Symbol::Sym(name)) => Ok(name.clone()),
+        exp => Err(format!(
+            "defmacro {}: illegal parameter {}",
+            macro_name, exp
+        )),
+    }
+}
+
+impl Macro {
+    /// Expands the call of the macro into the forms of its templates. The
+    /// forms keep the location of their template.
+    pub fn expand(&self, call: &Form) -> Result<Vec<Form>, String> {
+        let args = &call.exps;
+        let arity_ok = match self.rest_param {
+            Some(_) => args.len() >= self.params.len(),
+            None => args.len() == self.params.len(),
+        };
+        if !arity_ok {
+            return Err(format!(
+                "{}: expected {}{} arguments, got {}",
+                self.name,
+                if self.rest_param.is_some() {
+                    "at least "
+                } else {
+                    ""
+                },
+                self.params.len(),
+                args.len()
+            ));
+        }
+
+        let mut bindings = HashMap::new();
+        for (param, arg) in self.params.iter().zip(args) {
+            bindings.insert(param.as_str(), Binding::One(arg));
+        }
+        if let Some(rest) = &self.rest_param {
+            bindings.insert(rest.as_str(), Binding::Rest(&args[self.params.len()..]));
+        }
+
+        self.templates
+            .iter()
+            .map(|template| substitute_form(template, &bindings))
+            .collect::<Result<Vec<Form>, String>>()
+            .map_err(|e| format!("{}: {}", self.name, e))
+    }
+}
+
+fn substitute_form(form: &Form, bindings: &HashMap<&str, Binding>) -> Result<Form, String> {
+    let mut items = Vec::new();
+    for item in form_items(form) {
+        items.append(&mut substitute(&item, bindings)?);
+    }
+    let mut result = items_to_form(items);
+    result.span = form.span.clone();
+    Ok(result)
+}
+
+fn substitute(exp: &SExp, bindings: &HashMap<&str, Binding>) -> Result<Vec<SExp>, String> {
+    match exp {
+        SExp::Unquote(param) => match lookup_binding(param, bindings)? {
+            Binding::One(arg) => Ok(vec![(*arg).clone()]),
+            Binding::Rest(_) => Err(format!("rest parameter {} must be spliced with ,@", param)),
+        },
+        SExp::UnquoteSplicing(param) => match lookup_binding(param, bindings)? {
+            Binding::Rest(args) => Ok(args.to_vec()),
+            Binding::One(_) => Err(format!(
+                "only the rest parameter can be spliced, got {}",
+                param
+            )),
+        },
+        SExp::Form(form) => Ok(vec![SExp::Form(substitute_form(form, bindings)?)]),
+        SExp::Quasiquote(_) => Err("nested quasiquote not supported".to_string()),
+        exp => Ok(vec![exp.clone()]),
+    }
+}
+
+fn lookup_binding<'a>(
+    param: &SExp,
+    bindings: &'a HashMap<&str, Binding>,
+) -> Result<&'a Binding<'a>, String> {
+    match param {
+        SExp::Symbol(Symbol::Sym(name)) => bindings
+            .get(name.as_str())
+            .ok_or_else(|| format!("unquote of unknown parameter {}", name)),
+        exp => Err(format!("only parameters can be unquoted, got {}", exp)),
+    }
+}
+
+/// The label, operator and expressions of the form as one list, in the
+/// order they were written.
+fn form_items(form: &Form) -> Vec<SExp> {
+    let mut items = Vec::new();
+    if let Some(lbl) = &form.label {
+        items.push(SExp::Symbol(Symbol::Label(lbl.clone())));
+    }
+    if form.op != Symbol::Sym("".to_string()) {
+        items.push(SExp::Symbol(form.op.clone()));
+    }
+    items.extend(form.exps.iter().cloned());
+    items
+}
+
+/// Builds the form from the list of items as the parser would have built
+/// it from the written items: a leading label is the form label, the next
+/// symbol (or number) is the operator.
+fn items_to_form(mut items: Vec<SExp>) -> Form {
+    let mut label = None;
+    if let Some(SExp::Symbol(Symbol::Label(lbl))) = items.first() {
+        label = Some(lbl.clone());
+        items.remove(0);
+    }
+    let op = match items.first() {
+        Some(SExp::Symbol(sym)) => Some(sym.clone()),
+        Some(SExp::Immediate(val)) => Some(Symbol::Sym(val.to_string())),
+        _ => None,
+    };
+    let op = match op {
+        Some(op) => {
+            items.remove(0);
+            op
+        }
+        None => Symbol::Sym("".to_string()),
+    };
+    Form {
+        label,
+        op,
+        exps: items,
+        span: Default::default(),
+    }
+}
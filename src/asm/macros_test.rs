@@ -0,0 +1,150 @@
+use crate::asm::macros::define_macro;
+use crate::asm::parser::{Form, parse_from_string};
+use crate::error::Error;
+
+#[test]
+fn test_expand() -> Result<(), Error> {
+    let cases = [
+        (
+            "(defmacro wait-vblank () `('wait ldh %a (0xFF44)) `(cp 144) `(jr #nz 'wait))",
+            "(wait-vblank)",
+            "('wait ldh %a (0xFF44))\n(cp 144)\n(jr #nz 'wait)\n",
+        ),
+        (
+            "(defmacro load16 (reg value) `(ld ,reg ,value))",
+            "(load16 %hl ((+ 'data 2)))",
+            "(ld %hl ((+ 'data 2)))\n",
+        ),
+        // substituted into nested forms
+        (
+            "(defmacro store (addr) `(ld ((+ ,addr 1)) %a))",
+            "(store 'counter)",
+            "(ld ((+ 'counter 1)) %a)\n",
+        ),
+        (
+            "(defmacro bytes (first &rest more) `(db ,first ,@more 0))",
+            "(bytes 1 2 3)",
+            "(db 1 2 3 0)\n",
+        ),
+        (
+            "(defmacro bytes (first &rest more) `(db ,first ,@more 0))",
+            "(bytes 1)",
+            "(db 1 0)\n",
+        ),
+        // operator and label from the arguments
+        (
+            "(defmacro op-a (op value) `(,op %a ,value))",
+            "(op-a cp 10)",
+            "(cp %a 10)\n",
+        ),
+        (
+            "(defmacro at (lbl) `(,lbl nop))",
+            "(at 'start)",
+            "('start nop)\n",
+        ),
+        (
+            "(defmacro deref (addr) `(ld %a (,addr)))",
+            "(deref 0xFF40)",
+            "(ld %a (65344))\n",
+        ),
+    ];
+
+    for (def, call, expect) in cases {
+        let mac = define_macro(&must_parse_form(def)).map_err(Error::syntax)?;
+        let forms = mac.expand(&must_parse_form(call)).map_err(Error::syntax)?;
+        let got: String = forms.iter().map(|form| format!("{}\n", form)).collect();
+        assert_eq!(got, expect, "def={}, call={}", def, call);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_expand_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            "(defmacro two (a b) `(db ,a ,b))",
+            "(two 1)",
+            "two: expected 2 arguments, got 1",
+        ),
+        (
+            "(defmacro some (a &rest b) `(db ,a ,@b))",
+            "(some)",
+            "some: expected at least 1 arguments, got 0",
+        ),
+        (
+            "(defmacro m (a) `(db ,b))",
+            "(m 1)",
+            "m: unquote of unknown parameter b",
+        ),
+        (
+            "(defmacro m (a) `(db ,@a))",
+            "(m 1)",
+            "m: only the rest parameter can be spliced, got a",
+        ),
+        (
+            "(defmacro m (&rest a) `(db ,a))",
+            "(m 1)",
+            "m: rest parameter a must be spliced with ,@",
+        ),
+        (
+            "(defmacro m (a) `(db ,(+ a 1)))",
+            "(m 1)",
+            "m: only parameters can be unquoted, got (+ a 1)",
+        ),
+    ];
+
+    for (def, call, err) in cases {
+        let mac = define_macro(&must_parse_form(def)).map_err(Error::syntax)?;
+        assert_eq!(
+            mac.expand(&must_parse_form(call)).unwrap_err(),
+            err,
+            "def={}, call={}",
+            def,
+            call
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_define_macro_fails() -> Result<(), Error> {
+    let cases = [
+        ("(defmacro)", "defmacro: name and parameter list required"),
+        ("(defmacro m)", "defmacro: name and parameter list required"),
+        ("(defmacro 'm ())", "defmacro: illegal macro name: 'm"),
+        (
+            "(defmacro m a)",
+            "defmacro m: parameter list expected, got a",
+        ),
+        ("(defmacro m (%a))", "defmacro m: illegal parameter %a"),
+        ("(defmacro m (a a))", "defmacro m: duplicate parameter a"),
+        (
+            "(defmacro m (&rest))",
+            "defmacro m: &rest needs a parameter",
+        ),
+        (
+            "(defmacro m (&rest a b))",
+            "defmacro m: no parameter allowed after the &rest parameter, got b",
+        ),
+        (
+            "(defmacro m () (nop))",
+            "defmacro m: body must be quasiquoted forms, got (nop)",
+        ),
+    ];
+
+    for (def, err) in cases {
+        assert_eq!(
+            define_macro(&must_parse_form(def)).unwrap_err(),
+            err,
+            "def={}",
+            def
+        );
+    }
+    Ok(())
+}
+
+// helper
+
+fn must_parse_form(str: &str) -> Form {
+    parse_from_string(str).expect("parse").forms.remove(0)
+}
@@ -1,11 +1,38 @@
 pub mod assembler;
-mod interpreter;
-mod parser;
+pub(crate) mod encoder;
+pub(crate) mod interpreter;
+pub(crate) mod macros;
+pub mod object;
+pub(crate) mod parser;
+pub(crate) mod scope;
+pub(crate) mod stdlib;
+pub mod vfs;
 
 use crate::asm;
+use crate::asm::assembler::{Assembly, Environment};
+use crate::asm::vfs::FileSystem;
+use crate::error::Error;
 use std::fs::File;
 
-pub fn assemble_file(file: &mut File, options: asm::assembler::Options) -> Result<(), String> {
-    let tl = asm::parser::parse_from_file(file)?;
+/// Assembles the file, the name is used for the error locations.
+pub fn assemble_file(
+    file: &mut File,
+    name: &str,
+    options: asm::assembler::Options,
+) -> Result<(), Error> {
+    let tl = asm::parser::parse_from_file(file, name)?;
     asm::assembler::assemble(tl, options)
 }
+
+/// Assembles the source text into a flat image without writing any file,
+/// the included files are read from the file system. The name is used for
+/// the error locations and the includes relative to the source.
+pub fn assemble_source(
+    name: &str,
+    text: &str,
+    fs: &dyn FileSystem,
+    env: &Environment,
+) -> Result<Assembly, Error> {
+    let tl = asm::parser::parse_from_source(name, text)?;
+    asm::assembler::assemble_flat(tl, fs, env)
+}
@@ -0,0 +1,417 @@
+#[cfg(test)]
+#[path = "./object_test.rs"]
+mod object_test;
+
+use crate::asm::assembler::{
+    Floating, Region, expect_bool_sym, expect_immediate, expect_label_name, expect_region_name,
+    expect_section_name, key_value,
+};
+use crate::asm::parser::{Address, Form, Label, SExp, Symbol, parse_from_file, parse_from_string};
+use crate::error::{Error, ErrorKind};
+use std::fmt;
+use std::fs::File;
+
+pub const OBJECT_VERSION: i64 = 1;
+
+/// The number of bytes written per data form in the object file.
+const DATA_BYTES_PER_FORM: usize = 16;
+
+/// A relocatable object file. An object is the result of assembling without
+/// the flat option, it is written as (psy) s-expressions and contains the
+/// assembled sections, the defined labels and all not yet patched label references.
+#[derive(Debug, PartialEq)]
+pub struct Object {
+    pub sections: Vec<ObjectSection>,
+    /// the regions of the floating sections
+    pub regions: Vec<Region>,
+    pub symbols: Vec<ObjectSymbol>,
+    pub patches: Vec<ObjectPatch>,
+    /// the ROM has a gb-header, the linker computes the checksums
+    pub gb_header: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ObjectSection {
+    pub name: String,
+    pub offset: Address,
+    pub length: Option<u64>,
+    pub label_only: bool,
+    pub data: Vec<u8>,
+    /// the bytes reserved for the labels of a label-only section
+    pub reserved: u64,
+    /// the placement of a section without offset, the offset is 0 and the
+    /// linker places the section into its region
+    pub floating: Option<Floating>,
+    /// the alignment of the data, the linker places it at a multiple of it
+    pub data_align: u64,
+    /// the data must start at the section offset (org, gb-header), the
+    /// linker cannot place it after the data of other objects
+    pub pinned: bool,
+}
+
+/// A label definition. The offset is relative to the section start, or
+/// an absolute address if the label is not part of a section.
+#[derive(Debug, PartialEq)]
+pub struct ObjectSymbol {
+    pub label: Label,
+    pub section: Option<String>,
+    pub offset: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Patch {
+    /// the address expression is written as little endian 16-bit value
+    Absolute16(SExp),
+    /// the distance from the section relative offset to the target
+    /// address expression is written as a signed 8-bit value
+    Relative8(u64, SExp),
+    /// the address expression must be in the high page (0xFF00-0xFFFF),
+    /// the low byte is written
+    High8(SExp),
+    /// the expression is written as signed or unsigned 8-bit value
+    Immediate8(SExp),
+    /// the expression is written as signed or unsigned little endian
+    /// 16-bit value
+    Immediate16(SExp),
+}
+
+/// A patch site, the patch index is relative to the data of the section.
+#[derive(Debug, PartialEq)]
+pub struct ObjectPatch {
+    pub section: String,
+    pub patch_index: usize,
+    pub patch: Patch,
+}
+
+impl Patch {
+    /// The number of bytes the patch writes.
+    pub fn width(&self) -> usize {
+        match self {
+            Patch::Absolute16(_) | Patch::Immediate16(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl Object {
+    pub fn lookup_section(&self, name: &str) -> Option<&ObjectSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "(object :version {})", OBJECT_VERSION)?;
+        if self.gb_header {
+            writeln!(f, "(gb-header)")?;
+        }
+        for region in &self.regions {
+            write!(
+                f,
+                "(region {} :offset 0x{:X} :length 0x{:X}",
+                region.name, region.offset, region.length
+            )?;
+            if let Some(bank) = region.bank {
+                write!(f, " :bank 0x{:X}", bank)?;
+            }
+            writeln!(f, ")")?;
+        }
+        for sec in &self.sections {
+            match &sec.floating {
+                Some(floating) => {
+                    write!(
+                        f,
+                        "(section .{} :region {} :align 0x{:X}",
+                        sec.name, floating.region, floating.align
+                    )?;
+                    if let Some(bank) = floating.bank {
+                        write!(f, " :bank 0x{:X}", bank)?;
+                    }
+                }
+                None => write!(f, "(section .{} :offset 0x{:X}", sec.name, sec.offset.0)?,
+            }
+            if let Some(length) = sec.length {
+                write!(f, " :length 0x{:X}", length)?;
+            }
+            if sec.reserved > 0 {
+                write!(f, " :reserved 0x{:X}", sec.reserved)?;
+            }
+            if sec.data_align > 1 {
+                write!(f, " :data-align 0x{:X}", sec.data_align)?;
+            }
+            if sec.pinned {
+                write!(f, " :pinned true")?;
+            }
+            writeln!(f, " :label-only {})", sec.label_only)?;
+            for chunk in sec.data.chunks(DATA_BYTES_PER_FORM) {
+                write!(f, "(data .{}", sec.name)?;
+                for b in chunk {
+                    write!(f, " 0x{:02X}", b)?;
+                }
+                writeln!(f, ")")?;
+            }
+        }
+        for sym in &self.symbols {
+            match &sym.section {
+                Some(sec_name) => writeln!(
+                    f,
+                    "(symbol '{} .{} 0x{:X})",
+                    sym.label.name(),
+                    sec_name,
+                    sym.offset
+                )?,
+                None => writeln!(f, "(symbol '{} 0x{:X})", sym.label.name(), sym.offset)?,
+            }
+        }
+        for patch in &self.patches {
+            write!(f, "(patch .{} {} ", patch.section, patch.patch_index)?;
+            match &patch.patch {
+                Patch::Absolute16(exp) => writeln!(f, ":abs16 {})", exp)?,
+                Patch::Relative8(from, exp) => writeln!(f, ":rel8 {} 0x{:X})", exp, from)?,
+                Patch::High8(exp) => writeln!(f, ":high8 {})", exp)?,
+                Patch::Immediate8(exp) => writeln!(f, ":imm8 {})", exp)?,
+                Patch::Immediate16(exp) => writeln!(f, ":imm16 {})", exp)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn read_from_file(file: &mut File, name: &str) -> Result<Object, Error> {
+    let tl = parse_from_file(file, name)?;
+    read_forms(name, tl.forms)
+}
+
+pub fn read_from_string(str: &str) -> Result<Object, Error> {
+    let tl = parse_from_string(str)?;
+    read_forms("<string>", tl.forms)
+}
+
+/// Reads the object from the forms, errors are reported with the
+/// location of the form.
+fn read_forms(name: &str, forms: Vec<Form>) -> Result<Object, Error> {
+    let mut forms = forms.into_iter();
+    match forms.next() {
+        Some(header) if is_op(&header, "object") => {
+            read_header(&header).map_err(|e| object_error(e).at(&header.span))?
+        }
+        Some(form) => {
+            return Err(object_error("object: not a psy object file".to_string()).at(&form.span));
+        }
+        None => {
+            return Err(object_error(format!(
+                "{}: object: not a psy object file",
+                name
+            )));
+        }
+    }
+
+    let mut obj = Object {
+        sections: Vec::new(),
+        regions: Vec::new(),
+        symbols: Vec::new(),
+        patches: Vec::new(),
+        gb_header: false,
+    };
+    for form in forms {
+        let span = form.span.clone();
+        read_form(&mut obj, form).map_err(|e| object_error(e).at(&span))?;
+    }
+    Ok(obj)
+}
+
+fn object_error(message: String) -> Error {
+    Error::new(ErrorKind::Object, message)
+}
+
+fn read_header(header: &Form) -> Result<(), String> {
+    let version =
+        expect_immediate(key_value(&header.exps, "version")?.ok_or("object: version missing")?)?;
+    if version != OBJECT_VERSION {
+        return Err(format!("object: unsupported version {}", version));
+    }
+    Ok(())
+}
+
+fn read_form(obj: &mut Object, form: Form) -> Result<(), String> {
+    let op = match &form.op {
+        Symbol::Sym(op) => op.as_str(),
+        illegal => return Err(format!("object: illegal form: {:?}", illegal)),
+    };
+    match op {
+        "region" => obj.regions.push(read_region(&form)?),
+        "section" => obj.sections.push(read_section(&form)?),
+        "data" => read_data(obj, &form)?,
+        "symbol" => obj.symbols.push(read_symbol(&form)?),
+        "patch" => obj.patches.push(read_patch(form)?),
+        "gb-header" => obj.gb_header = true,
+        illegal => return Err(format!("object: unknown form: {}", illegal)),
+    }
+    Ok(())
+}
+
+fn read_region(form: &Form) -> Result<Region, String> {
+    let name = expect_region_name(form.exps.first().ok_or("object: region name missing")?)?;
+    let offset =
+        expect_unsigned(key_value(&form.exps, "offset")?.ok_or("object: offset missing")?)?;
+    let length =
+        expect_unsigned(key_value(&form.exps, "length")?.ok_or("object: length missing")?)?;
+    let bank = match key_value(&form.exps, "bank")? {
+        Some(exp) => Some(expect_unsigned(exp)?),
+        None => None,
+    };
+    Ok(Region {
+        name,
+        offset,
+        length,
+        bank,
+    })
+}
+
+fn read_section(form: &Form) -> Result<ObjectSection, String> {
+    let name = expect_section_name(form.exps.first().ok_or("object: section name missing")?)?;
+    let floating = match key_value(&form.exps, "region")? {
+        Some(exp) => {
+            let align = match key_value(&form.exps, "align")? {
+                Some(exp) => expect_unsigned(exp)?,
+                None => 1,
+            };
+            if align < 1 {
+                return Err("object: align must be at least 1".to_string());
+            }
+            let bank = match key_value(&form.exps, "bank")? {
+                Some(exp) => Some(expect_unsigned(exp)?),
+                None => None,
+            };
+            Some(Floating {
+                region: expect_region_name(exp)?,
+                align,
+                bank,
+            })
+        }
+        None => None,
+    };
+    let offset = match key_value(&form.exps, "offset")? {
+        Some(_) if floating.is_some() => {
+            return Err("object: a section has either an offset or a region".to_string());
+        }
+        Some(exp) => expect_unsigned(exp)?,
+        None if floating.is_some() => 0,
+        None => return Err("object: offset missing".to_string()),
+    };
+    let length = match key_value(&form.exps, "length")? {
+        Some(exp) => Some(expect_unsigned(exp)?),
+        None => None,
+    };
+    let reserved = match key_value(&form.exps, "reserved")? {
+        Some(exp) => expect_unsigned(exp)?,
+        None => 0,
+    };
+    let label_only = match key_value(&form.exps, "label-only")? {
+        Some(SExp::Symbol(sym)) => expect_bool_sym(sym)?,
+        _ => return Err("object: label-only missing".to_string()),
+    };
+    let data_align = match key_value(&form.exps, "data-align")? {
+        Some(exp) => expect_unsigned(exp)?,
+        None => 1,
+    };
+    if data_align < 1 {
+        return Err("object: data-align must be at least 1".to_string());
+    }
+    let pinned = match key_value(&form.exps, "pinned")? {
+        Some(SExp::Symbol(sym)) => expect_bool_sym(sym)?,
+        Some(exp) => return Err(format!("object: pinned must be a boolean, got {}", exp)),
+        None => false,
+    };
+    Ok(ObjectSection {
+        name,
+        offset: Address(offset),
+        length,
+        label_only,
+        data: Vec::new(),
+        reserved,
+        floating,
+        data_align,
+        pinned,
+    })
+}
+
+fn read_data(obj: &mut Object, form: &Form) -> Result<(), String> {
+    let name = expect_section_name(form.exps.first().ok_or("object: section name missing")?)?;
+    let sec = obj
+        .sections
+        .iter_mut()
+        .find(|sec| sec.name == name)
+        .ok_or(format!("object: data for undefined section {}", name))?;
+    for exp in &form.exps[1..] {
+        let v = expect_unsigned(exp)?;
+        if v > u8::MAX as u64 {
+            return Err(format!("object: illegal data byte {}", v));
+        }
+        sec.data.push(v as u8);
+    }
+    Ok(())
+}
+
+fn read_symbol(form: &Form) -> Result<ObjectSymbol, String> {
+    let label = expect_label_name(form.exps.first().ok_or("object: symbol label missing")?)?;
+    let (section, offset_exp) = match form.exps.get(1) {
+        Some(exp @ SExp::Symbol(Symbol::Section(_))) => {
+            (Some(expect_section_name(exp)?), form.exps.get(2))
+        }
+        exp => (None, exp),
+    };
+    let offset = expect_unsigned(offset_exp.ok_or("object: symbol offset missing")?)?;
+    Ok(ObjectSymbol {
+        label,
+        section,
+        offset,
+    })
+}
+
+fn read_patch(form: Form) -> Result<ObjectPatch, String> {
+    if form.exps.len() < 4 {
+        return Err(format!("object: illegal patch: {}", form));
+    }
+    let mut exps = form.exps.into_iter();
+    let section = expect_section_name(&exps.next().expect("section"))?;
+    let patch_index = expect_unsigned(&exps.next().expect("patch index"))? as usize;
+    let kind = exps.next().expect("patch kind");
+    let patch = match kind {
+        SExp::Symbol(Symbol::Keyword(kind)) if kind == "abs16" => {
+            Patch::Absolute16(exps.next().expect("patch expression"))
+        }
+        SExp::Symbol(Symbol::Keyword(kind)) if kind == "high8" => {
+            Patch::High8(exps.next().expect("patch expression"))
+        }
+        SExp::Symbol(Symbol::Keyword(kind)) if kind == "imm8" => {
+            Patch::Immediate8(exps.next().expect("patch expression"))
+        }
+        SExp::Symbol(Symbol::Keyword(kind)) if kind == "imm16" => {
+            Patch::Immediate16(exps.next().expect("patch expression"))
+        }
+        SExp::Symbol(Symbol::Keyword(kind)) if kind == "rel8" => {
+            let target = exps.next().expect("patch expression");
+            let from = expect_unsigned(&exps.next().ok_or("object: rel8 needs an offset")?)?;
+            Patch::Relative8(from, target)
+        }
+        illegal => return Err(format!("object: unknown patch kind {}", illegal)),
+    };
+    Ok(ObjectPatch {
+        section,
+        patch_index,
+        patch,
+    })
+}
+
+fn expect_unsigned(exp: &SExp) -> Result<u64, String> {
+    let v = expect_immediate(exp)?;
+    if v.is_negative() {
+        return Err(format!("object: negative value {}", v));
+    }
+    Ok(v as u64)
+}
+
+fn is_op(form: &Form, name: &str) -> bool {
+    matches!(&form.op, Symbol::Sym(op) if op == name)
+}
@@ -0,0 +1,158 @@
+use crate::asm::assembler::{Floating, Region};
+use crate::asm::object::{
+    Object, ObjectPatch, ObjectSection, ObjectSymbol, Patch, read_from_string,
+};
+use crate::asm::parser::{Address, Label, SExp, Symbol, parse_from_string};
+use crate::error::Error;
+
+#[test]
+fn test_object_write_read() -> Result<(), Error> {
+    let obj = Object {
+        sections: vec![
+            ObjectSection {
+                name: "rom0".to_string(),
+                offset: Address(0x150),
+                length: Some(0x4000),
+                label_only: false,
+                data: (0..20).collect(),
+                reserved: 0,
+                floating: None,
+                data_align: 1,
+                pinned: true,
+            },
+            ObjectSection {
+                name: "hw".to_string(),
+                offset: Address(0xFF00),
+                length: None,
+                label_only: true,
+                data: Vec::new(),
+                reserved: 0x48,
+                floating: None,
+                data_align: 1,
+                pinned: false,
+            },
+            ObjectSection {
+                name: "code".to_string(),
+                offset: Address(0),
+                length: None,
+                label_only: false,
+                data: vec![0xC9],
+                reserved: 0,
+                floating: Some(Floating {
+                    region: "romx".to_string(),
+                    align: 0x10,
+                    bank: Some(2),
+                }),
+                data_align: 0x8,
+                pinned: false,
+            },
+        ],
+        regions: vec![
+            Region {
+                name: "romx".to_string(),
+                offset: 0x4000,
+                length: 0x4000,
+                bank: Some(2),
+            },
+            Region {
+                name: "wram".to_string(),
+                offset: 0xC000,
+                length: 0x1000,
+                bank: None,
+            },
+        ],
+        symbols: vec![
+            ObjectSymbol {
+                label: Label::from_str("main"),
+                section: Some("rom0".to_string()),
+                offset: 2,
+            },
+            ObjectSymbol {
+                label: Label::from_str("abs"),
+                section: None,
+                offset: 0x666,
+            },
+        ],
+        patches: vec![
+            ObjectPatch {
+                section: "rom0".to_string(),
+                patch_index: 3,
+                patch: Patch::Absolute16(
+                    parse_from_string("(x (- 'lbl2 'lbl1))")?.forms[0]
+                        .exps
+                        .pop()
+                        .expect("exp"),
+                ),
+            },
+            ObjectPatch {
+                section: "rom0".to_string(),
+                patch_index: 7,
+                patch: Patch::Relative8(8, SExp::Symbol(Symbol::Label(Label::from_str("main")))),
+            },
+            ObjectPatch {
+                section: "rom0".to_string(),
+                patch_index: 9,
+                patch: Patch::Immediate8(
+                    parse_from_string("(x (hi (+ 'main 2)))")?.forms[0]
+                        .exps
+                        .pop()
+                        .expect("exp"),
+                ),
+            },
+        ],
+        gb_header: true,
+    };
+
+    let text = obj.to_string();
+    let read_obj = read_from_string(&text)?;
+    assert_eq!(read_obj, obj, "object text:\n{}", text);
+    Ok(())
+}
+
+#[test]
+fn test_object_read_fails() -> Result<(), Error> {
+    let cases = [
+        ("", "<string>: object: not a psy object file"),
+        (
+            "(section .rom0 :offset 0)",
+            "<string>:1:1: object: not a psy object file",
+        ),
+        (
+            "(object :version 2)",
+            "<string>:1:1: object: unsupported version 2",
+        ),
+        (
+            "(object :version 1) (data .rom0 1 2 3)",
+            "<string>:1:21: object: data for undefined section rom0",
+        ),
+        (
+            "(object :version 1) (section .rom0 :offset 0 :label-only false) (data .rom0 256)",
+            "<string>:1:65: object: illegal data byte 256",
+        ),
+        (
+            "(object :version 1) (section .rom0 :label-only false)",
+            "<string>:1:21: object: offset missing",
+        ),
+        (
+            "(object :version 1) (section .rom0 :offset 0 :region rom :label-only false)",
+            "<string>:1:21: object: a section has either an offset or a region",
+        ),
+        (
+            "(object :version 1) (section .rom0 :region rom :align 0 :label-only false)",
+            "<string>:1:21: object: align must be at least 1",
+        ),
+        (
+            "(object :version 1) (region rom :offset 0)",
+            "<string>:1:21: object: length missing",
+        ),
+    ];
+
+    for (text, err) in cases {
+        let r = read_from_string(text);
+        assert!(r.is_err(), "expected error '{}' on text = {:?}", err, text);
+        // only the location line, without the source excerpt
+        let got_err = r.unwrap_err().to_string();
+        assert_eq!(got_err.lines().next(), Some(err), "text={:?}", text);
+    }
+    Ok(())
+}
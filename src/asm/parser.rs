@@ -2,14 +2,17 @@
 #[path = "./parser_test.rs"]
 mod parser_test;
 
+use crate::error::{Error, ErrorKind};
 use core::iter::Iterator;
-use std::{fs::File, io::Read, iter::Peekable, str::Chars};
+use std::{fmt, fs::File, io::Read, iter::Peekable, str::Chars, sync::Arc};
 
 // special chars that cannot be part of a symbol
 const LPAREN: char = '(';
 const RPAREN: char = ')';
 const SEMICOLON: char = ';';
 const QUOTE: char = '"';
+const BACKQUOTE: char = '`';
+const COMMA: char = ',';
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct Label(String);
@@ -37,16 +40,22 @@ impl Address {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SExp {
     TopLevel(TopLevel),
     Symbol(Symbol),
     Form(Form),
     String(String),
     Immediate(i64),
+    /// `(..) in a macro body, a form template
+    Quasiquote(Form),
+    /// ,exp in a quasiquote, replaced by the macro argument
+    Unquote(Box<SExp>),
+    /// ,@exp in a quasiquote, replaced by all the rest arguments
+    UnquoteSplicing(Box<SExp>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Symbol {
     Keyword(String),     // : prefix
     Section(String),     // . prefix
@@ -57,55 +66,201 @@ pub enum Symbol {
     Sym(String),         //a-zA-Z only
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Form {
     pub label: Option<Label>,
     pub op: Symbol,
     pub exps: Vec<SExp>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq)]
+/// Forms are equal if they have the same structure, the span where
+/// they were parsed is ignored.
+impl PartialEq for Form {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label && self.op == other.op && self.exps == other.exps
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct TopLevel {
     pub forms: Vec<Form>,
 }
 
+/// A parsed source text, the name is the file name.
+#[derive(Default)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Source({})", self.name)
+    }
+}
+
+/// The location of a form in its source. Line and column start at 1.
+#[derive(Debug, Clone, Default)]
+pub struct Span {
+    pub source: Arc<Source>,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.source.name, self.line, self.col)
+    }
+}
+
+impl fmt::Display for SExp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SExp::TopLevel(tl) => write!(f, "{}", tl),
+            SExp::Symbol(sym) => write!(f, "{}", sym),
+            SExp::Form(form) => write!(f, "{}", form),
+            SExp::String(str) => write!(f, "\"{}\"", str),
+            SExp::Immediate(val) => write!(f, "{}", val),
+            SExp::Quasiquote(form) => write!(f, "`{}", form),
+            SExp::Unquote(exp) => write!(f, ",{}", exp),
+            SExp::UnquoteSplicing(exp) => write!(f, ",@{}", exp),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbol::Keyword(name) => write!(f, ":{}", name),
+            Symbol::Section(name) => write!(f, ".{}", name),
+            Symbol::Reg(name) => write!(f, "%{}", name),
+            Symbol::Label(lbl) => write!(f, "'{}", lbl.name()),
+            Symbol::Flag(name) => write!(f, "#{}", name),
+            Symbol::GameboyTile(tile) => write!(f, "`{}", tile),
+            Symbol::Sym(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Writes the form back in its (psy) text representation, parsing
+/// the output yields the same form again.
+impl fmt::Display for Form {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(lbl) = &self.label {
+            parts.push(format!("'{}", lbl.name()));
+        }
+        let op = self.op.to_string();
+        if !op.is_empty() {
+            parts.push(op);
+        }
+        for exp in &self.exps {
+            parts.push(exp.to_string());
+        }
+        write!(f, "({})", parts.join(" "))
+    }
+}
+
+impl fmt::Display for TopLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for form in &self.forms {
+            writeln!(f, "{}", form)?;
+        }
+        Ok(())
+    }
+}
+
 /*
 S => SExp*
 SExp => (SExp) | Symbol
 Symbol => Ascii-char*
 */
-pub fn parse_from_file(file: &mut File) -> Result<TopLevel, String> {
+pub fn parse_from_file(file: &mut File, name: &str) -> Result<TopLevel, Error> {
     let mut buf = String::new();
-    file.read_to_string(&mut buf).map_err(|e| e.to_string())?;
-    parse(&mut buf.chars().peekable())
+    file.read_to_string(&mut buf)
+        .map_err(|e| Error::io(name, e))?;
+    parse_from_source(name, &buf)
+}
+
+pub fn parse_from_string(str: &str) -> Result<TopLevel, Error> {
+    parse_from_source("<string>", str)
+}
+
+/// Parses the source text, the name is used for the error locations.
+pub fn parse_from_source(name: &str, text: &str) -> Result<TopLevel, Error> {
+    parse(&mut Cursor::new(name, text))
+}
+
+/// The chars of the source that is parsed, keeps track of the line
+/// and column of the next char.
+#[derive(Clone)]
+struct Cursor<'a> {
+    source: Arc<Source>,
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(name: &str, text: &'a str) -> Cursor<'a> {
+        Cursor {
+            source: Arc::new(Source {
+                name: name.to_string(),
+                text: text.to_string(),
+            }),
+            chars: text.chars().peekable(),
+            line: 1,
+            col: 1,
+        }
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    fn span(&self) -> Span {
+        Span {
+            source: self.source.clone(),
+            line: self.line,
+            col: self.col,
+        }
+    }
 }
 
-pub fn parse_from_string(str: &str) -> Result<TopLevel, String> {
-    parse(&mut str.chars().peekable())
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(ch)
+    }
 }
 
-fn parse(chars: &mut Peekable<Chars>) -> Result<TopLevel, String> {
+/// Parses all forms, an error is located where the parsing stopped.
+fn parse(chars: &mut Cursor) -> Result<TopLevel, Error> {
+    parse_forms(chars).map_err(|e| Error::new(ErrorKind::Parse, e).at(&chars.span()))
+}
+
+fn parse_forms(chars: &mut Cursor) -> Result<TopLevel, String> {
     let mut forms = Vec::new();
     loop {
         if skip_whitespace_and_comment(chars)? {
             break;
         }
-
-        let may_form = parse_form(chars);
-        if may_form.is_ok() {
-            forms.push(may_form.unwrap());
-        } else {
-            return Err(format!(
-                "error parsing form #{}: {:?}",
-                forms.len() + 1,
-                may_form.err()
-            ));
-        }
+        forms.push(parse_form(chars)?);
     }
     Ok(TopLevel { forms })
 }
 
-fn parse_form(chars: &mut Peekable<Chars>) -> Result<Form, String> {
+fn parse_form(chars: &mut Cursor) -> Result<Form, String> {
+    let span = chars.span();
     expect(chars, '(')?;
 
     if skip_whitespace_and_comment(chars)? {
@@ -128,27 +283,60 @@ fn parse_form(chars: &mut Peekable<Chars>) -> Result<Form, String> {
         let la = chars.peek();
         match la {
             None => return Err("unexpected form end".to_string()),
-            Some(&LPAREN) => exps.push(SExp::Form(parse_form(chars)?)),
-            Some(&QUOTE) => exps.push(SExp::String(parse_string(chars)?)),
             Some(&RPAREN) => {
                 chars.advance_by(1).map_err(|e| e.to_string())?;
                 break 'parse;
             }
             Some(&SEMICOLON) => skip_line_comment(chars)?,
-            Some(ch) => {
-                if ch.is_numeric() {
-                    exps.push(SExp::Immediate(parse_immediate(chars)?))
-                } else {
-                    exps.push(SExp::Symbol(parse_symbol(chars)?))
-                }
-            }
+            Some(_) => exps.push(parse_exp(chars)?),
         };
     }
 
-    Ok(Form { label, op, exps })
+    Ok(Form {
+        label,
+        op,
+        exps,
+        span,
+    })
+}
+
+fn parse_exp(chars: &mut Cursor) -> Result<SExp, String> {
+    let la = match chars.peek() {
+        None => return Err("unexpected form end".to_string()),
+        Some(ch) => *ch,
+    };
+    match la {
+        LPAREN => Ok(SExp::Form(parse_form(chars)?)),
+        QUOTE => Ok(SExp::String(parse_string(chars)?)),
+        COMMA => {
+            chars.advance_by(1).map_err(|e| e.to_string())?;
+            if chars.peek() == Some(&'@') {
+                chars.advance_by(1).map_err(|e| e.to_string())?;
+                Ok(SExp::UnquoteSplicing(Box::new(parse_exp(chars)?)))
+            } else {
+                Ok(SExp::Unquote(Box::new(parse_exp(chars)?)))
+            }
+        }
+        _ if is_quasiquote_start(chars) => {
+            chars.advance_by(1).map_err(|e| e.to_string())?;
+            Ok(SExp::Quasiquote(parse_form(chars)?))
+        }
+        _ if la.is_numeric() => Ok(SExp::Immediate(parse_immediate(chars)?)),
+        _ if is_negative_number_start(chars) => {
+            chars.advance_by(1).map_err(|e| e.to_string())?;
+            Ok(SExp::Immediate(-parse_immediate(chars)?))
+        }
+        _ => {
+            let sym = parse_symbol(chars)?;
+            if sym == Symbol::Sym("".to_string()) {
+                return Err(format!("unexpected char: {}", la));
+            }
+            Ok(SExp::Symbol(sym))
+        }
+    }
 }
 
-fn parse_symbol(chars: &mut Peekable<Chars>) -> Result<Symbol, String> {
+fn parse_symbol(chars: &mut Cursor) -> Result<Symbol, String> {
     let mut sym = String::new();
 
     let may_first_char = chars.peek();
@@ -202,8 +390,38 @@ fn parse_symbol(chars: &mut Peekable<Chars>) -> Result<Symbol, String> {
     }
 }
 
+/// Parses a number literal (decimal, hex or binary) that was read as a symbol,
+/// e.g. the address in a deref form (0xFF40).
+pub(crate) fn parse_number(str: &str) -> Result<i64, String> {
+    let mut chars = Cursor::new("", str);
+    let v = parse_immediate(&mut chars)?;
+    if chars.peek().is_some() {
+        return Err(format!("illegal number: {}", str));
+    }
+    Ok(v)
+}
+
+/// A '`' directly followed by a '(' starts a quasiquote, otherwise it
+/// is the prefix of a gameboy tile.
+fn is_quasiquote_start(chars: &Cursor) -> bool {
+    let mut la = chars.clone();
+    la.next() == Some(BACKQUOTE) && la.next() == Some(LPAREN)
+}
+
+/// A '-' directly followed by a digit starts a negative number, a '-'
+/// on its own is the subtraction symbol.
+fn is_negative_number_start(chars: &Cursor) -> bool {
+    let mut la = chars.clone();
+    la.next() == Some('-') && la.next().is_some_and(|ch| ch.is_numeric())
+}
+
 fn is_sym_char(ch: char) -> bool {
-    !ch.is_whitespace() && ch != LPAREN && ch != RPAREN && ch != SEMICOLON && ch != QUOTE
+    !ch.is_whitespace()
+        && ch != LPAREN
+        && ch != RPAREN
+        && ch != SEMICOLON
+        && ch != QUOTE
+        && ch != COMMA
 }
 
 #[derive(PartialEq)]
@@ -213,7 +431,7 @@ enum ImmediateType {
     Binary,
 }
 
-fn parse_immediate(chars: &mut Peekable<Chars>) -> Result<i64, String> {
+fn parse_immediate(chars: &mut Cursor) -> Result<i64, String> {
     let mut immediate = String::new();
 
     let may_first_num = chars.next();
@@ -230,7 +448,7 @@ fn parse_immediate(chars: &mut Peekable<Chars>) -> Result<i64, String> {
     let may_second_num = chars.peek();
     let mut immediate_type = ImmediateType::Decimal;
     match may_second_num {
-        None => return Ok(parse_number_value(&immediate, ImmediateType::Decimal)?),
+        None => return parse_number_value(&immediate, ImmediateType::Decimal),
         Some(ch) => {
             match *ch {
                 'x' => {
@@ -293,7 +511,7 @@ fn parse_number_value(str: &str, immediate_type: ImmediateType) -> Result<i64, S
     }
 }
 
-fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+fn parse_string(chars: &mut Cursor) -> Result<String, String> {
     expect(chars, '"')?;
     let mut literal = String::new();
     loop {
@@ -310,7 +528,7 @@ fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
 
 // helper
 
-fn expect(chars: &mut Peekable<Chars>, ch: char) -> Result<(), String> {
+fn expect(chars: &mut Cursor, ch: char) -> Result<(), String> {
     if let Some(next_char) = chars.next() {
         if next_char == ch {
             return Ok(());
@@ -321,7 +539,7 @@ fn expect(chars: &mut Peekable<Chars>, ch: char) -> Result<(), String> {
 }
 
 /// Returns true if EOF is reached
-fn skip_whitespace_and_comment(chars: &mut Peekable<Chars>) -> Result<bool, String> {
+fn skip_whitespace_and_comment(chars: &mut Cursor) -> Result<bool, String> {
     loop {
         let la = chars.peek();
         match la {
@@ -339,7 +557,7 @@ fn skip_whitespace_and_comment(chars: &mut Peekable<Chars>) -> Result<bool, Stri
     }
 }
 
-fn skip_line_comment(chars: &mut Peekable<Chars>) -> Result<(), String> {
+fn skip_line_comment(chars: &mut Cursor) -> Result<(), String> {
     expect(chars, ';')?;
     loop {
         let next = chars.next();
@@ -352,10 +570,7 @@ fn skip_line_comment(chars: &mut Peekable<Chars>) -> Result<(), String> {
 }
 
 fn is_label(sym: &Symbol) -> bool {
-    match sym {
-        Symbol::Label(_) => true,
-        _ => false,
-    }
+    matches!(sym, Symbol::Label(_))
 }
 
 fn sym_get_label(sym: Symbol) -> Result<Label, String> {
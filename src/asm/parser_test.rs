@@ -1,18 +1,22 @@
-use std::{fs::File, iter::Peekable, str::Chars};
+use std::fs::File;
 
-use crate::asm::parser::{Label, SExp, Symbol, parse, parse_from_file, parse_symbol};
+use crate::asm::parser::{
+    Cursor, Label, SExp, Symbol, parse, parse_from_file, parse_from_string, parse_symbol,
+};
+use crate::error::Error;
 
 #[test]
-fn test_parse_test_file() -> Result<(), String> {
-    let mut f = File::open("testdata/asm/test.asm").map_err(|e| e.to_string())?;
-    let tl = parse_from_file(&mut f)?;
+fn test_parse_test_file() -> Result<(), Error> {
+    let mut f =
+        File::open("testdata/asm/test.asm").map_err(|e| Error::io("testdata/asm/test.asm", e))?;
+    let tl = parse_from_file(&mut f, "test.asm")?;
 
     assert_eq!(tl.forms.len(), 13);
     Ok(())
 }
 
 #[test]
-fn test_parse_label_form() -> Result<(), String> {
+fn test_parse_label_form() -> Result<(), Error> {
     let tl = parse(&mut chars("('value1 db)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, Some(Label("value1".to_string())));
@@ -24,7 +28,7 @@ fn test_parse_label_form() -> Result<(), String> {
 /// Special case form, same as the explicit (label 'lbl).
 /// The only form allowed with an operator.
 #[test]
-fn test_parse_label_only_form() -> Result<(), String> {
+fn test_parse_label_only_form() -> Result<(), Error> {
     let tl = parse(&mut chars("('lbl)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, Some(Label("lbl".to_string())));
@@ -34,12 +38,14 @@ fn test_parse_label_only_form() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_immediate_values() -> Result<(), String> {
+fn test_parse_immediate_values() -> Result<(), Error> {
     // 'db' is a bit arbitrary, but the easiest for an immediate value test
     let cases = [
         ("(db 42)", 42),
         ("(db 0x42)", 66),
         ("(db 0b1010011010", 666),
+        ("(db -42)", -42),
+        ("(db -0x80)", -128),
     ];
 
     for (exp, val) in cases {
@@ -54,7 +60,20 @@ fn test_parse_immediate_values() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_gameboy_tile_values() -> Result<(), String> {
+fn test_parse_minus_symbol() -> Result<(), Error> {
+    let tl = parse(&mut chars("(- 'a 'b)"))?;
+    assert_eq!(tl.forms[0].op, Symbol::Sym("-".to_string()));
+
+    let tl = parse(&mut chars("(%hl -)"))?;
+    assert_eq!(
+        tl.forms[0].exps,
+        vec![SExp::Symbol(Symbol::Sym("-".to_string()))]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_gameboy_tile_values() -> Result<(), Error> {
     let cases = [("(dw `00112233)", "00112233")];
 
     for (exp, val) in cases {
@@ -72,7 +91,7 @@ fn test_parse_gameboy_tile_values() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_include() -> Result<(), String> {
+fn test_parse_include() -> Result<(), Error> {
     let tl = parse(&mut chars("(include \"gb_dma\")"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, None);
@@ -83,7 +102,7 @@ fn test_parse_include() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_def_constant() -> Result<(), String> {
+fn test_parse_def_constant() -> Result<(), Error> {
     let tl = parse(&mut chars("(def-constant +const+ 1)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, None);
@@ -98,7 +117,7 @@ fn test_parse_def_constant() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_shift_left() -> Result<(), String> {
+fn test_parse_shift_left() -> Result<(), Error> {
     let tl = parse(&mut chars("(<< 1 +const+)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, None);
@@ -114,7 +133,7 @@ fn test_parse_shift_left() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_deref_reg() -> Result<(), String> {
+fn test_parse_deref_reg() -> Result<(), Error> {
     let tl = parse(&mut chars("(%hl)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, None);
@@ -123,7 +142,7 @@ fn test_parse_deref_reg() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_ld_deref_immediate() -> Result<(), String> {
+fn test_parse_ld_deref_immediate() -> Result<(), Error> {
     let tl = parse(&mut chars("(ld (%hl) 1)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, None);
@@ -132,7 +151,7 @@ fn test_parse_ld_deref_immediate() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_jr_conditional_nz() -> Result<(), String> {
+fn test_parse_jr_conditional_nz() -> Result<(), Error> {
     let tl = parse(&mut chars("(jr #nz 'lbl)"))?;
     assert_eq!(tl.forms.len(), 1);
     assert_eq!(tl.forms[0].label, None);
@@ -150,7 +169,7 @@ fn test_parse_jr_conditional_nz() -> Result<(), String> {
 }
 
 #[test]
-fn test_parse_symbol() -> Result<(), String> {
+fn test_parse_symbol() -> Result<(), Error> {
     let cases = [
         ("", Symbol::Sym("".to_string())), //empty symbol is a special case, but allowed in places
         (")", Symbol::Sym("".to_string())),
@@ -166,15 +185,91 @@ fn test_parse_symbol() -> Result<(), String> {
     ];
 
     for (exp, symbol) in cases {
-        let parsed_symbol = parse_symbol(&mut chars(exp))?;
+        let parsed_symbol = parse_symbol(&mut chars(exp)).map_err(Error::syntax)?;
         assert_eq!(parsed_symbol, symbol, "exp: {}", exp);
     }
 
     Ok(())
 }
 
+#[test]
+fn test_parse_quasiquote() -> Result<(), Error> {
+    let tl = parse(&mut chars(
+        "(defmacro fill (dst &rest bytes) `(ld %hl ,dst) `(db ,@bytes `0123))",
+    ))?;
+    assert_eq!(tl.forms.len(), 1);
+    let exps = &tl.forms[0].exps;
+    assert_eq!(exps.len(), 4);
+
+    let ld = match &exps[2] {
+        SExp::Quasiquote(form) => form,
+        exp => panic!("quasiquote expected, got {:?}", exp),
+    };
+    assert_eq!(ld.op, Symbol::Sym("ld".to_string()));
+    assert_eq!(
+        ld.exps[1],
+        SExp::Unquote(Box::new(SExp::Symbol(Symbol::Sym("dst".to_string()))))
+    );
+
+    // a ` followed by a char other than ( is still a tile
+    let db = match &exps[3] {
+        SExp::Quasiquote(form) => form,
+        exp => panic!("quasiquote expected, got {:?}", exp),
+    };
+    assert_eq!(
+        db.exps,
+        vec![
+            SExp::UnquoteSplicing(Box::new(SExp::Symbol(Symbol::Sym("bytes".to_string())))),
+            SExp::Symbol(Symbol::GameboyTile("0123".to_string()))
+        ]
+    );
+
+    // an unquote in the operator position
+    let tl = parse(&mut chars("(x `(,op %a))"))?;
+    let form = match &tl.forms[0].exps[0] {
+        SExp::Quasiquote(form) => form,
+        exp => panic!("quasiquote expected, got {:?}", exp),
+    };
+    assert_eq!(form.op, Symbol::Sym("".to_string()));
+    assert_eq!(form.to_string(), "(,op %a)");
+    Ok(())
+}
+
+#[test]
+fn test_parse_spans() -> Result<(), Error> {
+    let tl = parse(&mut chars("(nop)\n  ; comment\n  ('lbl\n ld %a (+ 1 2))"))?;
+    assert_eq!(tl.forms.len(), 2);
+    assert_eq!(tl.forms[0].span.source.name, "test.asm");
+    assert_eq!((tl.forms[0].span.line, tl.forms[0].span.col), (1, 1));
+    assert_eq!((tl.forms[1].span.line, tl.forms[1].span.col), (3, 3));
+    match &tl.forms[1].exps[1] {
+        SExp::Form(form) => assert_eq!((form.span.line, form.span.col), (4, 8)),
+        exp => panic!("form expected, got {:?}", exp),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_error_location() -> Result<(), Error> {
+    let err = parse_from_string("(nop)\n(db 1 0xG)")
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "<string>:2:9: invalid hex immediate: \n  (db 1 0xG)\n          ^"
+    );
+
+    let err = parse_from_string("(db \"text)").unwrap_err().to_string();
+    assert!(
+        err.starts_with("<string>:1:11: unexpected end of string"),
+        "err={}",
+        err
+    );
+    Ok(())
+}
+
 // helper
 
-fn chars(str: &'static str) -> Peekable<Chars<'static>> {
-    str.chars().peekable()
+fn chars(str: &'static str) -> Cursor<'static> {
+    Cursor::new("test.asm", str)
 }
@@ -0,0 +1,176 @@
+#[cfg(test)]
+#[path = "./scope_test.rs"]
+mod scope_test;
+
+use crate::asm::parser::{Form, Label, SExp, Symbol};
+
+const LOCAL_PREFIX: char = '.';
+const ANONYMOUS: &str = "@";
+
+/// Returns true for the labels that are private to one object, the names
+/// generated for anonymous labels and the local labels of (scope ...).
+pub fn is_private_label(label: &Label) -> bool {
+    label.name().starts_with(ANONYMOUS)
+}
+
+/// The label scopes while assembling. The written labels are replaced by
+/// unique names:
+///
+/// 'name     a global label, it is the scope of the following local labels
+/// '.name    a local label, named name.local after the scope
+/// '@        an anonymous label
+/// '@- '@--  a reference to the previous, the second previous anonymous label
+/// '@+ '@++  a reference to the next, the second next anonymous label
+///
+/// A (scope ...) form and every macro expansion is a scope of its own.
+#[derive(Debug)]
+pub struct Scopes {
+    /// the name of the local labels scope, innermost last
+    frames: Vec<Option<String>>,
+    /// the number of scopes so far, for the scope names
+    scope_count: usize,
+    /// the number of anonymous labels defined so far
+    anonymous_count: usize,
+}
+
+impl Scopes {
+    pub fn new() -> Scopes {
+        Scopes {
+            frames: vec![None],
+            scope_count: 0,
+            anonymous_count: 0,
+        }
+    }
+
+    /// Enters a new unnamed scope.
+    pub fn push(&mut self) {
+        self.frames
+            .push(Some(format!("{}scope{}", ANONYMOUS, self.scope_count)));
+        self.scope_count += 1;
+    }
+
+    /// Leaves the scope, the enclosing scope is restored.
+    pub fn pop(&mut self) {
+        if self.frames.len() > 1 {
+            self.frames.pop();
+        }
+    }
+
+    /// Returns the unique name of the label defined at this point.
+    pub fn define(&mut self, label: &Label) -> Result<Label, String> {
+        let name = label.name();
+        if name == ANONYMOUS {
+            let anonymous = Label::from_string(format!("{}{}", ANONYMOUS, self.anonymous_count));
+            self.anonymous_count += 1;
+            return Ok(anonymous);
+        }
+        if name.starts_with(LOCAL_PREFIX) {
+            return self.local(label);
+        }
+        check_global_name(label)?;
+        // qualified local labels, e.g. 'copy.loop, do not open a scope
+        if !name.contains(LOCAL_PREFIX) {
+            *self.frames.last_mut().expect("top-level scope") = Some(name.to_string());
+        }
+        Ok(label.clone())
+    }
+
+    /// Returns the unique name of the label referenced at this point.
+    pub fn resolve(&self, label: &Label) -> Result<Label, String> {
+        let name = label.name();
+        if let Some(direction) = name.strip_prefix(ANONYMOUS) {
+            return self.anonymous(label, direction);
+        }
+        if name.starts_with(LOCAL_PREFIX) {
+            return self.local(label);
+        }
+        Ok(label.clone())
+    }
+
+    /// Replaces the labels referenced in the expression. Quasiquoted forms
+    /// are templates, their labels are replaced after the expansion.
+    pub fn resolve_exp(&self, exp: SExp) -> Result<SExp, String> {
+        match exp {
+            SExp::Symbol(Symbol::Label(label)) => {
+                Ok(SExp::Symbol(Symbol::Label(self.resolve(&label)?)))
+            }
+            SExp::Form(form) => Ok(SExp::Form(self.resolve_form(form)?)),
+            exp => Ok(exp),
+        }
+    }
+
+    /// Replaces the labels referenced in the form. The label of a nested
+    /// form is a reference, e.g. the address in (ld %a ('data)).
+    pub fn resolve_form(&self, mut form: Form) -> Result<Form, String> {
+        if let Some(label) = &form.label {
+            form.label = Some(self.resolve(label)?);
+        }
+        form.exps = form
+            .exps
+            .into_iter()
+            .map(|exp| self.resolve_exp(exp))
+            .collect::<Result<Vec<SExp>, String>>()?;
+        Ok(form)
+    }
+
+    fn local(&self, label: &Label) -> Result<Label, String> {
+        if label.name().len() == 1 {
+            return Err("illegal label name: '.".to_string());
+        }
+        match self.frames.last().expect("top-level scope") {
+            Some(scope) => Ok(Label::from_string(format!("{}{}", scope, label.name()))),
+            None => Err(format!(
+                "local label '{} outside of a scope, define a global label or use (scope ...) before",
+                label.name()
+            )),
+        }
+    }
+
+    fn anonymous(&self, label: &Label, direction: &str) -> Result<Label, String> {
+        let steps = direction.len();
+        if direction.is_empty() {
+            Err("the anonymous label '@ can only be referenced with '@- or '@+".to_string())
+        } else if direction.chars().all(|ch| ch == '-') {
+            if steps > self.anonymous_count {
+                return Err(format!(
+                    "'{}: only {} anonymous labels defined before",
+                    label.name(),
+                    self.anonymous_count
+                ));
+            }
+            Ok(Label::from_string(format!(
+                "{}{}",
+                ANONYMOUS,
+                self.anonymous_count - steps
+            )))
+        } else if direction.chars().all(|ch| ch == '+') {
+            Ok(Label::from_string(format!(
+                "{}{}",
+                ANONYMOUS,
+                self.anonymous_count + steps - 1
+            )))
+        } else {
+            Err(reserved_name(label))
+        }
+    }
+}
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Scopes::new()
+    }
+}
+
+fn check_global_name(label: &Label) -> Result<(), String> {
+    if label.name().starts_with(ANONYMOUS) {
+        return Err(reserved_name(label));
+    }
+    Ok(())
+}
+
+fn reserved_name(label: &Label) -> String {
+    format!(
+        "illegal label name: '{}, names starting with @ are reserved for anonymous labels",
+        label.name()
+    )
+}
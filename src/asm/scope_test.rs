@@ -0,0 +1,71 @@
+use crate::asm::parser::Label;
+use crate::asm::scope::{Scopes, is_private_label};
+
+#[test]
+fn test_scopes() -> Result<(), String> {
+    let mut scopes = Scopes::new();
+    assert_eq!(scopes.resolve(&lbl("start"))?, lbl("start"));
+
+    assert_eq!(scopes.define(&lbl("copy"))?, lbl("copy"));
+    assert_eq!(scopes.define(&lbl(".loop"))?, lbl("copy.loop"));
+    assert_eq!(scopes.resolve(&lbl(".loop"))?, lbl("copy.loop"));
+    // a qualified local label keeps the scope
+    assert_eq!(scopes.define(&lbl("copy.end"))?, lbl("copy.end"));
+    assert_eq!(scopes.resolve(&lbl(".end"))?, lbl("copy.end"));
+
+    scopes.push();
+    assert_eq!(scopes.resolve(&lbl(".loop"))?, lbl("@scope0.loop"));
+    assert_eq!(scopes.define(&lbl("inner"))?, lbl("inner"));
+    assert_eq!(scopes.resolve(&lbl(".loop"))?, lbl("inner.loop"));
+    scopes.pop();
+    assert_eq!(scopes.resolve(&lbl(".loop"))?, lbl("copy.loop"));
+    scopes.push();
+    assert_eq!(scopes.resolve(&lbl(".loop"))?, lbl("@scope1.loop"));
+    scopes.pop();
+
+    assert_eq!(scopes.resolve(&lbl("@+"))?, lbl("@0"));
+    assert_eq!(scopes.resolve(&lbl("@++"))?, lbl("@1"));
+    assert_eq!(scopes.define(&lbl("@"))?, lbl("@0"));
+    assert_eq!(scopes.define(&lbl("@"))?, lbl("@1"));
+    assert_eq!(scopes.resolve(&lbl("@-"))?, lbl("@1"));
+    assert_eq!(scopes.resolve(&lbl("@--"))?, lbl("@0"));
+    assert_eq!(scopes.resolve(&lbl("@+"))?, lbl("@2"));
+
+    assert!(is_private_label(&lbl("@0")));
+    assert!(is_private_label(&lbl("@scope0.loop")));
+    assert!(!is_private_label(&lbl("copy.loop")));
+    Ok(())
+}
+
+#[test]
+fn test_scopes_fails() {
+    let mut scopes = Scopes::new();
+    assert_eq!(
+        scopes.resolve(&lbl(".loop")).unwrap_err(),
+        "local label '.loop outside of a scope, define a global label or use (scope ...) before"
+    );
+    assert_eq!(
+        scopes.resolve(&lbl("@-")).unwrap_err(),
+        "'@-: only 0 anonymous labels defined before"
+    );
+    assert_eq!(
+        scopes.resolve(&lbl("@")).unwrap_err(),
+        "the anonymous label '@ can only be referenced with '@- or '@+"
+    );
+    assert_eq!(
+        scopes.define(&lbl("@x")).unwrap_err(),
+        "illegal label name: '@x, names starting with @ are reserved for anonymous labels"
+    );
+    assert_eq!(
+        scopes.resolve(&lbl("@-+")).unwrap_err(),
+        "illegal label name: '@-+, names starting with @ are reserved for anonymous labels"
+    );
+    assert_eq!(
+        scopes.define(&lbl(".")).unwrap_err(),
+        "illegal label name: '."
+    );
+}
+
+fn lbl(name: &str) -> Label {
+    Label::from_str(name)
+}
@@ -0,0 +1,16 @@
+/// The name of the embedded stdlib in the error locations, the embedded
+/// files are named <stdlib>/gb/dma.asm.
+pub const EMBEDDED_STDLIB: &str = "<stdlib>";
+
+/// The stdlib files compiled into the binary, (include :std ...) reads them
+/// if no stdlib directory is given.
+const FILES: [(&str, &str); 1] = [("gb/dma.asm", include_str!("../../stdlib/gb/dma.asm"))];
+
+/// Returns the text of the embedded stdlib file, the path is relative to
+/// the stdlib directory.
+pub fn embedded(path: &str) -> Option<&'static str> {
+    FILES
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, text)| *text)
+}
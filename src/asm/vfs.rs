@@ -0,0 +1,68 @@
+#[cfg(test)]
+#[path = "./vfs_test.rs"]
+mod vfs_test;
+
+use std::collections::HashMap;
+use std::io;
+
+/// The files `include` and `incbin` read from. Build scripts and tests can
+/// assemble without touching the disk by passing a `MemoryFileSystem`.
+pub trait FileSystem {
+    fn read_to_string(&self, path: &str) -> io::Result<String>;
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+}
+
+/// Reads the files from the disk, relative paths are relative to the
+/// working directory.
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+}
+
+/// Files kept in memory, looked up by their exact path.
+#[derive(Debug, Default)]
+pub struct MemoryFileSystem {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> MemoryFileSystem {
+        MemoryFileSystem::default()
+    }
+
+    /// Adds the file, an existing file with the same path is replaced.
+    pub fn insert(&mut self, path: impl Into<String>, text: impl Into<String>) {
+        self.files.insert(path.into(), text.into().into_bytes());
+    }
+
+    /// Adds the binary file, an existing file with the same path is replaced.
+    pub fn insert_bytes(&mut self, path: impl Into<String>, data: impl Into<Vec<u8>>) {
+        self.files.insert(path.into(), data.into());
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_to_string(&self, path: &str) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No such file or directory"))
+    }
+}
@@ -0,0 +1,34 @@
+use crate::asm::vfs::{FileSystem, MemoryFileSystem, OsFileSystem};
+use std::io;
+
+#[test]
+fn test_memory_file_system() {
+    let mut fs = MemoryFileSystem::new();
+    fs.insert("lib/macros.asm", "(nop)");
+    assert_eq!(fs.read_to_string("lib/macros.asm").unwrap(), "(nop)");
+
+    fs.insert("lib/macros.asm", "(halt)");
+    assert_eq!(fs.read_to_string("lib/macros.asm").unwrap(), "(halt)");
+
+    let err = fs.read_to_string("macros.asm").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    fs.insert_bytes("gfx/font.2bpp", [0x00, 0xFF, 0x80]);
+    assert_eq!(fs.read("gfx/font.2bpp").unwrap(), vec![0x00, 0xFF, 0x80]);
+    let err = fs.read_to_string("gfx/font.2bpp").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(fs.read("lib/macros.asm").unwrap(), b"(halt)");
+}
+
+#[test]
+fn test_os_file_system() {
+    let text = OsFileSystem
+        .read_to_string("testdata/asm/include_error.asm")
+        .unwrap();
+    assert!(text.contains("(ld %a %sp)"));
+
+    let err = OsFileSystem
+        .read_to_string("testdata/asm/missing.asm")
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
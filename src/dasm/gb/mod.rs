@@ -2,30 +2,51 @@
 #[path = "./mod_test.rs"]
 mod mod_test;
 
-use crate::arch::sm83::{self, Sm83Instr};
+use crate::arch::sm83::{self, Sm83Instr, Sm83PrefixInstr};
+use crate::error::Error;
 
 pub struct GBDisInstr {
     pub offset: usize, // offset into the original byte sequence that produced this disassembly
     pub len: usize,    // length of the instruction in bytes
     pub instr: &'static Sm83Instr,
+    pub prefix_instr: Option<&'static Sm83PrefixInstr>, // the decoded instruction after a 0xCB prefix
 }
 
 pub struct GBDisassembly {
     pub instructions: Vec<GBDisInstr>, // ordered by location in the orginal binary
 }
 
-pub fn disassemble(data: &[u8]) -> Result<GBDisassembly, String> {
+impl GBDisInstr {
+    /// The (psy) text representation of the instruction. The data is the
+    /// original byte sequence that produced this disassembly, it is
+    /// located at address 0x0000 for the targets of jr.
+    pub fn text(&self, data: &[u8]) -> String {
+        match self.prefix_instr {
+            Some(prefix_instr) => prefix_instr.text(),
+            None => self.instr.text_at(&data[self.offset..], self.offset as u64),
+        }
+    }
+}
+
+pub fn disassemble(data: &[u8]) -> Result<GBDisassembly, Error> {
     let mut instructions = Vec::new();
     let mut ip = 0;
     while ip < data.len() {
         let start_ip = ip;
         let instr = sm83::decode(data[start_ip]);
+        let prefix_instr = if instr.op_code == sm83::INSTR_PREFIX.op_code {
+            data.get(start_ip + 1)
+                .map(|prefix_op| sm83::decode_prefix(*prefix_op))
+        } else {
+            None
+        };
         ip += instr.len();
         instructions.push(GBDisInstr {
             offset: start_ip,
-            len: ip - start_ip,
+            len: ip.min(data.len()) - start_ip,
             instr,
+            prefix_instr,
         });
     }
-    return Ok(GBDisassembly { instructions });
+    Ok(GBDisassembly { instructions })
 }
@@ -1,12 +1,60 @@
+use crate::asm::assemble_source;
+use crate::asm::assembler::Environment;
+use crate::asm::vfs::MemoryFileSystem;
 use crate::dasm::gb::disassemble;
+use crate::error::Error;
 
 #[test]
-fn test_disassemble() -> Result<(), String> {
-    let cases = [(vec!["(ld %a 0)"], vec![0x3E, 0x00])];
+fn test_disassemble() -> Result<(), Error> {
+    let cases = [
+        (vec!["(ld %a 0x0)"], vec![0x3E, 0x00]),
+        (vec!["(swap %a)"], vec![0xCB, 0x37]),
+        (
+            vec!["(bit 7 (%hl))", "(res 0 %b)", "(set 3 %e)", "(srl %a)"],
+            vec![0xCB, 0x7E, 0xCB, 0x80, 0xCB, 0xDB, 0xCB, 0x3F],
+        ),
+        (
+            vec!["(rlc %b)", "(jp 0x150)", "(sla (%hl))"],
+            vec![0xCB, 0x00, 0xC3, 0x50, 0x01, 0xCB, 0x26],
+        ),
+        // jr targets are addresses, the data starts at 0x0000
+        (
+            vec!["(nop)", "(jr #nz 0x0)", "(jr 0x9)"],
+            vec![0x00, 0x20, 0xFD, 0x18, 0x04],
+        ),
+    ];
     for (texts, bytes) in cases {
         let dis = disassemble(&bytes)?;
         assert_eq!(dis.instructions.len(), texts.len());
+        for (dis_instr, text) in dis.instructions.iter().zip(texts) {
+            assert_eq!(dis_instr.text(&bytes), text);
+        }
     }
 
     Ok(())
 }
+
+#[test]
+fn test_disassemble_reassembles() -> Result<(), Error> {
+    let bytes = [0x00, 0x20, 0xFD, 0x18, 0x7F, 0x38, 0xF9, 0xCB, 0x37];
+    let dis = disassemble(&bytes)?;
+    let texts: Vec<String> = dis.instructions.iter().map(|i| i.text(&bytes)).collect();
+    let src = format!(
+        "(def-section .code :offset 0x0) (section .code) {}",
+        texts.join(" ")
+    );
+    let fs = MemoryFileSystem::new();
+    let assembly = assemble_source("main.asm", &src, &fs, &Environment::default())?;
+    assert_eq!(assembly.image, bytes);
+    Ok(())
+}
+
+#[test]
+fn test_disassemble_truncated_prefix() -> Result<(), Error> {
+    let bytes = [0x00, 0xCB];
+    let dis = disassemble(&bytes)?;
+    assert_eq!(dis.instructions.len(), 2);
+    assert_eq!(dis.instructions[1].len, 1);
+    assert!(dis.instructions[1].prefix_instr.is_none());
+    Ok(())
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+#[path = "./error_test.rs"]
+mod error_test;
+
+use crate::asm::parser::Span;
+use std::fmt;
+
+/// The kind of an error, for tools that need to tell errors apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// reading or writing a file failed
+    Io,
+    /// the source text is not a valid s-expression
+    Parse,
+    /// a form has an illegal operator or illegal arguments
+    Syntax,
+    /// a label is used but never defined
+    UndefinedLabel,
+    /// a constant is used but never defined
+    UndefinedConstant,
+    /// a label is defined more than once
+    DuplicateLabel,
+    /// a value does not fit its operand, address or jump distance
+    Range,
+    /// an object file is malformed
+    Object,
+    /// the objects cannot be linked together
+    Link,
+    /// the data cannot be disassembled
+    Disassemble,
+}
+
+/// The error of all psy library functions. The span locates the form
+/// that caused the error, the notes add context (e.g. the include).
+#[derive(Debug, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Error {
+        Error {
+            kind,
+            message: message.into(),
+            span: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn io(name: &str, e: std::io::Error) -> Error {
+        Error::new(ErrorKind::Io, format!("{}: {}", name, e))
+    }
+
+    /// A form with an illegal operator or illegal arguments.
+    pub(crate) fn syntax(message: impl Into<String>) -> Error {
+        Error::new(ErrorKind::Syntax, message)
+    }
+
+    /// Locates the error at the span, an already located error keeps
+    /// its span.
+    pub fn at(mut self, span: &Span) -> Error {
+        if self.span.is_none() {
+            self.span = Some(span.clone());
+        }
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Error {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Prefixes the message, e.g. with the name of the form.
+    pub(crate) fn prefixed(mut self, prefix: &str) -> Error {
+        self.message = format!("{}: {}", prefix, self.message);
+        self
+    }
+}
+
+/// Writes the location and an excerpt of the source line with a caret
+/// at the column, followed by the notes:
+///
+/// file.asm:2:1: ld: illegal arguments ...
+///   (ld %a %sp)
+///   ^
+///   note: included from main.asm:1:1
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => {
+                write!(f, "{}: {}", span, self.message)?;
+                let line = span.source.text.lines().nth(span.line.saturating_sub(1));
+                if let Some(line) = line {
+                    let indent: String = line
+                        .chars()
+                        .take(span.col.saturating_sub(1))
+                        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                        .collect();
+                    write!(f, "\n  {}\n  {}^", line, indent)?;
+                }
+            }
+            None => write!(f, "{}", self.message)?,
+        }
+        for note in &self.notes {
+            write!(f, "\n  note: {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,27 @@
+use crate::asm::parser::parse_from_string;
+use crate::error::{Error, ErrorKind};
+
+#[test]
+fn test_display() -> Result<(), Error> {
+    let err = Error::new(ErrorKind::Range, "jr: max 127 jumps forward, was 130");
+    assert_eq!(err.to_string(), "jr: max 127 jumps forward, was 130");
+
+    let tl = parse_from_string("(nop)\n  (jr 'far)")?;
+    let err = err.at(&tl.forms[1].span).with_note("'far is at 0x200");
+    assert_eq!(
+        err.to_string(),
+        "<string>:2:3: jr: max 127 jumps forward, was 130\n    (jr 'far)\n    ^\n  note: 'far is at 0x200"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_at_keeps_first_span() -> Result<(), Error> {
+    let tl = parse_from_string("(nop)\n(nop)")?;
+    let err = Error::syntax("illegal")
+        .at(&tl.forms[1].span)
+        .at(&tl.forms[0].span);
+    assert_eq!(err.kind, ErrorKind::Syntax);
+    assert_eq!(err.span.map(|span| span.line), Some(2));
+    Ok(())
+}
@@ -3,3 +3,7 @@
 pub mod arch;
 pub mod asm;
 pub mod dasm;
+pub mod error;
+pub mod link;
+
+pub use error::{Error, ErrorKind};
@@ -0,0 +1,37 @@
+#[cfg(test)]
+#[path = "./mod_test.rs"]
+mod mod_test;
+
+use crate::arch::gb::fix_checksums;
+use crate::asm::object::Object;
+use crate::error::{Error, ErrorKind};
+
+/// Links the objects and returns the ROM image. The gaps between the sections
+/// and the unused bytes of the sections are filled with the fill byte.
+pub fn link(objects: &[Object], fill: u8) -> Result<Vec<u8>, Error> {
+    let mut linked = crate::link::link(objects)?;
+    linked.sections.sort_by_key(|section| section.offset);
+
+    let mut rom = Vec::new();
+    for sec in &linked.sections {
+        if sec.label_only {
+            continue;
+        }
+
+        // a section without length is as long as its data
+        let length = sec.length.unwrap_or(sec.data.len() as u64);
+        if (rom.len() as u64) > sec.offset.0 {
+            return Err(Error::new(
+                ErrorKind::Link,
+                format!("link: section .{} overlaps previous section", sec.name),
+            ));
+        }
+        rom.resize(sec.offset.0 as usize, fill);
+        rom.extend_from_slice(&sec.data);
+        rom.resize((sec.offset.0 + length) as usize, fill);
+    }
+    if linked.gb_header {
+        fix_checksums(&mut rom).map_err(|e| Error::new(ErrorKind::Link, format!("link: {}", e)))?;
+    }
+    Ok(rom)
+}
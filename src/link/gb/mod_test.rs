@@ -0,0 +1,406 @@
+use crate::asm::object::read_from_string;
+use crate::error::{Error, ErrorKind};
+use crate::link::gb::link;
+
+static OBJ_MAIN: &str = "
+(object :version 1)
+(section .header :offset 0x2 :length 0x4 :label-only false)
+(data .header 0xC3 0x00 0x00)
+(section .rom0 :offset 0x6 :length 0x8 :label-only false)
+(data .rom0 0x18 0x00 0xCD 0x00 0x00)
+(symbol 'start .rom0 0x0)
+(patch .header 1 :abs16 'start)
+(patch .rom0 1 :rel8 'start 0x2)
+(patch .rom0 3 :abs16 'helper)
+";
+
+static OBJ_HELPER: &str = "
+(object :version 1)
+(section .rom0 :offset 0x6 :length 0x8 :label-only false)
+(data .rom0 0x00 0xC9)
+(symbol 'helper .rom0 0x1)
+";
+
+#[test]
+fn test_link_ok() -> Result<(), Error> {
+    let objects = [read_from_string(OBJ_MAIN)?, read_from_string(OBJ_HELPER)?];
+    let rom = link(&objects, 0)?;
+    assert_eq!(
+        rom,
+        vec![
+            0x00, 0x00, // gap before .header
+            0xC3, 0x06, 0x00, 0x00, // .header
+            0x18, 0xFE, 0xCD, 0x0C, 0x00, // .rom0 from main
+            0x00, 0xC9, // .rom0 from helper
+            0x00, // .rom0 fill
+        ]
+    );
+
+    let rom = link(&objects, 0xFF)?;
+    assert_eq!(rom[..2], [0xFF, 0xFF]);
+    assert_eq!(rom[13], 0xFF);
+    Ok(())
+}
+
+#[test]
+fn test_link_fails() -> Result<(), Error> {
+    let cases = [
+        (
+            vec![OBJ_MAIN],
+            "undefined label: 'helper",
+            ErrorKind::UndefinedLabel,
+        ),
+        (
+            vec![OBJ_MAIN, OBJ_HELPER, OBJ_HELPER],
+            "link: section .rom0 overflows, length 0x8 but 0x9 bytes defined",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                OBJ_HELPER,
+                "(object :version 1)
+                 (section .rom0 :offset 0x6 :length 0x8 :label-only false)
+                 (data .rom0 0xC9)
+                 (symbol 'helper .rom0 0x0)",
+            ],
+            "link: duplicate label definition: 'helper",
+            ErrorKind::DuplicateLabel,
+        ),
+        (
+            vec![
+                OBJ_HELPER,
+                "(object :version 1)
+                 (section .rom0 :offset 0x7 :length 0x8 :label-only false)",
+            ],
+            "link: conflicting definitions for section .rom0",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (section .rom0 :offset 0x6 :length 0x8 :label-only false)
+                 (data .rom0 0xC3)
+                 (patch .rom0 5 :imm8 1)",
+            ],
+            "link: patch at index 5 beyond the data of section .rom0 (0x1 bytes)",
+            ErrorKind::Object,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (section .rom0 :offset 0x6 :length 0x8 :label-only false)
+                 (data .rom0 0xC3 0x00)
+                 (patch .rom0 1 :abs16 0x100)",
+            ],
+            "link: patch at index 1 beyond the data of section .rom0 (0x2 bytes)",
+            ErrorKind::Object,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (region ram :offset 0xC000 :length 0x10)
+                 (section .ram :region ram :align 0x1 :length 0x2 :reserved 0x2 :label-only true)
+                 (symbol 'x .ram 0x0)",
+                "(object :version 1)
+                 (section .ram :region ram :align 0x1 :length 0x2 :reserved 0x1 :label-only true)
+                 (symbol 'y .ram 0x0)",
+            ],
+            "link: section .ram overflows, length 0x2 but 0x3 bytes reserved",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (section .hw :offset 0xFF00 :reserved 0x3 :label-only true)
+                 (symbol 'hw-sc .hw 0x2)",
+                "(object :version 1)
+                 (section .hw :offset 0xFF00 :reserved 0x5 :label-only true)
+                 (symbol 'hw-div .hw 0x4)",
+            ],
+            "link: conflicting definitions for label-only section .hw\n  \
+             note: a section with a :region places the labels of the objects one after the other",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (section .rom0 :offset 0x0 :label-only false)
+                 (data .rom0 0x00)
+                 (symbol 'init .rom0 0x0)",
+                "(object :version 1)
+                 (section .rom0 :offset 0x0 :label-only false)
+                 (data .rom0 0xC9)
+                 (symbol 'init .rom0 0x0)",
+            ],
+            "link: duplicate label definition: 'init",
+            ErrorKind::DuplicateLabel,
+        ),
+        (
+            vec![
+                "(object :version 1) (region rom :offset 0x10 :length 0x10)",
+                "(object :version 1) (region rom :offset 0x10 :length 0x20)",
+            ],
+            "link: conflicting definitions for region rom",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (region rom :offset 0x10 :length 0x4)
+                 (section .a :region rom :align 0x1 :label-only false)
+                 (data .a 0x01 0x02 0x03)",
+                "(object :version 1)
+                 (section .a :region rom :align 0x1 :label-only false)
+                 (data .a 0x04 0x05)",
+            ],
+            "link: section .a (0x5 bytes, align 1) does not fit into region rom",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (region rom :offset 0x10 :length 0x4)
+                 (section .a :region rom :align 0x1 :label-only false)",
+                "(object :version 1)
+                 (section .a :region rom :align 0x2 :label-only false)",
+            ],
+            "link: conflicting definitions for section .a",
+            ErrorKind::Link,
+        ),
+        (
+            vec![
+                "(object :version 1)
+                 (section .rom0 :offset 0x0 :label-only false)
+                 (data .rom0 0x01)",
+                "(object :version 1)
+                 (section .rom0 :offset 0x0 :pinned true :label-only false)
+                 (data .rom0 0x02)",
+            ],
+            "link: section .rom0 must start at its offset (org or gb-header), \
+             but follows the data of other objects\n  \
+             note: link the object first or use a section of its own",
+            ErrorKind::Link,
+        ),
+        (
+            vec!["(object :version 1) (gb-header)", OBJ_HELPER],
+            "link: the rom of 0xE bytes ends before the header end 0x0150",
+            ErrorKind::Link,
+        ),
+    ];
+
+    for (texts, err, kind) in cases {
+        let mut objects = Vec::new();
+        for text in texts {
+            objects.push(read_from_string(text)?);
+        }
+        let r = link(&objects, 0);
+        assert!(r.is_err(), "expected error '{}'", err);
+        let got_err = r.unwrap_err();
+        assert_eq!(got_err.to_string(), err);
+        assert_eq!(got_err.kind, kind, "err={}", err);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_link_identical_definitions() -> Result<(), Error> {
+    // both objects define the same label-only section and label, as
+    // it happens if both include the same stdlib definitions
+    let obj = "
+        (object :version 1)
+        (section .hw :offset 0xFF00 :label-only true)
+        (symbol 'hw-joyp .hw 0x0)
+        (symbol 'hw-lcdc .hw 0x40)
+    ";
+    let objects = [read_from_string(obj)?, read_from_string(obj)?];
+    let linked = crate::link::link(&objects)?;
+    assert_eq!(linked.symbols.len(), 2);
+    assert_eq!(linked.symbols[1].label.name(), "hw-lcdc");
+    assert_eq!(linked.symbols[1].offset, 0xFF40);
+    Ok(())
+}
+
+#[test]
+fn test_link_label_only_reservations() -> Result<(), Error> {
+    // the variables of the objects follow each other, the fixed register
+    // definitions are shared, b defines a register more than a
+    let a = "(object :version 1)
+             (region ram :offset 0xC000 :length 0x10)
+             (section .hw :offset 0xFF00 :reserved 0x3 :label-only true)
+             (symbol 'hw-joyp .hw 0x0)
+             (symbol 'hw-sc .hw 0x2)
+             (section .ram :region ram :align 0x1 :reserved 0x2 :label-only true)
+             (symbol 'x .ram 0x0)";
+    let b = "(object :version 1)
+             (section .hw :offset 0xFF00 :reserved 0x41 :label-only true)
+             (symbol 'hw-joyp .hw 0x0)
+             (symbol 'hw-sc .hw 0x2)
+             (symbol 'hw-lcdc .hw 0x40)
+             (section .ram :region ram :align 0x1 :reserved 0x1 :label-only true)
+             (symbol 'y .ram 0x0)";
+    let objects = [read_from_string(a)?, read_from_string(b)?];
+    let linked = crate::link::link(&objects)?;
+    let address = |name: &str| {
+        linked
+            .symbols
+            .iter()
+            .find(|sym| sym.label.name() == name)
+            .map(|sym| sym.offset)
+    };
+    assert_eq!(address("x"), Some(0xC000));
+    assert_eq!(address("y"), Some(0xC002));
+    assert_eq!(address("hw-sc"), Some(0xFF02));
+    assert_eq!(address("hw-lcdc"), Some(0xFF40));
+    Ok(())
+}
+
+#[test]
+fn test_link_floating_sections() -> Result<(), Error> {
+    // both objects float a section into the same region, the linker
+    // places them one after the other
+    let a = "(object :version 1)
+             (region rom :offset 0x10 :length 0x10)
+             (section .header :offset 0x0 :length 0x3 :label-only false)
+             (data .header 0xC3 0x00 0x00)
+             (patch .header 1 :abs16 'b)
+             (section .a :region rom :align 0x1 :label-only false)
+             (data .a 0x18 0x00 0xC9)
+             (symbol 'a .a 0x0)
+             (patch .a 1 :rel8 'a 0x2)
+             (section .vars :region ram :align 0x1 :reserved 0x1 :label-only true)
+             (symbol 'x .vars 0x0)";
+    let b = "(object :version 1)
+             (region rom :offset 0x10 :length 0x10)
+             (region ram :offset 0xC000 :length 0x10)
+             (section .b :region rom :align 0x4 :label-only false)
+             (data .b 0xCD 0x00 0x00)
+             (symbol 'b .b 0x0)
+             (patch .b 1 :abs16 'a)
+             (section .vars :region ram :align 0x1 :reserved 0x2 :label-only true)
+             (symbol 'y .vars 0x0)";
+    let objects = [read_from_string(a)?, read_from_string(b)?];
+    let rom = link(&objects, 0)?;
+    let mut expected = vec![0xC3, 0x14, 0x00]; // .header
+    expected.resize(0x10, 0x00);
+    expected.extend([0x18, 0xFE, 0xC9, 0x00]); // .a, aligned .b follows
+    expected.extend([0xCD, 0x10, 0x00]); // .b
+    assert_eq!(rom, expected);
+
+    let linked = crate::link::link(&objects)?;
+    let address = |name: &str| {
+        linked
+            .symbols
+            .iter()
+            .find(|sym| sym.label.name() == name)
+            .map(|sym| sym.offset)
+    };
+    assert_eq!(address("x"), Some(0xC000));
+    assert_eq!(address("y"), Some(0xC001));
+    Ok(())
+}
+
+#[test]
+fn test_link_align_and_org() -> Result<(), Error> {
+    // b aligned its data to 4, the pinned a must come first
+    let a = "(object :version 1)
+             (section .rom0 :offset 0x0 :pinned true :label-only false)
+             (data .rom0 0x01 0x02 0x03)";
+    let b = "(object :version 1)
+             (section .rom0 :offset 0x0 :data-align 0x4 :label-only false)
+             (data .rom0 0xC3 0x00 0x00)
+             (symbol 'b .rom0 0x0)
+             (patch .rom0 1 :abs16 'b)";
+    let objects = [read_from_string(a)?, read_from_string(b)?];
+    let rom = link(&objects, 0)?;
+    assert_eq!(rom, vec![0x01, 0x02, 0x03, 0x00, 0xC3, 0x04, 0x00]);
+    Ok(())
+}
+
+#[test]
+fn test_link_expression_patches() -> Result<(), Error> {
+    let obj = "
+        (object :version 1)
+        (section .rom0 :offset 0x0 :length 0x6 :label-only false)
+        (data .rom0 0x3E 0x00 0x21 0x00 0x00)
+        (symbol 'table .rom0 0x5)
+        (patch .rom0 1 :imm8 (lo (+ 'table 0x100)))
+        (patch .rom0 3 :abs16 (+ 'table (* 2 3)))
+    ";
+    let rom = link(&[read_from_string(obj)?], 0)?;
+    assert_eq!(rom, vec![0x3E, 0x05, 0x21, 0x0B, 0x00, 0x00]);
+
+    // a data word may be a negative distance
+    let obj = "
+        (object :version 1)
+        (section .rom0 :offset 0x0 :length 0x3 :label-only false)
+        (data .rom0 0x00 0x00 0x00)
+        (symbol 'start .rom0 0x0)
+        (symbol 'end .rom0 0x2)
+        (patch .rom0 0 :imm16 (- 'start 'end))
+    ";
+    let rom = link(&[read_from_string(obj)?], 0)?;
+    assert_eq!(rom, vec![0xFE, 0xFF, 0x00]);
+
+    let obj = "
+        (object :version 1)
+        (section .rom0 :offset 0x0 :length 0x2 :label-only false)
+        (data .rom0 0x3E 0x00)
+        (symbol 'far .rom0 0x1)
+        (patch .rom0 1 :imm8 (+ 'far 0x200))
+    ";
+    let err = link(&[read_from_string(obj)?], 0).unwrap_err();
+    assert_eq!(err.to_string(), "value 513 out of range -128..255");
+    assert_eq!(err.kind, ErrorKind::Range);
+    Ok(())
+}
+
+#[test]
+fn test_link_private_labels() -> Result<(), Error> {
+    // both objects define the anonymous label @0, each patch uses its own
+    let obj = |offset| {
+        format!(
+            "(object :version 1)
+             (section .rom0 :offset 0x0 :length 0x8 :label-only false)
+             (data .rom0 {} 0xC3 0x00 0x00)
+             (symbol '@0 .rom0 0x0)
+             (patch .rom0 {} :abs16 '@0)",
+            vec!["0x00"; offset].join(" "),
+            offset + 1
+        )
+    };
+    let objects = [read_from_string(&obj(1))?, read_from_string(&obj(0))?];
+    let rom = link(&objects, 0)?;
+    assert_eq!(rom, vec![0x00, 0xC3, 0x00, 0x00, 0xC3, 0x04, 0x00, 0x00]);
+
+    // not visible to the other object
+    let objects = [
+        read_from_string(&obj(0))?,
+        read_from_string(
+            "(object :version 1)
+             (section .rom0 :offset 0x0 :length 0x8 :label-only false)
+             (data .rom0 0xC3 0x00 0x00)
+             (patch .rom0 1 :abs16 '@0)",
+        )?,
+    ];
+    let err = link(&objects, 0).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::UndefinedLabel);
+    Ok(())
+}
+
+#[test]
+fn test_link_gb_header_checksums() -> Result<(), Error> {
+    let header = "
+        (object :version 1)
+        (gb-header)
+        (section .header :offset 0x100 :length 0x50 :label-only false)
+    ";
+    let code = "
+        (object :version 1)
+        (section .rom0 :offset 0x150 :length 0x2 :label-only false)
+        (data .rom0 0x18 0xFE)
+    ";
+    let rom = link(&[read_from_string(header)?, read_from_string(code)?], 0)?;
+    assert_eq!(rom.len(), 0x152);
+    assert_eq!(rom[0x14D..0x150], [0xE7, 0x01, 0xFD]);
+    Ok(())
+}
@@ -0,0 +1,383 @@
+pub mod gb;
+
+use crate::asm::assembler::{
+    Block, Region, check_8_bit_value, check_16_bit_address_range, check_16_bit_value,
+    check_high_page_address, check_jr_jump, find_free_block, lcm,
+};
+use crate::asm::interpreter::eval;
+use crate::asm::object::{Object, ObjectSection, ObjectSymbol, Patch};
+use crate::asm::parser::{Address, Label};
+use crate::asm::scope::is_private_label;
+use crate::error::{Error, ErrorKind};
+use std::collections::HashMap;
+
+/// Where the section data of one object ended up in the linked section.
+struct Placement {
+    /// relative to the start of the linked section
+    base: u64,
+    data_index: usize,
+}
+
+/// The labels an object defines in a label-only section, with their offsets.
+type Reservation<'a> = (u64, Vec<(&'a Label, u64)>);
+
+/// Links the objects into one object with all patches applied.
+///
+/// Sections with the same name are merged, the section data of the objects
+/// is placed one after the other in the order of the objects. All symbols of
+/// the result are absolute. A label may be defined in more than one object if
+/// all definitions have the same address, this allows every object to include
+/// the same definitions (e.g. from the stdlib). The anonymous and scope local
+/// labels are private, they are only visible to the patches of their object.
+///
+/// A label-only section with an offset describes fixed memory, e.g. the
+/// hardware registers. The objects share it, the labels of one object must
+/// be a subset or a superset of the labels of the others. The reserved bytes
+/// of a floating label-only section are placed one after the other, unless
+/// an object reserves the same bytes for the same labels as an object before.
+///
+/// The data of an object starts at a multiple of its data alignment (from
+/// align), padded with 0. Pinned data (from org or gb-header) is only
+/// valid at the section offset, it must come before the data of the other
+/// objects.
+///
+/// The floating sections are placed once they are merged, in the order they
+/// appear in the objects, into the first free block of their region.
+pub fn link(objects: &[Object]) -> Result<Object, Error> {
+    let regions = link_regions(objects)?;
+    let mut sections: Vec<ObjectSection> = Vec::new();
+    // placements[object_ix][section name]
+    let mut placements: Vec<HashMap<&str, Placement>> = Vec::new();
+    // the reservations in the floating label-only sections so far, by section name
+    let mut reservations: HashMap<&str, Vec<(Reservation, u64)>> = HashMap::new();
+    // the labels of the fixed label-only sections, by section name
+    let mut memory_maps: HashMap<&str, Vec<(&Label, u64)>> = HashMap::new();
+
+    for obj in objects {
+        let mut obj_placements = HashMap::new();
+        for sec in &obj.sections {
+            let linked = match sections.iter_mut().find(|s| s.name == sec.name) {
+                Some(linked) => {
+                    if linked.offset != sec.offset
+                        || linked.length != sec.length
+                        || linked.label_only != sec.label_only
+                        || linked.floating != sec.floating
+                    {
+                        return Err(Error::new(
+                            ErrorKind::Link,
+                            format!("link: conflicting definitions for section .{}", sec.name),
+                        ));
+                    }
+                    linked
+                }
+                None => {
+                    sections.push(ObjectSection {
+                        name: sec.name.clone(),
+                        offset: sec.offset,
+                        length: sec.length,
+                        label_only: sec.label_only,
+                        data: Vec::new(),
+                        reserved: 0,
+                        floating: sec.floating.clone(),
+                        data_align: 1,
+                        pinned: sec.pinned,
+                    });
+                    sections.last_mut().expect("pushed section")
+                }
+            };
+
+            if sec.pinned && !linked.data.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::Link,
+                    format!(
+                        "link: section .{} must start at its offset (org or gb-header), \
+                         but follows the data of other objects",
+                        sec.name
+                    ),
+                )
+                .with_note("link the object first or use a section of its own"));
+            }
+            linked.data_align = lcm(linked.data_align, sec.data_align);
+            let padded = linked.data.len().next_multiple_of(sec.data_align as usize);
+            linked.data.resize(padded, 0);
+            let data_index = linked.data.len();
+            let base = if linked.label_only && linked.floating.is_none() {
+                let symbols = section_symbols(obj, &sec.name);
+                let known = memory_maps.entry(sec.name.as_str()).or_default();
+                if is_subset(known, &symbols) {
+                    *known = symbols;
+                } else if !is_subset(&symbols, known) {
+                    return Err(Error::new(
+                        ErrorKind::Link,
+                        format!(
+                            "link: conflicting definitions for label-only section .{}",
+                            sec.name
+                        ),
+                    )
+                    .with_note("a section with a :region places the labels of the objects one after the other"));
+                }
+                linked.reserved = linked.reserved.max(sec.reserved);
+                0
+            } else if linked.label_only {
+                let reservation = (sec.reserved, section_symbols(obj, &sec.name));
+                let section_reservations = reservations.entry(sec.name.as_str()).or_default();
+                match section_reservations.iter().find(|(r, _)| *r == reservation) {
+                    Some((_, base)) => *base,
+                    None => {
+                        let base = linked.reserved.next_multiple_of(sec.data_align);
+                        linked.reserved = base + sec.reserved;
+                        section_reservations.push((reservation, base));
+                        base
+                    }
+                }
+            } else {
+                data_index as u64
+            };
+            linked.data.extend_from_slice(&sec.data);
+            if let Some(length) = linked.length
+                && linked.label_only
+                && linked.reserved > length
+            {
+                return Err(Error::new(
+                    ErrorKind::Link,
+                    format!(
+                        "link: section .{} overflows, length 0x{:X} but 0x{:X} bytes reserved",
+                        linked.name, length, linked.reserved
+                    ),
+                ));
+            }
+            if let Some(length) = linked.length
+                && linked.data.len() as u64 > length
+            {
+                return Err(Error::new(
+                    ErrorKind::Link,
+                    format!(
+                        "link: section .{} overflows, length 0x{:X} but 0x{:X} bytes defined",
+                        linked.name,
+                        length,
+                        linked.data.len()
+                    ),
+                ));
+            }
+            obj_placements.insert(sec.name.as_str(), Placement { base, data_index });
+        }
+        placements.push(obj_placements);
+    }
+    place_sections(&mut sections, &regions)?;
+    let section_offsets: HashMap<String, u64> = sections
+        .iter()
+        .map(|sec| (sec.name.clone(), sec.offset.0))
+        .collect();
+
+    let mut label_addresses: HashMap<Label, Address> = HashMap::new();
+    let mut private_addresses: Vec<HashMap<Label, Address>> = vec![HashMap::new(); objects.len()];
+    for (obj_ix, obj) in objects.iter().enumerate() {
+        for sym in &obj.symbols {
+            let address = match &sym.section {
+                Some(sec_name) => {
+                    let placement = placements[obj_ix].get(sec_name.as_str()).ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Link,
+                            format!(
+                                "link: label '{} defined in unknown section .{}",
+                                sym.label.name(),
+                                sec_name
+                            ),
+                        )
+                    })?;
+                    Address(section_offsets[sec_name] + placement.base + sym.offset)
+                }
+                None => Address(sym.offset),
+            };
+
+            if is_private_label(&sym.label) {
+                private_addresses[obj_ix].insert(sym.label.clone(), address);
+                continue;
+            }
+
+            match label_addresses.get(&sym.label) {
+                Some(existing) if *existing != address => {
+                    return Err(Error::new(
+                        ErrorKind::DuplicateLabel,
+                        format!("link: duplicate label definition: '{}", sym.label.name()),
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    label_addresses.insert(sym.label.clone(), address);
+                }
+            }
+        }
+    }
+
+    // the constants are already replaced in the patch expressions
+    let const_values = HashMap::new();
+    for (obj_ix, obj) in objects.iter().enumerate() {
+        let mut obj_addresses = label_addresses.clone();
+        obj_addresses.extend(private_addresses[obj_ix].drain());
+        for patch in &obj.patches {
+            let placement = placements[obj_ix]
+                .get(patch.section.as_str())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Link,
+                        format!("link: patch in unknown section .{}", patch.section),
+                    )
+                })?;
+            let linked = sections
+                .iter_mut()
+                .find(|s| s.name == patch.section)
+                .expect("linked section");
+            let data_len = obj
+                .lookup_section(&patch.section)
+                .map_or(0, |sec| sec.data.len());
+            if patch.patch_index + patch.patch.width() > data_len {
+                return Err(Error::new(
+                    ErrorKind::Object,
+                    format!(
+                        "link: patch at index {} beyond the data of section .{} (0x{:X} bytes)",
+                        patch.patch_index, patch.section, data_len
+                    ),
+                ));
+            }
+            let ix = placement.data_index + patch.patch_index;
+            match &patch.patch {
+                Patch::Absolute16(exp) => {
+                    let address = eval(exp, &const_values, &obj_addresses)?;
+                    check_16_bit_address_range(address)?;
+                    let bytes = (address as u16).to_le_bytes();
+                    linked.data[ix] = bytes[0];
+                    linked.data[ix + 1] = bytes[1];
+                }
+                Patch::High8(exp) => {
+                    let address = eval(exp, &const_values, &obj_addresses)?;
+                    check_high_page_address(address)?;
+                    linked.data[ix] = address as u8;
+                }
+                Patch::Immediate8(exp) => {
+                    let v = eval(exp, &const_values, &obj_addresses)?;
+                    check_8_bit_value(v)?;
+                    linked.data[ix] = v as u8;
+                }
+                Patch::Immediate16(exp) => {
+                    let v = eval(exp, &const_values, &obj_addresses)?;
+                    check_16_bit_value(v)?;
+                    let bytes = (v as u16).to_le_bytes();
+                    linked.data[ix] = bytes[0];
+                    linked.data[ix + 1] = bytes[1];
+                }
+                Patch::Relative8(from, exp) => {
+                    let address = eval(exp, &const_values, &obj_addresses)?;
+                    let base = section_offsets[&patch.section] + placement.base;
+                    let dist = address as i32 - (base + from) as i32;
+                    check_jr_jump(dist)?;
+                    linked.data[ix] = dist as u8;
+                }
+            }
+        }
+    }
+
+    let mut symbols: Vec<ObjectSymbol> = label_addresses
+        .into_iter()
+        .map(|(label, address)| ObjectSymbol {
+            label,
+            section: None,
+            offset: address.0,
+        })
+        .collect();
+    symbols.sort_by(|a, b| a.label.name().cmp(b.label.name()));
+
+    Ok(Object {
+        sections,
+        regions,
+        symbols,
+        patches: Vec::new(),
+        gb_header: objects.iter().any(|obj| obj.gb_header),
+    })
+}
+
+/// All labels of a are labels of b, with the same offsets.
+fn is_subset(a: &[(&Label, u64)], b: &[(&Label, u64)]) -> bool {
+    a.iter().all(|symbol| b.contains(symbol))
+}
+
+/// The labels the object defines in the section, sorted by offset and name.
+fn section_symbols<'a>(obj: &'a Object, sec_name: &str) -> Vec<(&'a Label, u64)> {
+    let mut symbols: Vec<(&Label, u64)> = obj
+        .symbols
+        .iter()
+        .filter(|sym| sym.section.as_deref() == Some(sec_name))
+        .map(|sym| (&sym.label, sym.offset))
+        .collect();
+    symbols.sort_by(|a, b| (a.1, a.0.name()).cmp(&(b.1, b.0.name())));
+    symbols
+}
+
+/// The regions of all objects. The objects may define the same region, e.g.
+/// from a shared include, but the definitions must be identical.
+fn link_regions(objects: &[Object]) -> Result<Vec<Region>, Error> {
+    let mut regions: Vec<Region> = Vec::new();
+    for region in objects.iter().flat_map(|obj| &obj.regions) {
+        match regions
+            .iter()
+            .find(|r| r.name == region.name && r.bank == region.bank)
+        {
+            Some(existing) if existing != region => {
+                return Err(Error::new(
+                    ErrorKind::Link,
+                    format!("link: conflicting definitions for region {}", region.name),
+                ));
+            }
+            Some(_) => {}
+            None => regions.push(region.clone()),
+        }
+    }
+    Ok(regions)
+}
+
+/// Places the floating sections into the first free block of their region
+/// with the alignment, like the assembler places them in a flat assembly.
+fn place_sections(sections: &mut [ObjectSection], regions: &[Region]) -> Result<(), Error> {
+    let extent = |sec: &ObjectSection| match sec.length {
+        Some(length) => length,
+        None if sec.label_only => sec.reserved,
+        None => sec.data.len() as u64,
+    };
+    // the blocks taken by the fixed sections, in all banks
+    let mut taken: Vec<Block> = sections
+        .iter()
+        .filter(|sec| sec.floating.is_none())
+        .map(|sec| Block {
+            start: sec.offset.0,
+            end: sec.offset.0 + extent(sec),
+            bank: None,
+        })
+        .collect();
+
+    for sec in sections.iter_mut() {
+        let size = extent(sec);
+        let Some(floating) = &mut sec.floating else {
+            continue;
+        };
+        let block = find_free_block(regions, floating, size, &taken).ok_or_else(|| {
+            Error::new(
+                ErrorKind::Link,
+                format!(
+                    "link: section .{} (0x{:X} bytes, align {}) does not fit into region {}{}",
+                    sec.name,
+                    size,
+                    floating.align,
+                    floating.region,
+                    floating
+                        .bank
+                        .map(|bank| format!(" bank {}", bank))
+                        .unwrap_or_default()
+                ),
+            )
+        })?;
+        sec.offset = Address(block.start);
+        floating.bank = block.bank;
+        taken.push(block);
+    }
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use clap::{ArgAction, Args, Parser, Subcommand};
+use psy::{Error, ErrorKind};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
+use std::process::ExitCode;
 use std::str::FromStr;
 
 #[derive(Parser)]
@@ -40,10 +43,23 @@ struct Assemble {
     file: String,
     /// Will assemble a 'flat' binary. All sections have to be defined for this
     /// and the result is a direct binary output, not an object file that can be linked.
+    /// Without this flag a relocatable object file is written, see 'link'.
     #[clap(long, short, action=ArgAction::SetTrue)]
     flat: bool,
     #[clap(long, short, default_value = "a.out")]
     out: String,
+    /// Defines the constant +NAME+ with the value (1 if omitted), can be
+    /// given more than once. Test it with ifdef or use it in expressions.
+    #[clap(short = 'D', value_name = "NAME[=VALUE]")]
+    define: Vec<String>,
+    /// Adds the directory to the include search path, searched in the given
+    /// order after the directory of the including file.
+    #[clap(short = 'I', value_name = "DIR")]
+    include: Vec<PathBuf>,
+    /// The byte for the gaps between the sections and the unused bytes of
+    /// the sections in a flat binary, e.g. 0xFF.
+    #[clap(long, value_name = "BYTE", default_value = "0")]
+    fill: String,
 }
 
 #[derive(Args)]
@@ -59,59 +75,95 @@ enum LinkSubCommands {
 
 #[derive(Args)]
 struct LinkGB {
-    /// The input files to link
+    /// The object files to link
     file: Vec<String>,
+    #[clap(long, short, default_value = "a.gb")]
+    out: String,
+    /// The byte for the gaps between the sections and the unused bytes of
+    /// the sections, e.g. 0xFF.
+    #[clap(long, value_name = "BYTE", default_value = "0")]
+    fill: String,
 }
 
-fn main() -> Result<(), String> {
+fn main() -> ExitCode {
     let args = Cli::parse();
-    match &args.command {
+    let result = match &args.command {
         MainCommands::Disassemble(cmd) => match &cmd.command {
-            DisassembleSubCommands::GB(dis_gb_arg) => disassemble_gb(&dis_gb_arg),
+            DisassembleSubCommands::GB(dis_gb_arg) => disassemble_gb(dis_gb_arg),
         },
         MainCommands::Assemble(asm) => assemble(asm),
         MainCommands::Link(cmd) => match &cmd.command {
-            LinkSubCommands::GB(link_gb_arg) => link_gb(&link_gb_arg),
+            LinkSubCommands::GB(link_gb_arg) => link_gb(link_gb_arg),
         },
+    };
+    // printed as is, the errors may contain a source excerpt
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        return ExitCode::FAILURE;
     }
+    ExitCode::SUCCESS
 }
 
-fn assemble(arg: &Assemble) -> Result<(), String> {
-    if !arg.flat {
-        return Err("currently only flat mode assemble is supported".to_string());
+fn assemble(arg: &Assemble) -> Result<(), Error> {
+    let mut defines = HashMap::new();
+    for define in &arg.define {
+        let (name, value) = psy::asm::assembler::parse_define(define)?;
+        defines.insert(name, value);
     }
-
-    let mut file = File::open(&arg.file).map_err(|e| e.to_string())?;
+    let mut file = File::open(&arg.file).map_err(|e| Error::io(&arg.file, e))?;
+    let env = psy::asm::assembler::Environment {
+        defines,
+        include_paths: arg.include.clone(),
+        // the stdlib embedded in the binary if not set
+        stdlib: std::env::var_os("PSY_STDLIB").map(PathBuf::from),
+        fill: psy::asm::assembler::parse_fill(&arg.fill)?,
+    };
     let options = psy::asm::assembler::Options {
         flat: arg.flat,
         out: PathBuf::from_str(&arg.out).unwrap(),
+        env,
     };
-    psy::asm::assemble_file(&mut file, options)
+    psy::asm::assemble_file(&mut file, &arg.file, options)
 }
 
-fn disassemble_gb(arg: &DisassembleGB) -> Result<(), String> {
+fn disassemble_gb(arg: &DisassembleGB) -> Result<(), Error> {
     let data = read_all_from_file(&arg.file)?;
     let dis = psy::dasm::gb::disassemble(&data)?;
     for dis_instr in &dis.instructions {
-        println!("{}", dis_instr.instr.text(None))
+        println!("{}", dis_instr.text(&data))
     }
     Ok(())
 }
 
-fn link_gb(arg: &LinkGB) -> Result<(), String> {
-    // TODO
-    Ok(())
+fn link_gb(arg: &LinkGB) -> Result<(), Error> {
+    if arg.file.is_empty() {
+        return Err(Error::new(ErrorKind::Link, "link: no object files given"));
+    }
+
+    let fill = psy::asm::assembler::parse_fill(&arg.fill)?;
+    let mut objects = Vec::new();
+    for file_name in &arg.file {
+        let mut file = File::open(file_name).map_err(|e| Error::io(file_name, e))?;
+        let obj = psy::asm::object::read_from_file(&mut file, file_name)?;
+        objects.push(obj);
+    }
+
+    let rom = psy::link::gb::link(&objects, fill)?;
+    std::fs::write(&arg.out, rom).map_err(|e| Error::io(&arg.out, e))
 }
 
-fn read_all_from_file(file_def: &str) -> Result<Vec<u8>, String> {
+fn read_all_from_file(file_def: &str) -> Result<Vec<u8>, Error> {
     let mut buf = Vec::new();
     if file_def == "-" {
         let stdin = std::io::stdin();
         let mut handle = stdin.lock();
-        handle.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+        handle
+            .read_to_end(&mut buf)
+            .map_err(|e| Error::io("stdin", e))?;
     } else {
-        let mut file = File::open(file_def).map_err(|e| e.to_string())?;
-        file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+        let mut file = File::open(file_def).map_err(|e| Error::io(file_def, e))?;
+        file.read_to_end(&mut buf)
+            .map_err(|e| Error::io(file_def, e))?;
     }
     Ok(buf)
 }
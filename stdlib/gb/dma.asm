@@ -47,69 +47,9 @@
     :label-only true)
 (section .hw-ports)
 ('hw-joyp db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
-(db)
+(repeat 37 (db)) ; 0xFF01-0xFF25
 ('hw-sound db) ; aka NR52, 0xFF26
-(db) ;unused 0xFF27
-(db) ;unused 0xFF28
-(db) ;unused 0xFF29
-(db) ;unused 0xFF2A
-(db) ;unused 0xFF2B
-(db) ;unused 0xFF2C
-(db) ;unused 0xFF2D
-(db) ;unused 0xFF2E
-(db) ;unused 0xFF2F
-(db) ;0xFF30
-(db) ;0xFF31
-(db) ;0xFF32
-(db) ;0xFF33
-(db) ;0xFF34
-(db) ;0xFF35
-(db) ;0xFF36
-(db) ;0xFF37
-(db) ;0xFF38
-(db) ;0xFF39
-(db) ;0xFF3A
-(db) ;0xFF3B
-(db) ;0xFF3C
-(db) ;0xFF3D
-(db) ;0xFF3E
-(db) ;0xFF3F
+(repeat 25 (db)) ; 0xFF27-0xFF3F, wave RAM from 0xFF30
 ('hw-lcdc db) ;0xFF40
 (db) ;0xFF41
 (db) ;0xFF42
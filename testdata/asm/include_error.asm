@@ -0,0 +1,3 @@
+; included by the error location test, the ld is illegal
+(nop)
+  (ld %a %sp)